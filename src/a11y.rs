@@ -0,0 +1,45 @@
+//! System accessibility preferences
+//!
+//! winit doesn't report the desktop's reduced-motion or high-contrast
+//! settings, so on GNOME-based Linux desktops they're read with `gsettings`.
+//! Elsewhere they're unknown and Klippy's own settings decide.
+use std::process::Command;
+
+/// Accessibility preferences reported by the system, None where unknown
+#[derive(Clone, Copy, Default)]
+pub struct SystemPreferences {
+    /// Whether animations should be avoided
+    pub reduced_motion: Option<bool>,
+    /// Whether stronger contrast is wanted
+    pub high_contrast: Option<bool>,
+}
+
+impl SystemPreferences {
+    /// Query the system's preferences
+    pub fn detect() -> Self {
+        if !cfg!(target_os = "linux") {
+            return Self::default();
+        }
+        Self {
+            reduced_motion: gsettings("org.gnome.desktop.interface", "enable-animations")
+                .map(|enabled| !enabled),
+            high_contrast: gsettings("org.gnome.desktop.a11y.interface", "high-contrast"),
+        }
+    }
+}
+
+/// Read a boolean GNOME setting
+fn gsettings(schema: &str, key: &str) -> Option<bool> {
+    let output = Command::new("gsettings")
+        .args(["get", schema, key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
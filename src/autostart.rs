@@ -0,0 +1,40 @@
+//! Launch on login
+//!
+//! Registers the running executable to start when the user logs in, using
+//! the per-user mechanism of each platform (XDG autostart, Launch Agents, the
+//! `Run` registry key), so no elevated permissions are needed.
+use auto_launch::{AutoLaunch, AutoLaunchBuilder};
+use std::env;
+
+/// Returns true if launch on login is available on this platform
+pub fn is_supported() -> bool {
+    AutoLaunch::is_support()
+}
+
+/// Returns true if Klippy is currently registered to launch on login
+pub fn is_enabled() -> bool {
+    launcher()
+        .and_then(|launcher| launcher.is_enabled().map_err(|e| e.to_string()))
+        .unwrap_or(false)
+}
+
+/// Register or unregister Klippy to launch on login
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let launcher = launcher()?;
+    let result = if enabled {
+        launcher.enable()
+    } else {
+        launcher.disable()
+    };
+    result.map_err(|e| format!("Failed to update launch on login: {}", e))
+}
+
+/// Build the launcher for the running executable
+fn launcher() -> Result<AutoLaunch, String> {
+    let exe = env::current_exe().map_err(|e| format!("Failed to locate Klippy: {}", e))?;
+    AutoLaunchBuilder::new()
+        .set_app_name("Klippy")
+        .set_app_path(&exe.to_string_lossy())
+        .build()
+        .map_err(|e| format!("Launch on login unavailable: {}", e))
+}
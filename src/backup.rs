@@ -0,0 +1,66 @@
+//! Automatic backups of the data file
+//!
+//! Backups are timestamped copies of `data.json` kept in a `backups/` folder
+//! next to it. Only the newest few are kept.
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Returns the folder holding backups for the given data file
+pub fn backup_dir(save_path: &Path) -> PathBuf {
+    save_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+/// Copy the data file into the backup folder, keeping at most `keep` backups
+pub fn create_backup(save_path: &Path, keep: usize) -> Result<PathBuf, String> {
+    let dir = backup_dir(save_path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let target = dir.join(format!(
+        "data-{}.json",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::copy(save_path, &target).map_err(|e| format!("Failed to back up data: {}", e))?;
+
+    // Rotate out the oldest backups
+    for old in list_backups(save_path).into_iter().skip(keep.max(1)) {
+        let _ = fs::remove_file(old);
+    }
+
+    Ok(target)
+}
+
+/// List existing backups, newest first
+pub fn list_backups(save_path: &Path) -> Vec<PathBuf> {
+    let Ok(dir) = fs::read_dir(backup_dir(save_path)) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<PathBuf> = dir
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("data-") && name.ends_with(".json"))
+        })
+        .collect();
+
+    // Timestamped names sort chronologically
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Returns how long ago the newest backup was made, or None if there are none
+pub fn newest_backup_age(save_path: &Path) -> Option<Duration> {
+    let newest = list_backups(save_path).into_iter().next()?;
+    fs::metadata(newest)
+        .and_then(|meta| meta.modified())
+        .ok()?
+        .elapsed()
+        .ok()
+}
@@ -0,0 +1,70 @@
+//! Capture log
+//!
+//! An optional append-only record of what was captured, copied and removed,
+//! kept in `captures.log` next to the data file. Unlike the history it's
+//! never pruned entry by entry, so it still shows what was copied after the
+//! entry itself is gone. Each line holds a timestamp, the event and a short
+//! preview; full content is never written. Once the file reaches
+//! `MAX_BYTES` it's rotated to `captures.log.1`, replacing the previous
+//! rotation, so the log never takes more than twice that.
+use chrono::Local;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Name of the log file, inside the data directory
+pub const FILE: &str = "captures.log";
+/// Size at which the log is rotated
+const MAX_BYTES: u64 = 512 * 1024;
+/// Characters of the content written to the log
+pub const PREVIEW_CHARS: usize = 80;
+
+/// Something that happened to an entry
+#[derive(Clone, Copy)]
+pub enum Event {
+    /// New content was captured from the clipboard
+    Captured,
+    /// An entry was copied back to the clipboard
+    Copied,
+    /// An entry was moved to the trash
+    Deleted,
+    /// An entry was dropped to stay within the history limits
+    Evicted,
+}
+
+impl Event {
+    /// Name written to the log
+    fn label(&self) -> &'static str {
+        match self {
+            Event::Captured => "captured",
+            Event::Copied => "copied",
+            Event::Deleted => "deleted",
+            Event::Evicted => "evicted",
+        }
+    }
+}
+
+/// Append an event to the log in `dir`, rotating it first if it's full
+pub fn append(dir: &Path, event: Event, preview: &str) -> Result<(), String> {
+    let path = dir.join(FILE);
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() >= MAX_BYTES) {
+        let rotated = dir.join(format!("{}.1", FILE));
+        fs::rename(&path, &rotated)
+            .map_err(|e| format!("Failed to rotate {}: {}", path.display(), e))?;
+    }
+
+    // Keep every event on one line with tab-separated fields
+    let preview = preview.replace(['\t', '\r', '\n'], " ");
+    let line = format!(
+        "{}\t{}\t{}\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        event.label(),
+        preview
+    );
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,290 @@
+//! Command-line actions
+//!
+//! `klippy --copy-pinned <n>` copies the n-th pinned entry (counting from 1,
+//! in list order) and exits without opening the window. It's meant for
+//! desktop shortcuts, launcher entries and the Windows jump list (see
+//! `jump_list`), so a frequent clip is one click away without bringing up
+//! Klippy. On X11 the clipboard is served by whoever set it, so the text is
+//! handed to a background copy of Klippy that keeps serving it until
+//! something else is copied.
+//!
+//! `klippy --selftest` checks that the clipboard, the data directory and the
+//! data file format work, prints a report and exits. It leaves the saved
+//! data alone and puts the clipboard contents back afterwards.
+//!
+//! `--portable` can be added to any of these (or to a normal start) to keep
+//! data next to the executable; it's handled before parsing, as is the
+//! hidden `--demo` flag (see `demo`).
+use crate::{ClipboardEntry, ClipboardManager, StorageKind};
+use clipboard::{ClipboardContext, ClipboardProvider};
+use std::fs;
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+
+/// Hidden flag starting the background copy that serves the clipboard
+const SERVE_FLAG: &str = "--serve-clipboard";
+/// How often the background copy checks whether its text was replaced
+const SERVE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// An action requested on the command line
+pub enum Command {
+    /// Copy the pinned entry at this 1-based position
+    CopyPinned(usize),
+    /// Check the environment and print a report
+    SelfTest,
+    /// Copy the text read from stdin and serve it until it's replaced
+    ServeClipboard,
+}
+
+/// Parse the command line, returning None to start the app normally
+pub fn parse(args: &[String]) -> Result<Option<Command>, String> {
+    match args {
+        [] => Ok(None),
+        [flag, position] if flag == "--copy-pinned" => position
+            .parse()
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| Some(Command::CopyPinned(n)))
+            .ok_or_else(|| format!("Invalid position: {}", position)),
+        [flag] if flag == "--selftest" => Ok(Some(Command::SelfTest)),
+        [flag] if flag == SERVE_FLAG => Ok(Some(Command::ServeClipboard)),
+        _ => Err("Usage: klippy [--portable] [--copy-pinned <n> | --selftest]".to_owned()),
+    }
+}
+
+/// Run a command
+pub fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::CopyPinned(position) => {
+            let entries = ClipboardManager::new().entries;
+            let entry = entries
+                .iter()
+                .filter(|e| e.pinned)
+                .nth(position - 1)
+                .ok_or_else(|| format!("There is no pinned entry #{}", position))?;
+            copy(&entry.content)
+        }
+        Command::SelfTest => self_test(),
+        Command::ServeClipboard => {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| format!("Failed to read the text to copy: {}", e))?;
+            serve(&content)
+        }
+    }
+}
+
+/// Put `content` on the clipboard, leaving it there after Klippy exits
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+fn copy(content: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let serving = std::env::current_exe().and_then(|exe| {
+        let mut child = std::process::Command::new(exe)
+            .arg(SERVE_FLAG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        match child.stdin.take() {
+            Some(mut stdin) => stdin.write_all(content.as_bytes()),
+            None => Ok(()),
+        }
+    });
+    match serving {
+        Ok(()) => Ok(()),
+        // Without a background copy, serve the text from here instead
+        Err(e) => {
+            log::warn!(
+                "Failed to start serving the clipboard in the background: {}",
+                e
+            );
+            serve(content)
+        }
+    }
+}
+
+/// Put `content` on the clipboard, leaving it there after Klippy exits
+#[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android")))))]
+fn copy(content: &str) -> Result<(), String> {
+    let mut ctx: ClipboardContext =
+        ClipboardProvider::new().map_err(|e| format!("Clipboard unavailable: {}", e))?;
+    ctx.set_contents(content.to_owned())
+        .map_err(|e| format!("Failed to copy: {}", e))
+}
+
+/// Put `content` on the clipboard and keep serving it until it's replaced
+fn serve(content: &str) -> Result<(), String> {
+    let mut ctx: ClipboardContext =
+        ClipboardProvider::new().map_err(|e| format!("Clipboard unavailable: {}", e))?;
+    ctx.set_contents(content.to_owned())
+        .map_err(|e| format!("Failed to copy: {}", e))?;
+    while ctx.get_contents().is_ok_and(|current| current == content) {
+        thread::sleep(SERVE_POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+/// A self-test check, returning a detail on success or the problem found
+type Check = fn() -> Result<String, String>;
+
+/// Run every check, printing a line per check
+fn self_test() -> Result<(), String> {
+    let checks: [(&str, Check); 3] = [
+        ("Clipboard read/write", check_clipboard),
+        ("Data directory writable", check_data_dir),
+        ("Data file round-trip", check_round_trip),
+    ];
+
+    let mut failed = 0;
+    for (name, check) in checks {
+        match check() {
+            Ok(detail) => println!("PASS  {}: {}", name, detail),
+            Err(message) => {
+                failed += 1;
+                println!("FAIL  {}: {}", name, message);
+            }
+        }
+    }
+
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(format!("{} of {} checks failed", failed, checks.len()))
+    }
+}
+
+/// Set and read back a test string, then restore the previous contents
+fn check_clipboard() -> Result<String, String> {
+    let mut ctx: ClipboardContext =
+        ClipboardProvider::new().map_err(|e| format!("unavailable: {}", e))?;
+    let previous = ctx.get_contents().ok();
+
+    let probe = format!("klippy self-test {}", std::process::id());
+    let result = ctx
+        .set_contents(probe.clone())
+        .map_err(|e| format!("write failed: {}", e))
+        .and_then(|()| {
+            ctx.get_contents()
+                .map_err(|e| format!("read failed: {}", e))
+        })
+        .and_then(|read| {
+            if read == probe {
+                Ok("text copied and read back".to_owned())
+            } else {
+                Err("read back different text".to_owned())
+            }
+        });
+
+    if let Some(previous) = previous {
+        let _ = ctx.set_contents(previous);
+    }
+    result
+}
+
+/// Create and remove a scratch file in the data directory
+fn check_data_dir() -> Result<String, String> {
+    let (dir, kind) = crate::storage_dir().ok_or_else(|| "no usable data directory".to_owned())?;
+    let probe = dir.join(format!(".selftest-{}", std::process::id()));
+    fs::write(&probe, b"ok").map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+
+    let note = match kind {
+        StorageKind::Config => "",
+        StorageKind::Portable => " (portable)",
+        StorageKind::Fallback => " (fallback location)",
+        StorageKind::Demo => " (demo)",
+    };
+    Ok(format!("{}{}", dir.display(), note))
+}
+
+/// Serialize the saved data (or a sample) to a temp file and parse it back
+fn check_round_trip() -> Result<String, String> {
+    let saved = crate::storage_dir()
+        .map(|(dir, _)| dir.join("data.json"))
+        .filter(|path| path.exists());
+    let (app, source) = match &saved {
+        Some(path) => {
+            let data = fs::read_to_string(path)
+                .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+            let app: ClipboardManager = serde_json::from_str(&data)
+                .map_err(|e| format!("can't parse {}: {}", path.display(), e))?;
+            (app, "saved data")
+        }
+        None => {
+            let app = ClipboardManager {
+                entries: vec![ClipboardEntry::new(1, "self-test entry".to_owned())],
+                ..ClipboardManager::default()
+            };
+            (app, "sample data")
+        }
+    };
+
+    let path = std::env::temp_dir().join(format!("klippy-selftest-{}.json", std::process::id()));
+    let json = serde_json::to_string(&app).map_err(|e| format!("serialize failed: {}", e))?;
+    let written = fs::write(&path, &json).map_err(|e| format!("{}: {}", path.display(), e));
+    let reloaded = written.and_then(|()| {
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str::<ClipboardManager>(&data).map_err(|e| format!("parse failed: {}", e))
+    });
+    let _ = fs::remove_file(&path);
+
+    let reloaded = reloaded?;
+    let same = reloaded.entries.len() == app.entries.len()
+        && reloaded
+            .entries
+            .iter()
+            .zip(&app.entries)
+            .all(|(a, b)| a.id == b.id && a.content == b.content && a.pinned == b.pinned);
+    if same {
+        Ok(format!("{} entries from {}", app.entries.len(), source))
+    } else {
+        Err("entries changed after reloading".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| (*arg).to_owned()).collect()
+    }
+
+    #[test]
+    fn no_arguments_start_the_app() {
+        assert!(matches!(parse(&[]), Ok(None)));
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert!(matches!(
+            parse(&args(&["--copy-pinned", "3"])),
+            Ok(Some(Command::CopyPinned(3)))
+        ));
+        assert!(matches!(
+            parse(&args(&["--selftest"])),
+            Ok(Some(Command::SelfTest))
+        ));
+        assert!(matches!(
+            parse(&args(&[SERVE_FLAG])),
+            Ok(Some(Command::ServeClipboard))
+        ));
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        for invalid in [
+            &["--copy-pinned", "0"][..],
+            &["--copy-pinned", "first"],
+            &["--copy-pinned"],
+            &["--selftest", "now"],
+            &["--unknown"],
+        ] {
+            assert!(parse(&args(invalid)).is_err(), "accepted {:?}", invalid);
+        }
+    }
+}
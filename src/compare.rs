@@ -0,0 +1,84 @@
+//! Word-level comparison of two entries
+//!
+//! The diff is computed once when the window opens and kept as a layout job,
+//! with removed words struck through in red and added words in green.
+use egui::text::LayoutJob;
+use egui::{Color32, Context, FontId, RichText, ScrollArea, Stroke, TextFormat};
+use similar::{ChangeTag, TextDiff};
+use std::time::Duration;
+
+/// Largest entry that can be compared
+const MAX_COMPARE_BYTES: usize = 256 * 1024;
+/// Time after which the diff gives up on an optimal result and approximates
+const DIFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// State of an open comparison window
+pub struct CompareView {
+    /// Counts of added and removed words
+    summary: String,
+    /// The inline diff, ready to display
+    job: LayoutJob,
+}
+
+impl CompareView {
+    /// Compare an older and a newer text word by word
+    pub fn new(old: &str, new: &str) -> Result<Self, String> {
+        if old.len() > MAX_COMPARE_BYTES || new.len() > MAX_COMPARE_BYTES {
+            return Err("Entries are too large to compare".to_owned());
+        }
+
+        let diff = TextDiff::configure()
+            .timeout(DIFF_TIMEOUT)
+            .diff_words(old, new);
+
+        let font_id = FontId::monospace(13.0);
+        let mut job = LayoutJob::default();
+        let (mut added, mut removed) = (0, 0);
+        for change in diff.iter_all_changes() {
+            let value = change.value();
+            let is_word = !value.trim().is_empty();
+            let format = match change.tag() {
+                ChangeTag::Equal => TextFormat::simple(font_id.clone(), Color32::LIGHT_GRAY),
+                ChangeTag::Delete => {
+                    removed += usize::from(is_word);
+                    TextFormat {
+                        background: Color32::from_rgb(90, 30, 30),
+                        strikethrough: Stroke::new(1.0, Color32::LIGHT_RED),
+                        ..TextFormat::simple(font_id.clone(), Color32::LIGHT_RED)
+                    }
+                }
+                ChangeTag::Insert => {
+                    added += usize::from(is_word);
+                    TextFormat {
+                        background: Color32::from_rgb(30, 70, 30),
+                        ..TextFormat::simple(font_id.clone(), Color32::LIGHT_GREEN)
+                    }
+                }
+            };
+            job.append(value, 0.0, format);
+        }
+
+        Ok(Self {
+            summary: format!("{} words added · {} words removed", added, removed),
+            job,
+        })
+    }
+
+    /// Show the comparison window, returning whether it's still open
+    pub fn show(&self, ctx: &Context) -> bool {
+        let mut open = true;
+        egui::Window::new("⚖ Compare Entries")
+            .open(&mut open)
+            .default_size([560.0, 420.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.summary).weak());
+                ui.separator();
+                ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        ui.label(self.job.clone());
+                    });
+            });
+        open
+    }
+}
@@ -0,0 +1,95 @@
+//! Sample data for screenshots and demos
+//!
+//! `klippy --demo` starts with a fixed set of entries instead of the real
+//! history. They're written to a `klippy-demo` folder in the temp directory,
+//! replacing whatever an earlier demo left there, so every demo starts the
+//! same and the real data file is never read or written. The flag isn't
+//! listed in the usage text; it's handled before parsing, like `--portable`.
+use crate::{ClipboardEntry, ClipboardManager};
+use chrono::{Duration, Local, TimeZone};
+use std::fs;
+use std::path::PathBuf;
+
+/// Command-line flag that starts with the sample data
+pub const DEMO_FLAG: &str = "--demo";
+
+/// Sample entries, oldest first: content, minutes after the first one, pinned
+const SAMPLES: &[(&str, i64, bool)] = &[
+    ("git log --oneline --graph --decorate --all", 0, true),
+    ("https://github.com/ThembinkosiThemba/klippy", 12, false),
+    ("+1 (555) 013-2447", 25, false),
+    (
+        "Hi team,\n\nThe release notes are ready for review. Let me know if anything's missing before Friday.\n\nThanks!",
+        41,
+        false,
+    ),
+    ("2025-06-14", 58, false),
+    (
+        "{\n  \"name\": \"klippy\",\n  \"version\": \"1.0.0\",\n  \"features\": [\"search\", \"pins\", \"sync\"],\n  \"settings\": { \"max_entries\": 100, \"theme\": \"dark\" }\n}",
+        73,
+        false,
+    ),
+    (
+        "fn main() {\n    println!(\"Hello, clipboard!\");\n}",
+        90,
+        true,
+    ),
+    (
+        "name,role,city\nAda,Engineer,London\nGrace,Admiral,Arlington\nLinus,Maintainer,Portland",
+        104,
+        false,
+    ),
+    ("# Meeting notes\n\n- Ship the demo build\n- **Review** open issues\n- Plan the next sprint", 118, false),
+    ("https://docs.rs/egui/latest/egui/", 131, false),
+    ("ssh deploy@staging.example.com", 147, false),
+    ("Lorem ipsum dolor sit amet, consectetur adipiscing elit.", 160, false),
+];
+
+/// Returns true if `--demo` was passed
+pub fn is_demo() -> bool {
+    std::env::args().skip(1).any(|arg| arg == DEMO_FLAG)
+}
+
+/// Returns the folder the demo data is kept in
+pub fn data_dir() -> PathBuf {
+    std::env::temp_dir().join("klippy-demo")
+}
+
+/// Write a fresh demo data file, replacing any left from an earlier demo
+pub fn seed() -> Result<(), String> {
+    let dir = data_dir();
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .map_err(|e| format!("Failed to clear {}: {}", dir.display(), e))?;
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let app = ClipboardManager {
+        entries: entries(),
+        ..ClipboardManager::default()
+    };
+    let json = serde_json::to_string_pretty(&app).map_err(|e| e.to_string())?;
+    let path = dir.join("data.json");
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Build the sample entries, newest first like the history
+///
+/// Timestamps are fixed rather than relative to now, so screenshots taken on
+/// different days look the same.
+fn entries() -> Vec<ClipboardEntry> {
+    let start = Local
+        .with_ymd_and_hms(2025, 6, 2, 9, 0, 0)
+        .earliest()
+        .unwrap_or_else(Local::now);
+    SAMPLES
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, &(content, minutes, pinned))| ClipboardEntry {
+            timestamp: start + Duration::minutes(minutes),
+            pinned,
+            ..ClipboardEntry::new(index as u64 + 1, content.to_owned())
+        })
+        .collect()
+}
@@ -0,0 +1,116 @@
+//! Details panel
+//!
+//! Shows everything known about the selected entry in one place, so rows
+//! only need to carry a few badges. Only the selected entry is inspected,
+//! and its size counts are computed once rather than every frame.
+use crate::{format_size, ClipboardEntry};
+use egui::{RichText, Ui};
+
+/// Format of the timestamps shown in the panel
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Size counts of an entry's content
+pub struct Counts {
+    /// Entry the counts were taken from
+    id: u64,
+    /// Version of the content counted, so a change is noticed
+    version: u64,
+    /// Number of bytes
+    bytes: usize,
+    /// Number of characters
+    chars: usize,
+    /// Number of whitespace-separated words
+    words: usize,
+    /// Number of lines
+    lines: usize,
+}
+
+impl Counts {
+    /// Count the characters, words and lines of an entry
+    pub fn of(entry: &ClipboardEntry) -> Self {
+        let content = &entry.content;
+        Self {
+            id: entry.id,
+            version: entry.version,
+            bytes: content.len(),
+            chars: content.chars().count(),
+            words: content.split_whitespace().count(),
+            lines: content.lines().count(),
+        }
+    }
+
+    /// Returns true if these counts are still current for the entry
+    pub fn is_for(&self, entry: &ClipboardEntry) -> bool {
+        self.id == entry.id && self.version == entry.version
+    }
+}
+
+/// Show the details of an entry
+pub fn show(ui: &mut Ui, entry: &ClipboardEntry, counts: &Counts) {
+    egui::Grid::new("entry_details")
+        .num_columns(2)
+        .spacing([12.0, 6.0])
+        .striped(true)
+        .show(ui, |ui| {
+            row(
+                ui,
+                "Captured",
+                entry.timestamp.format(TIME_FORMAT).to_string(),
+            );
+            row(
+                ui,
+                "Last used",
+                entry.last_used.map_or_else(
+                    || "never".to_owned(),
+                    |time| time.format(TIME_FORMAT).to_string(),
+                ),
+            );
+            row(ui, "Copied", format!("{} times", entry.use_count));
+            if let Some(until) = entry.pin_until {
+                row(ui, "Pinned until", until.format(TIME_FORMAT).to_string());
+            }
+            if entry.occurrences > 1 {
+                row(
+                    ui,
+                    "Captured again",
+                    format!("{} times", entry.occurrences - 1),
+                );
+            }
+            if entry.revisions > 0 {
+                row(ui, "Revisions", entry.revisions.to_string());
+            }
+            row(ui, "Type", entry.kind().label().to_owned());
+            row(ui, "Characters", counts.chars.to_string());
+            row(ui, "Words", counts.words.to_string());
+            row(ui, "Lines", counts.lines.to_string());
+            row(ui, "Size", format_size(counts.bytes));
+
+            let flags: Vec<&str> = [
+                (entry.pinned, "📌 pinned"),
+                (entry.local_only, "🏠 local"),
+                (entry.one_shot, "🔥 one-shot"),
+                (entry.locked, "🔒 locked"),
+                (entry.clear_after_copy, "⏲ clears clipboard"),
+                (entry.raw.is_some(), "✂ trimmed"),
+                (entry.lossy, "� invalid bytes replaced"),
+            ]
+            .into_iter()
+            .filter_map(|(set, label)| set.then_some(label))
+            .collect();
+            if !flags.is_empty() {
+                row(ui, "Flags", flags.join("\n"));
+            }
+
+            let rich_formats = entry.rich_formats();
+            if !rich_formats.is_empty() {
+                row(ui, "Also offered", rich_formats.join("\n"));
+            }
+        });
+}
+
+/// Add a labelled row to the details grid
+fn row(ui: &mut Ui, label: &str, value: String) {
+    ui.label(RichText::new(label).weak());
+    ui.label(value);
+    ui.end_row();
+}
@@ -0,0 +1,53 @@
+//! Errors shared across the app
+//!
+//! Failures that the user should hear about are logged and shown in the
+//! status bar. Their messages read as full sentences, so they can go straight
+//! into either. Functions that only ever report a message still return
+//! `Result<_, String>`; `?` converts an `Error` into one.
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Something that went wrong while reading, writing or using the clipboard
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read
+    Read(PathBuf, io::Error),
+    /// A file couldn't be written
+    Write(PathBuf, io::Error),
+    /// Data couldn't be converted to or from JSON
+    Json(serde_json::Error),
+    /// The system clipboard couldn't be opened, read or written
+    Clipboard(String),
+    /// The clipboard still didn't hold what was copied after a retry
+    Unchanged,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read(path, e) => write!(f, "Failed to read {}: {}", path.display(), e),
+            Error::Write(path, e) => write!(f, "Failed to save {}: {}", path.display(), e),
+            Error::Json(e) => write!(f, "Failed to process data: {}", e),
+            Error::Clipboard(reason) => write!(f, "Clipboard unavailable: {}", reason),
+            Error::Unchanged => write!(
+                f,
+                "Copy didn't reach the clipboard: it still holds something else"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<Error> for String {
+    fn from(e: Error) -> Self {
+        e.to_string()
+    }
+}
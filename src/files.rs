@@ -0,0 +1,96 @@
+//! Acting on file-path entries
+//!
+//! Entries detected as a local path can be opened in their default app or
+//! shown in the file manager. Detection only looks at the text, so the path
+//! is resolved and checked here, when the user acts on it: `~/` is expanded
+//! and `file://` links are decoded.
+use directories::UserDirs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the file an entry's content points to, if it exists
+pub fn resolve(content: &str) -> Result<PathBuf, String> {
+    let text = content.trim();
+    let path = if let Some(rest) = text.strip_prefix("file://") {
+        let decoded = decode(rest);
+        // `file:///C:/...` names a drive on Windows, not a folder under `/`
+        if cfg!(windows) {
+            PathBuf::from(decoded.trim_start_matches('/'))
+        } else {
+            PathBuf::from(decoded)
+        }
+    } else if let Some(rest) = text.strip_prefix("~/") {
+        let home = UserDirs::new().ok_or("Home folder not found")?;
+        home.home_dir().join(rest)
+    } else {
+        PathBuf::from(text)
+    };
+    if path.exists() {
+        Ok(path)
+    } else {
+        Err(format!("{} doesn't exist", path.display()))
+    }
+}
+
+/// Open a file or folder in its default app
+pub fn open(path: &Path) -> Result<(), String> {
+    open::that(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Show a file selected in the file manager
+///
+/// Linux file managers have no common way to select a file, so its folder is
+/// opened instead.
+pub fn reveal(path: &Path) -> Result<(), String> {
+    let failed = |e: std::io::Error| format!("Failed to show {}: {}", path.display(), e);
+    if cfg!(target_os = "macos") {
+        Command::new("open")
+            .arg("-R")
+            .arg(path)
+            .spawn()
+            .map(drop)
+            .map_err(failed)
+    } else if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        Command::new("explorer")
+            .arg(select)
+            .spawn()
+            .map(drop)
+            .map_err(failed)
+    } else {
+        let folder = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        open::that(folder).map_err(failed)
+    }
+}
+
+/// Decode the `%XX` escapes in a `file://` link
+///
+/// Bytes that don't make valid UTF-8 are replaced, like other invalid text.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,49 @@
+//! The application in the foreground
+//!
+//! Used to decide whether a capture should be kept based on where it was
+//! copied from. On X11 the active window's `WM_CLASS` is read with `xprop`.
+//! Other platforms (and Wayland, which doesn't expose the active window) are
+//! not supported, so captures are never filtered there.
+use std::process::Command;
+
+/// Returns true if the foreground application can be queried on this platform
+pub fn is_supported() -> bool {
+    cfg!(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    )) && std::env::var_os("DISPLAY").is_some()
+}
+
+/// Returns the names identifying the foreground application, e.g.
+/// `["keepassxc", "KeePassXC"]`, or None if it can't be determined
+pub fn active_app() -> Option<Vec<String>> {
+    if !is_supported() {
+        return None;
+    }
+
+    // _NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007
+    let root = xprop(&["-root", "_NET_ACTIVE_WINDOW"])?;
+    let window = root.split_whitespace().last()?.trim_end_matches(',');
+    if window == "0x0" {
+        return None;
+    }
+
+    // WM_CLASS(STRING) = "keepassxc", "KeePassXC"
+    let class = xprop(&["-id", window, "WM_CLASS"])?;
+    let (_, names) = class.split_once('=')?;
+    let names: Vec<String> = names
+        .split(',')
+        .map(|name| name.trim().trim_matches('"').to_owned())
+        .filter(|name| !name.is_empty())
+        .collect();
+    (!names.is_empty()).then_some(names)
+}
+
+/// Run `xprop` with the given arguments and return its output
+fn xprop(args: &[&str]) -> Option<String> {
+    let output = Command::new("xprop").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,171 @@
+//! Formats offered by the clipboard
+//!
+//! Apps usually put several flavors of the same copy on the clipboard (plain
+//! text, HTML, an image...). Only the plain text is stored, but the other
+//! formats are recorded with the entry so the UI can say richer content was
+//! available. On Wayland they're listed with `wl-paste --list-types`, on X11
+//! with `xclip`. Other platforms aren't supported, so nothing is recorded.
+//!
+//! Listing runs an external tool, so it's done on a background thread once
+//! the entry has been captured, and the formats are filled in when it's done.
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// X11 targets describing the selection itself rather than a format
+const META_TARGETS: &[&str] = &["TARGETS", "TIMESTAMP", "MULTIPLE", "SAVE_TARGETS", "DELETE"];
+
+/// Formats that are plain text in some encoding
+const TEXT_FORMATS: &[&str] = &[
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+    "COMPOUND_TEXT",
+    "text/plain",
+];
+
+/// Formats of a captured entry being listed on a background thread
+pub struct Listing {
+    /// Id of the entry the formats belong to
+    pub id: u64,
+    result: Receiver<Vec<String>>,
+}
+
+impl Listing {
+    /// Start listing the clipboard's formats for entry `id`, repainting `ctx`
+    /// once they're listed
+    pub fn start(id: u64, ctx: &egui::Context) -> Self {
+        let (sender, result) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let _ = sender.send(available());
+            ctx.request_repaint();
+        });
+        Listing { id, result }
+    }
+
+    /// Returns the formats once they've been listed
+    ///
+    /// A listing that stopped without an answer gives an empty list, the
+    /// same as one that couldn't determine the formats.
+    pub fn poll(&self) -> Option<Vec<String>> {
+        match self.result.try_recv() {
+            Ok(formats) => Some(formats),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Vec::new()),
+        }
+    }
+}
+
+/// Returns true if formats can be listed on this platform
+pub fn is_supported() -> bool {
+    cfg!(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android"))
+    ))
+}
+
+/// Returns the formats on the clipboard right now, or an empty list if they
+/// can't be determined
+fn available() -> Vec<String> {
+    if !is_supported() {
+        return Vec::new();
+    }
+
+    let listing = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        run("wl-paste", &["--list-types"])
+    } else if std::env::var_os("DISPLAY").is_some() {
+        run("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"])
+    } else {
+        None
+    };
+
+    let mut formats: Vec<String> = Vec::new();
+    for format in listing.unwrap_or_default().lines().map(str::trim) {
+        if !format.is_empty()
+            && !META_TARGETS.contains(&format)
+            && !formats.iter().any(|f| f == format)
+        {
+            formats.push(format.to_owned());
+        }
+    }
+    formats
+}
+
+/// Returns the clipboard's text as raw bytes, without assuming an encoding,
+/// or None if it can't be read this way
+pub fn read_raw() -> Option<Vec<u8>> {
+    if !is_supported() {
+        return None;
+    }
+
+    let (program, args): (&str, &[&str]) = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-paste", &["--no-newline"])
+    } else if std::env::var_os("DISPLAY").is_some() {
+        ("xclip", &["-selection", "clipboard", "-o"])
+    } else {
+        return None;
+    };
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then_some(output.stdout)
+}
+
+/// Decode clipboard bytes as UTF-8, replacing invalid sequences with `�`
+///
+/// Returns the text and whether anything had to be replaced.
+pub fn decode(bytes: Vec<u8>) -> (String, bool) {
+    match String::from_utf8(bytes) {
+        Ok(text) => (text, false),
+        Err(error) => (String::from_utf8_lossy(error.as_bytes()).into_owned(), true),
+    }
+}
+
+/// Returns true if the format is plain text, e.g. `UTF8_STRING` or
+/// `text/plain;charset=utf-8`
+pub fn is_plain_text(format: &str) -> bool {
+    let base = format.split(';').next().unwrap_or_default().trim();
+    TEXT_FORMATS.contains(&base)
+}
+
+/// Run a command and return its stdout if it succeeded
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_text_is_unchanged() {
+        assert_eq!(decode("héllo ✓".into()), ("héllo ✓".to_owned(), false));
+    }
+
+    #[test]
+    fn invalid_bytes_are_replaced() {
+        assert_eq!(decode(b"a\xffb\xfe".to_vec()), ("a�b�".to_owned(), true));
+    }
+
+    #[test]
+    fn truncated_sequences_are_replaced() {
+        // "é" is C3 A9 and "€" is E2 82 AC; both cut short here
+        assert_eq!(decode(b"caf\xc3".to_vec()), ("caf�".to_owned(), true));
+        assert_eq!(
+            decode(b"\xe2\x82 euro".to_vec()),
+            ("� euro".to_owned(), true)
+        );
+    }
+
+    #[test]
+    fn lone_surrogates_are_replaced() {
+        // U+D800 encoded as if it were a character, as CESU-8 and WTF-8 do
+        let (text, lossy) = decode(b"x\xed\xa0\x80y".to_vec());
+        assert!(lossy);
+        assert!(text.starts_with('x') && text.ends_with('y'));
+        assert!(text.contains('�'));
+    }
+}
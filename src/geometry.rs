@@ -0,0 +1,128 @@
+//! Window geometry persistence
+//!
+//! The window's position and size are saved with the rest of the data and
+//! restored on the next launch, provided the saved position is still on one of
+//! the connected monitors.
+use display_info::DisplayInfo;
+use egui::Rect;
+use serde::{Deserialize, Serialize};
+
+/// Minimum visible width/height (in points) for a restored window to count as on screen
+const MIN_VISIBLE: f32 = 48.0;
+
+/// Saved window position and size, in points
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Left edge of the outer window
+    pub x: f32,
+    /// Top edge of the outer window
+    pub y: f32,
+    /// Width of the window content
+    pub width: f32,
+    /// Height of the window content
+    pub height: f32,
+}
+
+impl WindowGeometry {
+    /// Build the geometry from the viewport's outer and inner rectangles
+    pub fn from_rects(outer: Rect, inner: Rect) -> Self {
+        Self {
+            x: outer.min.x,
+            y: outer.min.y,
+            width: inner.width(),
+            height: inner.height(),
+        }
+    }
+
+    /// Returns true if enough of the window would be visible on a connected monitor
+    ///
+    /// Monitors can change between sessions, e.g. after unplugging an external
+    /// display. If the monitors can't be queried the position is treated as
+    /// invalid so the window falls back to the primary display.
+    pub fn is_on_screen(&self) -> bool {
+        let Ok(displays) = DisplayInfo::all() else {
+            return false;
+        };
+        let monitors: Vec<Rect> = displays
+            .iter()
+            .map(|d| monitor_rect(d.x, d.y, d.width, d.height, pixels_per_point(d)))
+            .collect();
+        self.is_visible_on(&monitors)
+    }
+
+    /// Returns true if enough of the window would be visible on one of `monitors`
+    fn is_visible_on(&self, monitors: &[Rect]) -> bool {
+        let window = Rect::from_min_size(
+            egui::pos2(self.x, self.y),
+            egui::vec2(self.width, self.height),
+        );
+        monitors.iter().any(|&monitor| {
+            let visible = window.intersect(monitor);
+            visible.width() >= MIN_VISIBLE && visible.height() >= MIN_VISIBLE
+        })
+    }
+}
+
+/// Returns how many of a display's reported units make up one point
+///
+/// Windows reports displays in physical pixels. On Linux and macOS they're
+/// already scaled to points.
+fn pixels_per_point(display: &DisplayInfo) -> f32 {
+    if cfg!(windows) {
+        display.scale_factor
+    } else {
+        1.0
+    }
+}
+
+/// Returns a monitor's area in points, like the saved geometry, from its
+/// position and size in units of `scale_factor` per point
+fn monitor_rect(x: i32, y: i32, width: u32, height: u32, scale_factor: f32) -> Rect {
+    let scale = if scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    };
+    Rect::from_min_size(
+        egui::pos2(x as f32, y as f32),
+        egui::vec2(width as f32, height as f32),
+    ) / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(x: f32, y: f32) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width: 400.0,
+            height: 300.0,
+        }
+    }
+
+    #[test]
+    fn monitors_are_measured_in_points() {
+        let monitor = monitor_rect(3840, 0, 3840, 2160, 2.0);
+        assert_eq!(
+            monitor,
+            Rect::from_min_size(egui::pos2(1920.0, 0.0), egui::vec2(1920.0, 1080.0))
+        );
+    }
+
+    #[test]
+    fn scaled_monitor_rejects_positions_beyond_its_points() {
+        let monitors = [monitor_rect(0, 0, 3840, 2160, 2.0)];
+        assert!(geometry(100.0, 100.0).is_visible_on(&monitors));
+        // On screen in pixels, but past the monitor's 1920x1080 points
+        assert!(!geometry(2500.0, 1500.0).is_visible_on(&monitors));
+    }
+
+    #[test]
+    fn window_must_be_visible_enough() {
+        let monitors = [monitor_rect(0, 0, 1920, 1080, 1.0)];
+        assert!(geometry(1500.0, 700.0).is_visible_on(&monitors));
+        assert!(!geometry(1900.0, 100.0).is_visible_on(&monitors));
+    }
+}
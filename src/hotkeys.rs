@@ -0,0 +1,81 @@
+//! Global hotkeys
+//!
+//! Hotkeys registered here work while Klippy is unfocused or hidden. Events
+//! arrive on a background thread, so they're forwarded through a channel and
+//! wake the UI with a repaint to be handled in `update`.
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver};
+
+/// Actions that can be bound to a global hotkey
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HotkeyAction {
+    /// Paste the current paste ring entry and advance the ring
+    PasteRing,
+    /// Show and focus the window after it was hidden
+    ShowWindow,
+    /// Copy the entry bound to this numbered slot (1-9)
+    CopySlot(u8),
+    /// Hide the window and clear the system clipboard
+    Panic,
+}
+
+/// Registered global hotkeys and the events received for them
+pub struct Hotkeys {
+    manager: GlobalHotKeyManager,
+    events: Receiver<GlobalHotKeyEvent>,
+    bindings: HashMap<u32, (HotKey, HotkeyAction)>,
+}
+
+impl Hotkeys {
+    /// Start listening for global hotkeys, waking the given context on events
+    pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+        let manager =
+            GlobalHotKeyManager::new().map_err(|e| format!("Global hotkeys unavailable: {}", e))?;
+
+        let (sender, events) = mpsc::channel();
+        let ctx = ctx.clone();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event| {
+            let _ = sender.send(event);
+            ctx.request_repaint();
+        }));
+
+        Ok(Self {
+            manager,
+            events,
+            bindings: HashMap::new(),
+        })
+    }
+
+    /// Bind an action to a hotkey such as `ctrl+alt+KeyV`
+    pub fn register(&mut self, hotkey: &str, action: HotkeyAction) -> Result<(), String> {
+        let hotkey: HotKey = hotkey
+            .parse()
+            .map_err(|e| format!("Invalid hotkey {}: {}", hotkey, e))?;
+        self.manager
+            .register(hotkey)
+            .map_err(|e| format!("Failed to register {}: {}", hotkey, e))?;
+        self.bindings.insert(hotkey.id(), (hotkey, action));
+        Ok(())
+    }
+
+    /// Remove every registered hotkey
+    pub fn unregister_all(&mut self) {
+        for (_, (hotkey, _)) in self.bindings.drain() {
+            let _ = self.manager.unregister(hotkey);
+        }
+    }
+
+    /// Returns the actions whose hotkeys were released since the last poll
+    ///
+    /// Actions fire on release so that the hotkey's modifiers are no longer
+    /// held when an action simulates key presses of its own.
+    pub fn poll(&self) -> Vec<HotkeyAction> {
+        self.events
+            .try_iter()
+            .filter(|event| event.state == HotKeyState::Released)
+            .filter_map(|event| self.bindings.get(&event.id).map(|(_, action)| *action))
+            .collect()
+    }
+}
@@ -0,0 +1,128 @@
+//! Preview of a merge import
+//!
+//! Lists the entries read from a file before they're merged, flagging those
+//! already in the history, so the user can untick anything they don't want.
+//! Duplicates start unticked and can't be selected, since the merge would
+//! skip them anyway.
+use crate::ClipboardEntry;
+use egui::{Context, RichText};
+
+/// An incoming entry and whether it will be merged
+struct Row {
+    entry: ClipboardEntry,
+    /// Whether the content is already in the history
+    duplicate: bool,
+    /// Whether the user wants it merged
+    selected: bool,
+}
+
+/// State of the open import preview
+pub struct ImportPreview {
+    rows: Vec<Row>,
+    /// Number of incoming entries already in the history
+    duplicates: usize,
+}
+
+impl ImportPreview {
+    /// Prepare a preview, using `is_duplicate` to flag entries already present
+    pub fn new(incoming: Vec<ClipboardEntry>, is_duplicate: impl Fn(&str) -> bool) -> Self {
+        let rows: Vec<Row> = incoming
+            .into_iter()
+            .filter(|entry| !entry.content.trim().is_empty())
+            .map(|entry| {
+                let duplicate = is_duplicate(&entry.content);
+                Row {
+                    entry,
+                    duplicate,
+                    selected: !duplicate,
+                }
+            })
+            .collect();
+        let duplicates = rows.iter().filter(|row| row.duplicate).count();
+        Self { rows, duplicates }
+    }
+
+    /// Show the preview window
+    ///
+    /// Returns Some(true) once the user confirms the merge, Some(false) if
+    /// they cancel, and None while it's still open.
+    pub fn show(&mut self, ctx: &Context, preview_chars: usize) -> Option<bool> {
+        let new = self.rows.len() - self.duplicates;
+        let selected = self.rows.iter().filter(|row| row.selected).count();
+        let mut result = None;
+
+        egui::Window::new("📥 Import Preview")
+            .collapsible(false)
+            .default_size([480.0, 400.0])
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} new entries, {} already in your history",
+                    new, self.duplicates
+                ));
+                ui.horizontal(|ui| {
+                    if ui.small_button("Select all new").clicked() {
+                        for row in &mut self.rows {
+                            row.selected = !row.duplicate;
+                        }
+                    }
+                    if ui.small_button("Select none").clicked() {
+                        for row in &mut self.rows {
+                            row.selected = false;
+                        }
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for row in &mut self.rows {
+                            ui.horizontal(|ui| {
+                                let preview = row.entry.preview(preview_chars);
+                                if row.duplicate {
+                                    ui.add_enabled(
+                                        false,
+                                        egui::Checkbox::new(&mut row.selected, preview),
+                                    );
+                                    ui.label(RichText::new("duplicate").small().weak());
+                                } else {
+                                    ui.checkbox(&mut row.selected, preview);
+                                }
+                                if row.entry.pinned {
+                                    ui.label("📌");
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            selected > 0,
+                            egui::Button::new(format!("📥 Import {}", selected)),
+                        )
+                        .clicked()
+                    {
+                        result = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        result = Some(false);
+                    }
+                });
+            });
+
+        result
+    }
+
+    /// Returns the entries the user left selected
+    pub fn into_selected(self) -> Vec<ClipboardEntry> {
+        self.rows
+            .into_iter()
+            .filter(|row| row.selected)
+            .map(|row| row.entry)
+            .collect()
+    }
+}
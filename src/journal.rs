@@ -0,0 +1,312 @@
+//! JSON Lines journal
+//!
+//! In the JSON Lines storage format, saving appends only what changed to
+//! `data.jsonl` next to `data.json` instead of rewriting the whole file:
+//! one line per changed entry, one per removed entry, and one for the rest
+//! of the state when it changes. `data.json` stays the snapshot everything
+//! else reads (backups, sync and the command line). Once the journal
+//! reaches `MAX_BYTES`, or on exit, a full snapshot is written and the
+//! journal emptied.
+//!
+//! The journal starts with the generation of the snapshot it follows.
+//! Loading replays it over the snapshot only if the generations match, so a
+//! journal left behind by a crash while a new snapshot replaced it is
+//! ignored rather than undoing newer changes. A crash mid-write leaves at
+//! most a truncated last line, which is skipped.
+use crate::error::Error;
+use crate::{ClipboardEntry, ClipboardManager};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Size at which the journal is folded into a new snapshot
+const MAX_BYTES: u64 = 256 * 1024;
+
+/// A single journal line
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Line {
+    /// The snapshot generation the lines that follow apply to
+    Snapshot(u64),
+    /// An entry that was added or changed
+    Entry(ClipboardEntry),
+    /// The id of an entry that left the history
+    Removed(u64),
+    /// Everything except the entries
+    State(Box<ClipboardManager>),
+}
+
+/// What the journal and snapshot on disk hold, to work out what changed
+pub struct Journal {
+    /// Hash of each entry's line, by id
+    entries: HashMap<u64, u64>,
+    /// Hash of the state line
+    state: u64,
+    /// Size of the journal file
+    bytes: u64,
+    /// Generation of the snapshot the journal follows
+    generation: u64,
+}
+
+/// Returns the journal file that goes with a data file
+pub fn path_for(save_path: &Path) -> PathBuf {
+    save_path.with_extension("jsonl")
+}
+
+/// Record what a freshly written snapshot of `app` holds
+pub fn after_snapshot(app: &mut ClipboardManager) -> Result<Journal, serde_json::Error> {
+    let mut entries = HashMap::with_capacity(app.entries.len());
+    for entry in &app.entries {
+        entries.insert(entry.id, hash(&serde_json::to_string(entry)?));
+    }
+    Ok(Journal {
+        entries,
+        state: hash(&state_json(app)?),
+        bytes: 0,
+        generation: app.snapshot_generation,
+    })
+}
+
+/// Returns true if the journal should be folded into a new snapshot
+pub fn is_full(journal: &Journal) -> bool {
+    journal.bytes >= MAX_BYTES
+}
+
+/// Append what changed in `app` since the last write
+pub fn append(journal: &mut Journal, app: &mut ClipboardManager, path: &Path) -> Result<(), Error> {
+    let mut lines = String::new();
+    let mut current = HashMap::with_capacity(app.entries.len());
+    let header = serde_json::to_string(&Line::Snapshot(journal.generation))? + "\n";
+    for entry in &app.entries {
+        let json = serde_json::to_string(entry)?;
+        let hash = hash(&json);
+        current.insert(entry.id, hash);
+        if journal.entries.get(&entry.id) != Some(&hash) {
+            lines.push_str(&format!("{{\"entry\":{}}}\n", json));
+        }
+    }
+    for id in journal
+        .entries
+        .keys()
+        .filter(|id| !current.contains_key(id))
+    {
+        lines.push_str(&serde_json::to_string(&Line::Removed(*id))?);
+        lines.push('\n');
+    }
+    let state = state_json(app)?;
+    let state_hash = hash(&state);
+    if state_hash != journal.state {
+        lines.push_str(&format!("{{\"state\":{}}}\n", state));
+    }
+    if lines.is_empty() {
+        return Ok(());
+    }
+    if journal.bytes == 0 {
+        lines.insert_str(0, &header);
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| Error::Write(path.to_path_buf(), e))?;
+    journal.entries = current;
+    journal.state = state_hash;
+    journal.bytes += lines.len() as u64;
+    Ok(())
+}
+
+/// Apply the journal at `path` to a loaded snapshot, returning the number of
+/// lines applied
+///
+/// Lines that don't parse, such as one cut off by a crash, are skipped, as
+/// are lines written after an older snapshot than the loaded one.
+pub fn replay(loaded: &mut ClipboardManager, path: &Path) -> usize {
+    let Ok(data) = fs::read_to_string(path) else {
+        return 0;
+    };
+    let generation = loaded.snapshot_generation;
+    let mut current = false;
+    let mut applied = 0;
+    let mut stale = 0;
+    for text in data.lines().filter(|text| !text.trim().is_empty()) {
+        let Ok(line) = serde_json::from_str::<Line>(text) else {
+            log::warn!("Skipped a damaged line in {}", path.display());
+            continue;
+        };
+        if let Line::Snapshot(written_after) = line {
+            current = written_after == generation;
+            continue;
+        }
+        if !current {
+            stale += 1;
+            continue;
+        }
+        match line {
+            Line::Snapshot(_) => {}
+            Line::Entry(entry) => match loaded.entries.iter_mut().find(|e| e.id == entry.id) {
+                Some(existing) => *existing = entry,
+                None => loaded.entries.push(entry),
+            },
+            Line::Removed(id) => loaded.entries.retain(|e| e.id != id),
+            Line::State(state) => {
+                let entries = std::mem::take(&mut loaded.entries);
+                let next_id = loaded.next_id;
+                *loaded = *state;
+                loaded.entries = entries;
+                loaded.next_id = next_id;
+            }
+        }
+        applied += 1;
+    }
+    if stale > 0 {
+        log::warn!(
+            "Ignored {} lines of {} left from an older snapshot",
+            stale,
+            path.display()
+        );
+    }
+    applied
+}
+
+/// Serialize everything except the entries
+///
+/// The next id is left out too, or every capture would add a state line;
+/// loading works it out again from the entries.
+fn state_json(app: &mut ClipboardManager) -> Result<String, serde_json::Error> {
+    let entries = std::mem::take(&mut app.entries);
+    let next_id = std::mem::take(&mut app.next_id);
+    let json = serde_json::to_string(app);
+    app.entries = entries;
+    app.next_id = next_id;
+    json
+}
+
+/// Hash a serialized line
+fn hash(json: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    json.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StorageFormat;
+
+    /// A manager saving in the JSON Lines format to a fresh folder
+    fn manager(name: &str) -> ClipboardManager {
+        let dir = std::env::temp_dir().join("klippy-journal-tests").join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut app = ClipboardManager {
+            save_path: Some(dir.join("data.json")),
+            ..ClipboardManager::default()
+        };
+        app.settings.storage_format = StorageFormat::JsonLines;
+        app
+    }
+
+    /// Load the data file and journal the way startup does
+    fn load(app: &ClipboardManager) -> ClipboardManager {
+        let path = app.save_path.as_ref().unwrap();
+        let mut loaded: ClipboardManager =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        replay(&mut loaded, &path_for(path));
+        let mut restored = ClipboardManager::default();
+        restored.apply_loaded(loaded);
+        restored
+    }
+
+    fn contents(app: &ClipboardManager) -> Vec<(&str, bool)> {
+        app.entries
+            .iter()
+            .map(|e| (e.content.as_str(), e.pinned))
+            .collect()
+    }
+
+    fn journal_of(app: &ClipboardManager) -> PathBuf {
+        path_for(app.save_path.as_ref().unwrap())
+    }
+
+    #[test]
+    fn changes_are_appended_and_replayed() {
+        let mut app = manager("replayed");
+        for content in ["a", "b", "c"] {
+            app.add_entry(content.to_owned());
+        }
+        let snapshot = fs::read_to_string(app.save_path.as_ref().unwrap()).unwrap();
+        let b = app.entries[1].id;
+        let c = app.entries[0].id;
+        app.toggle_pin(b);
+        app.remove_entry(c);
+        app.settings.max_entries = 77;
+        app.save_data();
+
+        // Only the journal changed after the first save
+        assert_eq!(
+            fs::read_to_string(app.save_path.as_ref().unwrap()).unwrap(),
+            snapshot
+        );
+        assert!(journal_of(&app).exists());
+        let loaded = load(&app);
+        assert_eq!(contents(&loaded), [("b", true), ("a", false)]);
+        assert_eq!(loaded.settings.max_entries, 77);
+        assert_eq!(loaded.trash.len(), 1);
+        assert!(loaded.next_id > c);
+    }
+
+    #[test]
+    fn truncated_last_line_is_skipped() {
+        let mut app = manager("truncated");
+        app.add_entry("a".to_owned());
+        app.add_entry("b".to_owned());
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(journal_of(&app))
+            .unwrap();
+        file.write_all(br#"{"entry":{"id":9,"cont"#).unwrap();
+        assert_eq!(contents(&load(&app)), [("b", false), ("a", false)]);
+    }
+
+    #[test]
+    fn journal_from_an_older_snapshot_is_ignored() {
+        let mut app = manager("stale");
+        app.add_entry("a".to_owned());
+        app.add_entry("deleted".to_owned());
+        let stale = fs::read(journal_of(&app)).unwrap();
+
+        // Crash after the new snapshot replaced the data file, but before
+        // the journal was removed
+        let id = app.entries[0].id;
+        app.remove_entry(id);
+        app.journal = None;
+        app.save_data();
+        assert!(!journal_of(&app).exists());
+        fs::write(journal_of(&app), stale).unwrap();
+
+        assert_eq!(contents(&load(&app)), [("a", false)]);
+
+        // Changes journaled after the new snapshot still apply
+        app.journal = None;
+        app.save_data();
+        app.add_entry("later".to_owned());
+        assert_eq!(contents(&load(&app)), [("later", false), ("a", false)]);
+    }
+
+    #[test]
+    fn snapshots_replace_the_data_file_whole() {
+        let mut app = manager("snapshot");
+        app.settings.storage_format = StorageFormat::Json;
+        app.add_entry("a".to_owned());
+        let path = app.save_path.clone().unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        assert!(!journal_of(&app).exists());
+        assert_eq!(contents(&load(&app)), [("a", false)]);
+    }
+}
@@ -0,0 +1,121 @@
+//! The taskbar jump list on Windows
+//!
+//! Right-clicking Klippy's taskbar button lists the first pinned entries as
+//! tasks. Each task runs `klippy --copy-pinned <n>`, which copies the entry
+//! without opening the window (see `cli`). Other platforms have no jump
+//! list, so everything here is a no-op there.
+
+/// Most pinned entries offered in the jump list
+pub const MAX_TASKS: usize = 10;
+
+/// A jump list task copying one pinned entry
+#[derive(Clone, PartialEq)]
+pub struct Task {
+    /// Shown in the jump list
+    pub title: String,
+    /// 1-based position of the entry among the pinned ones
+    pub position: usize,
+}
+
+/// Returns true if this platform has a jump list
+pub fn is_supported() -> bool {
+    cfg!(windows)
+}
+
+/// Replace the tasks in the jump list, on a background thread
+///
+/// An empty list removes the tasks.
+#[cfg(windows)]
+pub fn update(tasks: Vec<Task>) {
+    std::thread::spawn(move || {
+        if let Err(e) = shell::register(&tasks) {
+            log::warn!("Failed to update the jump list: {}", e);
+        }
+    });
+}
+
+/// Replace the tasks in the jump list, on a background thread
+#[cfg(not(windows))]
+pub fn update(_tasks: Vec<Task>) {}
+
+/// Returns the arguments a task starts Klippy with
+#[cfg_attr(not(windows), allow(dead_code))]
+fn arguments(task: &Task) -> String {
+    // Keep using the same storage when Klippy was started portable
+    let portable = std::env::args()
+        .skip(1)
+        .any(|arg| arg == crate::PORTABLE_FLAG);
+    let portable = if portable { " --portable" } else { "" };
+    format!("--copy-pinned {}{}", task.position, portable)
+}
+
+#[cfg(windows)]
+mod shell {
+    use super::{arguments, Task};
+    use windows::core::{Interface, HSTRING, PCWSTR, PROPVARIANT};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::StructuredStorage::{PropVariantChangeType, PVCHF_DEFAULT};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::System::Variant::VT_LPWSTR;
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{
+        DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink,
+    };
+
+    /// Register `tasks` as the jump list's tasks
+    pub fn register(tasks: &[Task]) -> windows::core::Result<()> {
+        let exe = std::env::current_exe()
+            .map(|exe| HSTRING::from(exe.as_os_str()))
+            .map_err(|e| {
+                windows::core::Error::new(windows::Win32::Foundation::E_FAIL, e.to_string())
+            })?;
+
+        // Each update runs on its own thread, which needs COM set up
+        unsafe {
+            CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok()?;
+            let result = build(&exe, tasks);
+            CoUninitialize();
+            result
+        }
+    }
+
+    /// Build and commit the jump list
+    unsafe fn build(exe: &HSTRING, tasks: &[Task]) -> windows::core::Result<()> {
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        if tasks.is_empty() {
+            return list.DeleteList(PCWSTR::null());
+        }
+
+        let mut slots = 0;
+        let _removed: IObjectArray = list.BeginList(&mut slots)?;
+        let collection: IObjectCollection =
+            CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+        for task in tasks {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            link.SetPath(exe)?;
+            link.SetArguments(&HSTRING::from(arguments(task)))?;
+            link.SetIconLocation(exe, 0)?;
+
+            // Tasks are titled through the link's properties, as a wide string
+            let mut title = PROPVARIANT::default();
+            PropVariantChangeType(
+                &mut title,
+                &PROPVARIANT::from(task.title.as_str()),
+                PVCHF_DEFAULT,
+                VT_LPWSTR,
+            )?;
+            let store: IPropertyStore = link.cast()?;
+            store.SetValue(&PKEY_Title, &title)?;
+            store.Commit()?;
+
+            collection.AddObject(&link)?;
+        }
+        list.AddUserTasks(&collection.cast::<IObjectArray>()?)?;
+        list.CommitList()
+    }
+}
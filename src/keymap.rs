@@ -0,0 +1,163 @@
+//! Remappable in-app shortcuts
+//!
+//! Each action that can be triggered from the keyboard while Klippy has focus
+//! has a default shortcut, which the user can rebind or disable. Only changed
+//! bindings are stored; an empty binding disables the action. Global hotkeys
+//! (showing the window, the paste ring, numbered slots) are set separately,
+//! since they're registered with the OS.
+use crate::shortcut;
+use egui::{Event, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Shortcuts changed from their defaults, by action
+pub type Bindings = BTreeMap<KeyAction, String>;
+
+/// An action that can be bound to an in-app shortcut
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum KeyAction {
+    /// Select the entry above the selected one
+    SelectPrevious,
+    /// Select the entry below the selected one
+    SelectNext,
+    /// Copy the selected entry
+    CopySelected,
+    /// Pin or unpin the selected entry
+    PinSelected,
+    /// Delete the selected entry
+    DeleteSelected,
+    /// Move the keyboard focus to the search box
+    FocusSearch,
+    /// Open the command palette
+    OpenPalette,
+    /// Open or close the settings window
+    ToggleSettings,
+    /// Clear the unpinned entries
+    ClearUnpinned,
+    /// Switch between light, dark and system appearance
+    CycleAppearance,
+    /// Open the quick open overlay to jump to an entry
+    QuickOpen,
+}
+
+impl KeyAction {
+    /// Every action, in the order listed in settings
+    pub const ALL: [KeyAction; 11] = [
+        KeyAction::SelectPrevious,
+        KeyAction::SelectNext,
+        KeyAction::CopySelected,
+        KeyAction::PinSelected,
+        KeyAction::DeleteSelected,
+        KeyAction::FocusSearch,
+        KeyAction::OpenPalette,
+        KeyAction::ToggleSettings,
+        KeyAction::ClearUnpinned,
+        KeyAction::CycleAppearance,
+        KeyAction::QuickOpen,
+    ];
+
+    /// Label shown in the settings window
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyAction::SelectPrevious => "Select previous entry",
+            KeyAction::SelectNext => "Select next entry",
+            KeyAction::CopySelected => "Copy selected entry",
+            KeyAction::PinSelected => "Pin or unpin selected entry",
+            KeyAction::DeleteSelected => "Delete selected entry",
+            KeyAction::FocusSearch => "Focus the search box",
+            KeyAction::OpenPalette => "Open the command palette",
+            KeyAction::ToggleSettings => "Open or close settings",
+            KeyAction::ClearUnpinned => "Clear unpinned entries",
+            KeyAction::CycleAppearance => "Switch appearance",
+            KeyAction::QuickOpen => "Jump to an entry (quick open)",
+        }
+    }
+
+    /// Shortcut used until the user rebinds it
+    pub fn default_shortcut(&self) -> &'static str {
+        match self {
+            KeyAction::SelectPrevious => "ArrowUp",
+            KeyAction::SelectNext => "ArrowDown",
+            KeyAction::CopySelected => "Enter",
+            KeyAction::PinSelected => "Ctrl+P",
+            KeyAction::DeleteSelected => "Delete",
+            KeyAction::FocusSearch => "Ctrl+F",
+            KeyAction::OpenPalette => "Ctrl+Shift+P",
+            KeyAction::ToggleSettings => "Ctrl+Comma",
+            KeyAction::ClearUnpinned => "Ctrl+Shift+Delete",
+            KeyAction::CycleAppearance => "Ctrl+Shift+L",
+            KeyAction::QuickOpen => "Ctrl+K",
+        }
+    }
+
+    /// Whether the action works while a text field has focus
+    pub fn works_while_typing(&self) -> bool {
+        matches!(self, KeyAction::OpenPalette | KeyAction::QuickOpen)
+    }
+}
+
+/// Returns the shortcut text bound to an action, empty if it's disabled
+pub fn binding(bindings: &Bindings, action: KeyAction) -> &str {
+    bindings
+        .get(&action)
+        .map_or(action.default_shortcut(), String::as_str)
+}
+
+/// Returns the parsed shortcut bound to an action, if any
+pub fn shortcut(bindings: &Bindings, action: KeyAction) -> Option<KeyboardShortcut> {
+    shortcut::parse(binding(bindings, action)).ok()
+}
+
+/// Returns the action other than `action` already bound to `candidate`
+pub fn conflict(
+    bindings: &Bindings,
+    action: KeyAction,
+    candidate: &KeyboardShortcut,
+) -> Option<KeyAction> {
+    KeyAction::ALL
+        .into_iter()
+        .filter(|&other| other != action)
+        .find(|&other| shortcut(bindings, other).as_ref() == Some(candidate))
+}
+
+/// Returns the actions in the order their shortcuts should be checked
+///
+/// egui matches a shortcut even when extra Shift or Alt keys are held, so
+/// shortcuts with more modifiers go first: Ctrl+Shift+P must win over Ctrl+P.
+pub fn check_order(bindings: &Bindings) -> Vec<(KeyAction, KeyboardShortcut)> {
+    let mut order: Vec<(KeyAction, KeyboardShortcut)> = KeyAction::ALL
+        .into_iter()
+        .filter_map(|action| shortcut(bindings, action).map(|s| (action, s)))
+        .collect();
+    let modifier_count =
+        |m: Modifiers| [m.command, m.shift, m.alt].iter().filter(|&&on| on).count();
+    order.sort_by_key(|(_, s)| std::cmp::Reverse(modifier_count(s.modifiers)));
+    order
+}
+
+/// Returns the key combination pressed in these events, for recording a
+/// binding. Modifier keys on their own don't count.
+pub fn pressed(events: &[Event]) -> Option<KeyboardShortcut> {
+    events.iter().find_map(|event| match event {
+        Event::Key {
+            key,
+            pressed: true,
+            repeat: false,
+            modifiers,
+            ..
+        } => {
+            let mut combo = Modifiers::NONE;
+            if modifiers.command || modifiers.ctrl {
+                combo |= Modifiers::COMMAND;
+            }
+            if modifiers.shift {
+                combo |= Modifiers::SHIFT;
+            }
+            if modifiers.alt {
+                combo |= Modifiers::ALT;
+            }
+            Some(KeyboardShortcut::new(combo, *key))
+        }
+        _ => None,
+    })
+}
@@ -0,0 +1,59 @@
+//! Logging to the terminal
+//!
+//! Warnings and errors are always written to stderr. Verbose logging, turned
+//! on in the settings or with `KLIPPY_LOG=debug`, adds routine details such as
+//! failed clipboard reads, which helps when tracking down a problem.
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Environment variable that turns on verbose logging from the start
+const ENV_VAR: &str = "KLIPPY_LOG";
+
+/// Writes log records to stderr
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // Other crates' routine messages would drown out ours
+        let ours = record.target().starts_with(env!("CARGO_CRATE_NAME"));
+        if !ours && record.level() > Level::Warn {
+            return;
+        }
+        eprintln!(
+            "{} {:<5} {}",
+            Local::now().format("%H:%M:%S%.3f"),
+            record.level(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger; call once at startup
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        set_verbose(false);
+    }
+}
+
+/// Log routine details as well as warnings and errors
+///
+/// `KLIPPY_LOG=debug` keeps verbose logging on whatever is passed.
+pub fn set_verbose(verbose: bool) {
+    let forced = std::env::var(ENV_VAR).is_ok_and(|value| value.eq_ignore_ascii_case("debug"));
+    log::set_max_level(if verbose || forced {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Warn
+    });
+}
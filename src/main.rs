@@ -2,427 +2,6164 @@
 ///
 /// A lightweight clipboard manager built with Rust and egui.
 /// This application allows users to store and manage multiple clipboard entries.
+use a11y::SystemPreferences;
 use chrono::{DateTime, Local};
 use clipboard::{ClipboardContext, ClipboardProvider};
+use compare::CompareView;
+use detect::{ContentKind, LineEnding, SecretRules};
 use directories::ProjectDirs;
 use eframe::{egui, App, CreationContext, Frame};
 use egui::{Color32, Context, RichText, Sense, Stroke, Vec2, ViewportBuilder};
+use error::Error;
+use geometry::WindowGeometry;
+use hotkeys::{HotkeyAction, Hotkeys};
+use import_preview::ImportPreview;
+use keymap::KeyAction;
+use onboarding::Setup;
+use palette::CommandPalette;
+use primary::PrimarySelection;
+use query::Query;
+use quick_open::QuickOpen;
 use serde::{Deserialize, Serialize};
+use std::cell::{Cell, OnceCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use transfer::ExportFormat;
+use viewer::ContentViewer;
+use watch::DataWatcher;
+
+mod a11y;
+mod autostart;
+mod backup;
+mod capture_log;
+mod cli;
+mod compare;
+mod demo;
+mod details;
+mod detect;
+mod error;
+mod files;
+mod foreground;
+mod formats;
+mod geometry;
+mod hotkeys;
+mod import_preview;
+mod journal;
+mod jump_list;
+mod keymap;
+mod logging;
+mod mini;
+mod onboarding;
+mod os_history;
+mod palette;
+mod paste;
+mod primary;
+mod query;
+mod quick_open;
+mod share;
+mod shortcut;
+mod transfer;
+mod transforms;
+mod viewer;
+mod watch;
 
 /// Represents a single clipboard entry with content and metadata
 #[derive(Clone, Serialize, Deserialize)]
 struct ClipboardEntry {
+    /// Stable identifier, unique within the history
+    ///
+    /// Assigned in increasing order as entries are added, so it also breaks
+    /// ties between entries captured at the same time.
+    #[serde(default)]
+    id: u64,
     /// The actual text content
     content: String,
     /// When the entry was created
     timestamp: DateTime<Local>,
     /// Whether this entry is pinned (won't be removed automatically)
     pinned: bool,
+    /// When a temporary pin runs out and the entry becomes unpinned again
+    #[serde(default)]
+    pin_until: Option<DateTime<Local>>,
+    /// When the entry was last copied back to the clipboard
+    #[serde(default)]
+    last_used: Option<DateTime<Local>>,
+    /// When the entry was moved to the trash
+    #[serde(default)]
+    deleted_at: Option<DateTime<Local>>,
+    /// Number of similar earlier versions merged into this entry
+    #[serde(default)]
+    revisions: u32,
+    /// Keep this entry on this machine: it's left out of exports and sync
+    #[serde(default)]
+    local_only: bool,
+    /// Marks the point where the clipboard was emptied, rather than captured text
+    #[serde(default)]
+    cleared: bool,
+    /// How many times this content was captured, when duplicates are counted
+    #[serde(default = "default_occurrences")]
+    occurrences: u32,
+    /// How many times the entry was copied out of Klippy
+    #[serde(default)]
+    use_count: u32,
+    /// Remove the entry from the history once it's been copied
+    #[serde(default)]
+    one_shot: bool,
+    /// Empty the system clipboard a while after this entry is copied
+    #[serde(default)]
+    clear_after_copy: bool,
+    /// Formats the clipboard offered when this was captured; only the plain
+    /// text is stored
+    #[serde(default)]
+    formats: Vec<String>,
+    /// The text as captured, when trimming on capture removed surrounding
+    /// whitespace from it
+    #[serde(default)]
+    raw: Option<String>,
+    /// The copied text wasn't valid UTF-8, so invalid bytes were replaced
+    #[serde(default)]
+    lossy: bool,
+    /// Read-only: the entry can't be deleted, replaced or transformed until
+    /// unlocked. Unlike pinning this doesn't affect eviction.
+    #[serde(default)]
+    locked: bool,
+    /// Made with the Duplicate action, so it's meant to sit next to an entry
+    /// with the same text: compacting never merges it away
+    #[serde(default)]
+    duplicate: bool,
+    /// Changes whenever the content does and differs between entries, so
+    /// views of the content can tell cheaply whether they're out of date
+    #[serde(skip, default = "next_content_version")]
+    version: u64,
+    /// Details worked out from the content, filled in when first needed
+    #[serde(skip)]
+    scan: OnceCell<ContentScan>,
+    /// Whether the content looks like a secret, under the rules it was
+    /// last checked with
+    #[serde(skip)]
+    secret: Cell<Option<(SecretRules, bool)>>,
+}
+
+/// Returns a new content version, distinct from every one handed out before
+fn next_content_version() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Details of an entry's content that take a pass over all of it to work
+/// out, so rows don't redo them every frame
+#[derive(Clone)]
+struct ContentScan {
+    kind: ContentKind,
+    extra_lines: usize,
+    line_ending: Option<LineEnding>,
+    needs_cleanup: bool,
+}
+
+impl ContentScan {
+    fn of(content: &str) -> Self {
+        Self {
+            kind: detect::detect(content),
+            extra_lines: content.trim().lines().count().saturating_sub(1),
+            line_ending: detect::line_ending(content),
+            needs_cleanup: detect::needs_cleanup(content),
+        }
+    }
 }
 
 impl ClipboardEntry {
     /// Create a new clipboard entry with the current timestamp
-    fn new(content: String) -> Self {
+    fn new(id: u64, content: String) -> Self {
         Self {
+            id,
             content,
             timestamp: Local::now(),
             pinned: false,
+            pin_until: None,
+            last_used: None,
+            deleted_at: None,
+            revisions: 0,
+            local_only: false,
+            cleared: false,
+            occurrences: 1,
+            use_count: 0,
+            one_shot: false,
+            clear_after_copy: false,
+            formats: Vec::new(),
+            raw: None,
+            lossy: false,
+            locked: false,
+            duplicate: false,
+            version: next_content_version(),
+            scan: OnceCell::new(),
+            secret: Cell::new(None),
         }
     }
 
-    /// Returns a formatted timestamp string
-    fn formatted_time(&self) -> String {
-        self.timestamp.format("%H:%M:%S").to_string()
+    /// Replace the content, forgetting what was worked out from the old one
+    fn set_content(&mut self, content: String) {
+        self.content = content;
+        self.version = next_content_version();
+        self.scan = OnceCell::new();
+        self.secret.set(None);
     }
 
-    /// Returns a preview of the content (truncated if too long)
-    fn preview(&self) -> String {
-        if self.content.len() > 50 {
-            format!("{}...", &self.content[..47])
-        } else {
-            self.content.clone()
-        }
+    /// Returns the details worked out from the content
+    fn scan(&self) -> &ContentScan {
+        self.scan.get_or_init(|| ContentScan::of(&self.content))
     }
-}
-
-/// Represents the main application state
-#[derive(Serialize, Deserialize)]
-struct ClipboardManager {
-    /// List of clipboard entries
-    entries: Vec<ClipboardEntry>,
-    /// Maximum number of entries to keep
-    max_entries: usize,
-    /// Path to save application data
-    #[serde(skip)]
-    save_path: Option<PathBuf>,
-    /// Clipboard context for interaction with system clipboard
-    #[serde(skip)]
-    clipboard_ctx: Option<ClipboardContext>,
-    /// Current clipboard content for change detection
-    #[serde(skip)]
-    current_clipboard: String,
-    /// Search term for filtering entries
-    #[serde(skip)]
-    search_term: String,
-    /// Status message to display
-    #[serde(skip)]
-    status_message: Option<(String, f32)>, // (message, timer)
-    #[serde(skip)]
-    show_settings_window: bool,
-}
 
-impl Default for ClipboardManager {
-    fn default() -> Self {
-        Self {
-            entries: Vec::new(),
-            max_entries: 50,
-            save_path: None,
-            clipboard_ctx: ClipboardProvider::new().ok(),
-            current_clipboard: String::new(),
-            search_term: String::new(),
-            status_message: None,
-            show_settings_window: false,
+    /// Returns true if the content looks like a secret under `rules`
+    fn looks_like_secret(&self, rules: &SecretRules) -> bool {
+        match self.secret.get() {
+            Some((checked, secret)) if checked == *rules => secret,
+            _ => {
+                let secret = detect::looks_like_secret(&self.content, rules);
+                self.secret.set(Some((*rules, secret)));
+                secret
+            }
         }
     }
-}
 
-impl ClipboardManager {
-    /// Initialize the application with saved data if available
-    fn new() -> Self {
-        let mut app = Self::default();
+    /// Returns the line ending style of the content, if it has several lines
+    fn line_ending(&self) -> Option<LineEnding> {
+        self.scan().line_ending
+    }
 
-        // Set up save path
-        if let Some(proj_dirs) = ProjectDirs::from("com", "klippy", "klippy") {
-            let config_dir = proj_dirs.config_dir();
-            if !config_dir.exists() {
-                let _ = fs::create_dir_all(config_dir);
-            }
+    /// Returns true if cleaning up the text would change it
+    fn needs_cleanup(&self) -> bool {
+        self.scan().needs_cleanup
+    }
+
+    /// Returns the captured formats that aren't plain text, e.g. `text/html`
+    fn rich_formats(&self) -> Vec<&str> {
+        self.formats
+            .iter()
+            .map(String::as_str)
+            .filter(|format| !formats::is_plain_text(format))
+            .collect()
+    }
 
-            app.save_path = Some(config_dir.join("data.json"));
+    /// Returns the timestamp formatted with a (valid) chrono format string
+    fn formatted_time(&self, format: &str) -> String {
+        self.timestamp.format(format).to_string()
+    }
 
-            // Load saved data
-            if let Some(path) = &app.save_path {
-                if path.exists() {
-                    if let Ok(data) = fs::read_to_string(path) {
-                        if let Ok(loaded) = serde_json::from_str::<ClipboardManager>(&data) {
-                            app.entries = loaded.entries;
-                            app.max_entries = loaded.max_entries;
-                        }
-                    }
-                }
-            }
+    /// Returns a description of when the entry was last copied
+    fn last_used_text(&self) -> String {
+        match self.last_used {
+            Some(time) => format!(
+                "Last used: {} · copied {} time{}",
+                time.format("%Y-%m-%d %H:%M:%S"),
+                self.use_count,
+                if self.use_count == 1 { "" } else { "s" }
+            ),
+            None => "Never copied from Klippy".to_owned(),
         }
+    }
 
-        app
+    /// Returns how long a temporary pin has left, e.g. `3h` or `2d`
+    fn pin_remaining(&self) -> Option<String> {
+        let left = self.pin_until? - Local::now();
+        let text = if left.num_days() > 0 {
+            format!("{}d", left.num_days())
+        } else if left.num_hours() > 0 {
+            format!("{}h", left.num_hours())
+        } else {
+            format!("{}m", left.num_minutes().max(1))
+        };
+        Some(text)
     }
 
-    /// Save application data to disk
-    fn save_data(&self) {
-        if let Some(path) = &self.save_path {
-            if let Ok(json) = serde_json::to_string(self) {
-                let _ = fs::write(path, json);
-            }
+    /// Returns how strongly to draw the entry, from 1.0 while it's fresh down
+    /// to `STALE_OPACITY` once it's a week old
+    ///
+    /// The fade follows the log of the age, so the first day shows more
+    /// change than the rest of the week.
+    fn freshness(&self) -> f32 {
+        let age = (Local::now() - self.timestamp).num_seconds() as f32;
+        let fresh = FRESH_FOR.num_seconds() as f32;
+        if age <= fresh {
+            return 1.0;
         }
+        let stale = STALE_AFTER.num_seconds() as f32;
+        let progress = ((age / fresh).ln() / (stale / fresh).ln()).min(1.0);
+        1.0 - progress * (1.0 - STALE_OPACITY)
     }
 
-    /// Add a new entry to the clipboard history
-    fn add_entry(&mut self, content: String) {
-        // Don't add empty content or duplicates
-        if content.trim().is_empty() || self.entries.iter().any(|e| e.content == content) {
-            return;
-        }
+    /// Take on another copy's pin: pinned if either is, and a temporary pin
+    /// only lasts as long as the longer-lived of the two
+    fn merge_pin(&mut self, other: &ClipboardEntry) {
+        self.pin_until = match (self.pinned, other.pinned) {
+            (true, true) => self.pin_until.zip(other.pin_until).map(|(a, b)| a.max(b)),
+            (false, true) => other.pin_until,
+            _ => self.pin_until,
+        };
+        self.pinned |= other.pinned;
+    }
 
-        self.entries.insert(0, ClipboardEntry::new(content));
+    /// Returns the key ordering entries by capture time, ties broken by id
+    fn capture_order(&self) -> (DateTime<Local>, u64) {
+        (self.timestamp, self.id)
+    }
 
-        // Remove oldest entries if we exceed max_entries (unless pinned)
-        while self.entries.len() > self.max_entries {
-            // Find the oldest non-pinned entry
-            if let Some(idx) = self
-                .entries
-                .iter()
-                .enumerate()
-                .filter(|(_, e)| !e.pinned)
-                .map(|(i, _)| i)
-                .last()
-            {
-                self.entries.remove(idx);
-            } else {
-                // All entries are pinned, can't remove any
-                break;
-            }
+    /// Returns the detected kind of content
+    fn kind(&self) -> ContentKind {
+        self.scan().kind
+    }
+
+    /// Returns a one-line preview of the content: the first non-empty line,
+    /// truncated if too long
+    ///
+    /// Only a preview's worth of the first line is scanned, so huge
+    /// single-line entries stay cheap to render every frame.
+    fn preview(&self, max_chars: usize) -> String {
+        if self.cleared {
+            return "⌀ Clipboard cleared".to_owned();
+        }
+        let max_chars = max_chars.max(MIN_PREVIEW_CHARS);
+        let mut line = self
+            .content
+            .trim_start()
+            .chars()
+            .take_while(|c| *c != '\n' && *c != '\r');
+        let head: String = line.by_ref().take(max_chars).collect();
+        if line.next().is_some() {
+            let truncated: String = head.chars().take(max_chars - 3).collect();
+            format!("{}...", truncated)
+        } else {
+            head.trim_end().to_owned()
         }
+    }
 
-        // Save data after changes
-        self.save_data();
+    /// Returns the number of lines following the previewed line
+    fn extra_lines(&self) -> usize {
+        self.scan().extra_lines
     }
 
-    /// Copy entry content to clipboard
-    fn copy_to_clipboard(&mut self, content: &str) -> bool {
-        if let Some(ctx) = &mut self.clipboard_ctx {
-            if ctx.set_contents(content.to_owned()).is_ok() {
-                self.current_clipboard = content.to_owned();
-                self.set_status("Copied to clipboard", 2.0);
-                return true;
-            }
+    /// Returns up to `max` of the lines following the first, for an expanded row
+    fn expanded_lines(&self, max: usize) -> Vec<String> {
+        self.content
+            .trim()
+            .lines()
+            .skip(1)
+            .take(max)
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+/// How entries are ordered in the list
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum SortMode {
+    /// Most recently captured first
+    #[default]
+    Newest,
+    /// Earliest captured first
+    Oldest,
+    /// Most recently copied out first, never-used entries last
+    RecentlyUsed,
+}
+
+impl SortMode {
+    /// All sort modes, in the order shown in the selector
+    const ALL: [SortMode; 3] = [SortMode::Newest, SortMode::Oldest, SortMode::RecentlyUsed];
+
+    /// Label shown in the sort selector
+    fn label(&self) -> &'static str {
+        match self {
+            SortMode::Newest => "Newest",
+            SortMode::Oldest => "Oldest",
+            SortMode::RecentlyUsed => "Recently used",
         }
-        self.set_status("Failed to copy to clipboard", 2.0);
-        false
     }
+}
 
-    /// Check for new clipboard content
-    fn check_clipboard(&mut self) {
-        if let Some(ctx) = &mut self.clipboard_ctx {
-            if let Ok(content) = ctx.get_contents() {
-                if !content.is_empty() && content != self.current_clipboard {
-                    self.current_clipboard = content.clone();
-                    self.add_entry(content);
-                }
-            }
+/// What closing the window does
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum CloseAction {
+    /// Save and exit
+    #[default]
+    Quit,
+    /// Minimize the window and keep capturing
+    Minimize,
+    /// Hide the window and keep capturing until the show hotkey is pressed
+    Hide,
+}
+
+impl CloseAction {
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            CloseAction::Quit => "Quit",
+            CloseAction::Minimize => "Minimize",
+            CloseAction::Hide => "Hide",
         }
     }
+}
 
-    /// Set a status message with a timer
-    fn set_status(&mut self, message: &str, timer: f32) {
-        self.status_message = Some((message.to_owned(), timer));
+/// What a single click on a row does
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum ClickAction {
+    /// Copy the entry
+    #[default]
+    Copy,
+    /// Only select the entry
+    Select,
+    /// Expand or collapse a multi-line entry
+    Expand,
+    /// Open the full content viewer
+    View,
+}
+
+impl ClickAction {
+    /// Every action, in the order offered
+    const ALL: [ClickAction; 4] = [
+        ClickAction::Copy,
+        ClickAction::Select,
+        ClickAction::Expand,
+        ClickAction::View,
+    ];
+
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            ClickAction::Copy => "Copy",
+            ClickAction::Select => "Select",
+            ClickAction::Expand => "Expand",
+            ClickAction::View => "View",
+        }
     }
+}
 
-    /// Update the status message timer
-    fn update_status(&mut self, ctx: &egui::Context) {
-        let current_time = ctx.input(|i| i.time); // Time since start in seconds
-        static mut LAST_TIME: f64 = 0.0;
-        let dt = unsafe {
-            let delta = current_time - LAST_TIME;
-            LAST_TIME = current_time;
-            delta as f32
-        };
+/// Light or dark appearance
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum Appearance {
+    /// Follow the system theme
+    #[default]
+    System,
+    /// Always dark
+    Dark,
+    /// Always light
+    Light,
+}
 
-        if let Some((_message, timer)) = &mut self.status_message {
-            *timer -= dt;
+impl Appearance {
+    /// Every appearance, in the order offered
+    const ALL: [Appearance; 3] = [Appearance::System, Appearance::Dark, Appearance::Light];
 
-            if *timer <= 0.0 {
-                self.status_message = None;
-            }
+    /// The appearance the quick switch moves to next: Light, Dark, System
+    fn next(&self) -> Self {
+        match self {
+            Appearance::Light => Appearance::Dark,
+            Appearance::Dark => Appearance::System,
+            Appearance::System => Appearance::Light,
+        }
+    }
 
-            // Request a repaint to update the status message
-            ctx.request_repaint();
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            Appearance::System => "System",
+            Appearance::Dark => "Dark",
+            Appearance::Light => "Light",
         }
     }
 
-    /// Remove an entry at the specified index
-    fn remove_entry(&mut self, index: usize) {
-        if index < self.entries.len() {
-            self.entries.remove(index);
-            self.save_data();
-            self.set_status("Entry removed", 2.0);
+    /// The matching egui theme preference
+    fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Appearance::System => egui::ThemePreference::System,
+            Appearance::Dark => egui::ThemePreference::Dark,
+            Appearance::Light => egui::ThemePreference::Light,
         }
     }
+}
 
-    /// Toggle pinned status of an entry
-    fn toggle_pin(&mut self, index: usize) {
-        if index < self.entries.len() {
-            self.entries[index].pinned = !self.entries[index].pinned;
-            self.save_data();
+/// Whether an accessibility preference follows the system or is set explicitly
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum Preference {
+    /// Use the system setting, off if the system doesn't report one
+    #[default]
+    System,
+    /// Always on
+    On,
+    /// Always off
+    Off,
+}
 
-            let status = if self.entries[index].pinned {
-                "Entry pinned"
-            } else {
-                "Entry unpinned"
-            };
-            self.set_status(status, 2.0);
+impl Preference {
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            Preference::System => "System",
+            Preference::On => "On",
+            Preference::Off => "Off",
         }
     }
 
-    /// Get filtered entries based on search term
-    fn filtered_entries(&self) -> Vec<usize> {
-        self.entries
-            .iter()
-            .enumerate()
-            .filter(|(_, entry)| {
-                if self.search_term.is_empty() {
-                    true
-                } else {
-                    entry
-                        .content
-                        .to_lowercase()
-                        .contains(&self.search_term.to_lowercase())
-                }
-            })
-            .map(|(idx, _)| idx)
-            .collect()
+    /// Returns whether the preference is in effect, given the system's setting
+    fn resolve(&self, system: Option<bool>) -> bool {
+        match self {
+            Preference::System => system.unwrap_or(false),
+            Preference::On => true,
+            Preference::Off => false,
+        }
     }
+}
 
-    fn open_clips(&mut self) {
-        if let Some(path) = &self.save_path {
-            if let Some(parent) = path.parent() {
-                if let Err(e) = open::that(parent) {
-                    self.set_status(&format!("Failed to open directory: {}", e), 3.0);
-                } else {
-                    self.set_status("Opened storage directory", 2.0);
-                }
+/// Markup used by "Copy as link"
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LinkFormat {
+    /// `[label](url)`
+    Markdown,
+    /// `<a href="url">label</a>`
+    Html,
+}
+
+impl LinkFormat {
+    /// Label shown in the link prompt
+    fn label(&self) -> &'static str {
+        match self {
+            LinkFormat::Markdown => "Markdown",
+            LinkFormat::Html => "HTML",
+        }
+    }
+
+    /// Format a link, escaping the label (and the URL for HTML)
+    fn format(&self, label: &str, url: &str) -> String {
+        match self {
+            LinkFormat::Markdown => {
+                let label = label.replace('[', "\\[").replace(']', "\\]");
+                format!("[{}]({})", label, url.replace(')', "%29"))
+            }
+            LinkFormat::Html => {
+                let escape = |text: &str| {
+                    text.replace('&', "&amp;")
+                        .replace('<', "&lt;")
+                        .replace('>', "&gt;")
+                        .replace('"', "&quot;")
+                };
+                format!("<a href=\"{}\">{}</a>", escape(url), escape(label))
             }
-        } else {
-            self.set_status("Storage path not available", 2.0);
         }
     }
 }
 
-impl App for ClipboardManager {
-    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
-        // Check for new clipboard content
-        self.check_clipboard();
+/// A named search query, applied from the saved searches menu
+#[derive(Clone, Serialize, Deserialize)]
+struct SavedSearch {
+    /// Name shown in the menu
+    name: String,
+    /// Query text, in the search box syntax
+    query: String,
+}
 
-        // Update status message timer
-        self.update_status(ctx); // Use delta_time instead of dt // Use delta time from ctx
+/// State of the "Copy as link" prompt
+struct LinkPrompt {
+    /// Id of the URL entry being linked
+    id: u64,
+    /// Link text typed so far
+    label: String,
+    /// Markup to produce
+    format: LinkFormat,
+}
 
-        // Top panel with search and status
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            ui.add_space(8.0);
-            ui.horizontal_wrapped(|ui| {
-                ui.label(RichText::new("🔍 Search").strong());
-                ui.add_sized(
-                    [200.0, 28.0],
-                    egui::TextEdit::singleline(&mut self.search_term),
-                );
+/// What happens to new captures once pinned entries fill the history
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum FullHistory {
+    /// Don't store new captures, and say so
+    #[default]
+    Block,
+    /// Store them anyway, letting the history grow past the entry limit
+    Grow,
+}
 
-                if ui
-                    .button("❌ Clear")
-                    .on_hover_text("Clear search")
-                    .clicked()
-                {
-                    self.search_term.clear();
-                }
+impl FullHistory {
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            FullHistory::Block => "Stop capturing",
+            FullHistory::Grow => "Keep capturing past the limit",
+        }
+    }
+}
 
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    ui.label(RichText::new(format!("📋 {} items", self.entries.len())).weak());
-                });
-            });
-            ui.add_space(4.0);
-            ui.separator();
-        });
+/// How the history is written to disk
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum StorageFormat {
+    /// Rewrite the whole data file on every save
+    #[default]
+    Json,
+    /// Append changes to a journal, rewriting the data file now and then
+    JsonLines,
+}
 
-        // Bottom panel with status messages
-        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-            ui.add_space(4.0);
-            ui.horizontal_wrapped(|ui| {
-                let status = self
-                    .status_message
-                    .as_ref()
-                    .map(|(msg, _)| msg.as_str())
-                    .unwrap_or("Ready");
-                ui.label(RichText::new(status).color(Color32::LIGHT_BLUE));
+impl StorageFormat {
+    /// Every format, in the order listed in settings
+    const ALL: [StorageFormat; 2] = [StorageFormat::Json, StorageFormat::JsonLines];
 
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if ui.button("⚙ Settings").clicked() {
-                        self.show_settings_window = true;
-                    }
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            StorageFormat::Json => "JSON",
+            StorageFormat::JsonLines => "JSON Lines (append changes)",
+        }
+    }
+}
 
-                    if ui.button("🧹 Clear Unpinned").clicked() {
-                        self.entries.retain(|e| e.pinned);
-                        self.save_data();
-                        self.set_status("Cleared unpinned", 2.0);
-                    }
+/// Which copies empty the system clipboard again after a delay
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum ClipboardWipe {
+    /// Only entries marked to clear the clipboard
+    #[default]
+    Marked,
+    /// Marked entries and anything that looks like a secret
+    Secrets,
+    /// Every copy
+    All,
+}
 
-                    if ui.button("📂 Open Location").clicked() {
-                        self.open_clips();
-                    }
-                });
-            });
-            ui.add_space(4.0);
-            ui.separator();
-        });
+impl ClipboardWipe {
+    /// Every option, in the order listed in settings
+    const ALL: [ClipboardWipe; 3] = [
+        ClipboardWipe::Marked,
+        ClipboardWipe::Secrets,
+        ClipboardWipe::All,
+    ];
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                // Store the filtered indices in a local variable to avoid keeping
-                // the immutable borrow of self active
-                let filtered = self.filtered_entries(); // Get Vec<usize> of indices
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            ClipboardWipe::Marked => "Marked entries",
+            ClipboardWipe::Secrets => "Marked entries and likely secrets",
+            ClipboardWipe::All => "Every copy",
+        }
+    }
+}
 
-                if filtered.is_empty() {
-                    ui.centered_and_justified(|ui| {
-                        if self.search_term.is_empty() {
-                            ui.label("No clipboard entries yet. Copy something to add it here.");
-                        } else {
-                            ui.label("No matching entries found.");
-                        }
-                    });
-                } else {
-                    // Clone any data needed in closures to avoid borrowing self
-                    for &idx in &filtered {
-                        ui.push_id(idx, |ui| {
-                            if idx >= self.entries.len() {
-                                return;
-                            }
+/// Where the details of the selected entry are shown
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum DetailsLayout {
+    /// In a panel beside the list
+    #[default]
+    Side,
+    /// Below the list, next to the full content
+    Split,
+}
 
-                            let preview = self.entries[idx].preview();
-                            let formatted_time = self.entries[idx].formatted_time();
-                            let is_pinned = self.entries[idx].pinned;
-                            let content = self.entries[idx].content.clone(); // Clone if needed for clipboard
+impl DetailsLayout {
+    /// Every layout, in the order listed in settings
+    const ALL: [DetailsLayout; 2] = [DetailsLayout::Side, DetailsLayout::Split];
 
-                            let (rect, response) = ui.allocate_exact_size(
-                                Vec2::new(ui.available_width(), 40.0),
-                                Sense::click(),
-                            );
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            DetailsLayout::Side => "Beside the list",
+            DetailsLayout::Split => "Below the list, with the content",
+        }
+    }
+}
 
-                            // Draw entry background
-                            let bg_color = if is_pinned {
-                                Color32::from_rgb(50, 50, 60)
-                            } else {
-                                Color32::from_rgb(30, 30, 35)
-                            };
-                            ui.painter().rect_filled(rect, 4.0, bg_color);
+/// Keys that make up the numbered slot hotkeys
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum SlotKeys {
+    /// Ctrl+Alt and a number key
+    #[default]
+    CtrlAlt,
+    /// Ctrl and a number key
+    Ctrl,
+    /// Ctrl+Alt and a numpad key, leaving the number row alone
+    Numpad,
+}
 
-                            // Handle click to copy
-                            if response.clicked() {
-                                // Use a separate method or closure that takes the content directly
-                                self.copy_to_clipboard(&content);
-                            }
+impl SlotKeys {
+    /// Every option, in the order listed in settings
+    const ALL: [SlotKeys; 3] = [SlotKeys::CtrlAlt, SlotKeys::Ctrl, SlotKeys::Numpad];
 
-                            let mut content_layout = ui.new_child(
-                                egui::UiBuilder::new()
-                                    .max_rect(rect.shrink(8.0))
-                                    .layout(egui::Layout::left_to_right(egui::Align::Center)),
-                            );
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            SlotKeys::CtrlAlt => "Ctrl+Alt+number",
+            SlotKeys::Ctrl => "Ctrl+number",
+            SlotKeys::Numpad => "Ctrl+Alt+numpad",
+        }
+    }
 
-                            content_layout.horizontal(|ui| {
-                                // Time
-                                ui.label(RichText::new(formatted_time).color(Color32::LIGHT_GRAY));
-                                ui.add_space(8.0);
+    /// Returns the hotkey for a slot
+    fn hotkey(&self, slot: u8) -> String {
+        match self {
+            SlotKeys::CtrlAlt => format!("Ctrl+Alt+{}", slot),
+            SlotKeys::Ctrl => format!("Ctrl+{}", slot),
+            SlotKeys::Numpad => format!("Ctrl+Alt+Num{}", slot),
+        }
+    }
+}
 
-                                // Content preview
-                                ui.label(preview);
+/// What happens to copied text that isn't valid UTF-8
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum InvalidText {
+    /// Store it with the invalid bytes replaced, marked as converted
+    #[default]
+    Convert,
+    /// Don't store it, and say so
+    Skip,
+}
 
-                                ui.with_layout(
-                                    egui::Layout::right_to_left(egui::Align::Center),
-                                    |ui| {
-                                        // copy button
-                                        if ui.button("📋").clicked() {
-                                            self.copy_to_clipboard(&content);
-                                        }
+impl InvalidText {
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            InvalidText::Convert => "Capture with invalid bytes replaced",
+            InvalidText::Skip => "Skip it",
+        }
+    }
+}
 
-                                        // Delete button with index capture
-                                        if ui.button("🗑").clicked() {
-                                            if idx < self.entries.len() {
-                                                // Validate index is still valid
-                                                self.remove_entry(idx);
-                                            }
-                                        }
+/// How status messages are shown
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum StatusStyle {
+    /// One line in the bottom bar, each message replacing the last
+    #[default]
+    Line,
+    /// A stack of toasts in a corner of the window
+    Toasts,
+}
 
-                                        // Pin button with index capture
-                                        let pin_text = if is_pinned { "📌" } else { "📍" };
-                                        if ui.button(pin_text).clicked() {
-                                            if idx < self.entries.len() {
-                                                // Validate index is still valid
-                                                self.toggle_pin(idx);
-                                            }
-                                        }
-                                    },
-                                );
-                            });
+impl StatusStyle {
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            StatusStyle::Line => "Status line",
+            StatusStyle::Toasts => "Toasts",
+        }
+    }
+}
 
-                            // Draw separator
+/// Corner of the list area toasts are stacked in
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+enum ToastCorner {
+    /// Top left
+    TopLeft,
+    /// Top right
+    TopRight,
+    /// Bottom left
+    BottomLeft,
+    /// Bottom right
+    #[default]
+    BottomRight,
+}
+
+impl ToastCorner {
+    /// Every corner, in menu order
+    const ALL: [ToastCorner; 4] = [
+        ToastCorner::TopLeft,
+        ToastCorner::TopRight,
+        ToastCorner::BottomLeft,
+        ToastCorner::BottomRight,
+    ];
+
+    /// Label shown in the settings window
+    fn label(&self) -> &'static str {
+        match self {
+            ToastCorner::TopLeft => "Top left",
+            ToastCorner::TopRight => "Top right",
+            ToastCorner::BottomLeft => "Bottom left",
+            ToastCorner::BottomRight => "Bottom right",
+        }
+    }
+
+    /// Alignment of the toast stack against its corner
+    fn align(&self) -> egui::Align2 {
+        match self {
+            ToastCorner::TopLeft => egui::Align2::LEFT_TOP,
+            ToastCorner::TopRight => egui::Align2::RIGHT_TOP,
+            ToastCorner::BottomLeft => egui::Align2::LEFT_BOTTOM,
+            ToastCorner::BottomRight => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+}
+
+/// Which unpinned entries a clear moves to the trash
+#[derive(Clone, Copy)]
+enum ClearScope {
+    /// Every unpinned entry
+    All,
+    /// Unpinned entries captured before this time
+    Before(DateTime<Local>),
+}
+
+impl ClearScope {
+    /// Returns true if the entry would be cleared; pinned and locked entries
+    /// never are
+    fn includes(&self, entry: &ClipboardEntry) -> bool {
+        !entry.pinned
+            && !entry.locked
+            && match self {
+                ClearScope::All => true,
+                ClearScope::Before(cutoff) => entry.timestamp < *cutoff,
+            }
+    }
+}
+
+/// Actions that can be run from the command palette
+#[derive(Clone, Copy)]
+enum PaletteCommand {
+    /// Open the settings window
+    OpenSettings,
+    /// Empty the search box
+    ClearSearch,
+    /// Apply the saved search at this index
+    SavedSearch(usize),
+    /// Switch between this session's entries and all entries
+    ToggleSessionOnly,
+    /// Pause or resume capturing
+    ToggleMonitoring,
+    /// Show or hide the details panel
+    ToggleDetails,
+    /// Switch to the mini strip
+    MiniMode,
+    /// Open the quick open overlay
+    QuickOpen,
+    /// Switch to the next appearance
+    CycleAppearance,
+    /// Change the list order
+    Sort(SortMode),
+    /// Clear unpinned entries, after confirmation
+    ClearUnpinned,
+    /// Export every entry in a format
+    Export(ExportFormat),
+    /// Import from a file, replacing the history if true
+    Import(bool),
+    /// Import the system clipboard history
+    ImportOsHistory,
+    /// Back up the data file
+    BackUpNow,
+    /// Compact the history and data file
+    Compact,
+    /// Open the trash window
+    OpenTrash,
+    /// Open the storage directory in the file manager
+    OpenStorage,
+    /// Copy the storage directory path
+    CopyStoragePath,
+    /// Turn secret masking on or off
+    ToggleMaskSecrets,
+    /// Turn launch on login on or off
+    ToggleAutostart,
+    /// Save and exit
+    Quit,
+}
+
+/// Consecutive clipboard read errors before polling backs off
+const READ_ERROR_LIMIT: u32 = 10;
+/// Longest wait between clipboard reads while they keep failing
+const MAX_READ_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Default maximum number of characters shown in a row preview
+const DEFAULT_PREVIEW_CHARS: usize = 50;
+/// Smallest allowed preview length, leaving room for the ellipsis
+const MIN_PREVIEW_CHARS: usize = 10;
+/// Characters of an entry shown as its jump list title
+const JUMP_LIST_PREVIEW_CHARS: usize = 40;
+
+/// Timestamp format used when none is configured or the configured one is invalid
+const DEFAULT_TIME_FORMAT: &str = "%H:%M:%S";
+
+/// Timestamp format presets offered in the settings, with their labels
+const TIME_FORMAT_PRESETS: &[(&str, &str)] = &[
+    ("24-hour", "%H:%M:%S"),
+    ("12-hour", "%I:%M:%S %p"),
+    ("Date + time", "%Y-%m-%d %H:%M"),
+];
+
+/// Returns true if the chrono format string can be used to format a timestamp
+fn is_valid_time_format(format: &str) -> bool {
+    !format.is_empty()
+        && chrono::format::StrftimeItems::new(format)
+            .all(|item| !matches!(item, chrono::format::Item::Error))
+}
+
+/// Maximum characters of content shown in a row's tooltip
+const TOOLTIP_CHARS: usize = 500;
+/// Maximum lines of content shown in a row's tooltip
+const TOOLTIP_LINES: usize = 20;
+
+/// Returns the beginning of the content for a tooltip, marking anything cut off
+fn tooltip_text(content: &str) -> String {
+    let mut lines = 1;
+    let end = content
+        .char_indices()
+        .enumerate()
+        .find(|&(count, (_, c))| {
+            if c == '\n' {
+                lines += 1;
+            }
+            count == TOOLTIP_CHARS || lines > TOOLTIP_LINES
+        })
+        .map_or(content.len(), |(_, (index, _))| index);
+
+    let mut text = content[..end].to_owned();
+    if !content[end..].trim().is_empty() {
+        text.push_str("\n…");
+    }
+    text
+}
+
+/// Combine what another copy of the history knows about an entry into ours,
+/// returning true if anything changed
+///
+/// Each detail merges the same way whichever side it comes from, so synced
+/// copies settle on the same values instead of overwriting each other back
+/// and forth: pinning and locking are kept if either side set them, and the
+/// counts and last use take the larger value.
+fn reconcile_entry(entry: &mut ClipboardEntry, other: &ClipboardEntry) -> bool {
+    let before = (
+        entry.pinned,
+        entry.pin_until,
+        entry.locked,
+        entry.use_count,
+        entry.occurrences,
+        entry.last_used,
+    );
+    entry.merge_pin(other);
+    entry.locked |= other.locked;
+    entry.use_count = entry.use_count.max(other.use_count);
+    entry.occurrences = entry.occurrences.max(other.occurrences);
+    entry.last_used = entry.last_used.max(other.last_used);
+    before
+        != (
+            entry.pinned,
+            entry.pin_until,
+            entry.locked,
+            entry.use_count,
+            entry.occurrences,
+            entry.last_used,
+        )
+}
+
+/// Turn entries read from the OS clipboard history, newest first, into
+/// entries to import
+///
+/// Entries without a known copy time are stamped with `now`. They're
+/// returned oldest first, so those stamped with the same time get increasing
+/// ids when imported and keep their order.
+fn history_entries(
+    items: Vec<os_history::HistoryItem>,
+    now: DateTime<Local>,
+) -> Vec<ClipboardEntry> {
+    items
+        .into_iter()
+        .rev()
+        .map(|item| ClipboardEntry {
+            timestamp: item.copied_at.unwrap_or(now),
+            ..ClipboardEntry::new(0, item.content)
+        })
+        .collect()
+}
+
+/// Count status message timers down by the time between the last frame and
+/// `now`, dropping messages whose time is up
+///
+/// Messages set before the first frame (e.g. at startup) start aging from
+/// it, and a clock going backwards doesn't age them at all.
+fn age_status_messages(messages: &mut Vec<(String, f32)>, last: Option<f64>, now: f64) {
+    let dt = last.map_or(0.0, |last| (now - last).max(0.0) as f32);
+    for (_message, timer) in messages.iter_mut() {
+        *timer -= dt;
+    }
+    messages.retain(|(_, timer)| *timer > 0.0);
+}
+
+/// Returns the modification times of the data file and its journal, used to
+/// tell our own writes from other programs'
+fn data_mtimes(path: &Path) -> [Option<SystemTime>; 2] {
+    [path.to_path_buf(), journal::path_for(path)]
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+}
+
+/// Format a byte count as a human-readable size
+fn format_size(bytes: usize) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+
+    let bytes_f = bytes as f64;
+    if bytes_f >= MB {
+        format!("{:.1} MB", bytes_f / MB)
+    } else if bytes_f >= KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn default_append_separator() -> String {
+    "\\n".to_owned()
+}
+
+/// Largest clipboard size that appending an entry may produce
+const MAX_APPEND_BYTES: usize = 1024 * 1024;
+
+/// Number of recent entries a new capture is compared with for similarity
+const SIMILARITY_WINDOW: usize = 10;
+
+/// Most numbered entry slots there can be, one per number key
+const MAX_SLOTS: u8 = 9;
+
+/// How long a newly captured row is highlighted
+const FLASH_DURATION: Duration = Duration::from_millis(500);
+
+/// Height of a collapsed row
+const ROW_HEIGHT: f32 = 40.0;
+
+/// Space between the timestamp column and the preview
+const TIME_COLUMN_GAP: f32 = 8.0;
+/// Rows narrower than this hide the timestamp column, if enabled
+const NARROW_ROW_WIDTH: f32 = 360.0;
+
+/// Minimum time between window title updates
+const TITLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most toasts shown at once; older ones are dropped first
+const MAX_TOASTS: usize = 5;
+/// Seconds a toast takes to fade out at the end of its time
+const TOAST_FADE: f32 = 0.5;
+
+/// Interval between clipboard checks while the app is active
+const ACTIVE_REPAINT: Duration = Duration::from_millis(250);
+/// How long after the last input or capture the app counts as active
+const ACTIVE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How often clipboard text that isn't valid UTF-8 is read again while it
+/// stays on the clipboard
+const INVALID_TEXT_RECHECK: Duration = Duration::from_secs(1);
+
+/// Durations offered for temporary pins, with their menu labels
+const TEMPORARY_PINS: [(&str, i64); 4] = [
+    ("1 hour", 1),
+    ("8 hours", 8),
+    ("1 day", 24),
+    ("1 week", 24 * 7),
+];
+
+/// Age up to which entries are drawn at full strength when fading old ones
+const FRESH_FOR: chrono::Duration = chrono::Duration::hours(1);
+/// Age at which old entries are drawn faintest
+const STALE_AFTER: chrono::Duration = chrono::Duration::days(7);
+/// Opacity of the faintest old entries
+const STALE_OPACITY: f32 = 0.45;
+
+/// Initial height of the details pane in the split layout
+const DEFAULT_SPLIT_HEIGHT: f32 = 260.0;
+/// Smallest height the split layout's details pane can be dragged to
+const MIN_SPLIT_HEIGHT: f32 = 120.0;
+
+/// Days between automatic compactions of the history
+const COMPACT_INTERVAL_DAYS: i64 = 7;
+
+/// Size of the full window the first time it's opened, in points
+const DEFAULT_WINDOW_SIZE: [f32; 2] = [800.0, 600.0];
+
+/// How long after switching layouts the window's size isn't remembered, since
+/// it still reports the old size until the resize lands
+const GEOMETRY_SETTLE: Duration = Duration::from_millis(500);
+
+fn default_auto_paste() -> bool {
+    true
+}
+
+fn default_paste_ring_size() -> usize {
+    5
+}
+
+fn default_slot_count() -> u8 {
+    MAX_SLOTS
+}
+
+fn default_paste_ring_hotkey() -> String {
+    "Ctrl+Alt+V".to_owned()
+}
+
+fn default_similarity_threshold() -> f32 {
+    0.9
+}
+
+fn default_search_min_chars() -> usize {
+    1
+}
+
+fn default_flash_captures() -> bool {
+    true
+}
+
+fn default_limit_warning() -> Option<u8> {
+    Some(90)
+}
+
+fn default_wipe_after_secs() -> u64 {
+    30
+}
+
+fn default_monitoring_enabled() -> bool {
+    true
+}
+
+fn default_accent_color() -> [u8; 3] {
+    let [r, g, b, _] = Color32::LIGHT_BLUE.to_array();
+    [r, g, b]
+}
+
+fn default_max_expanded_lines() -> usize {
+    10
+}
+
+fn default_time_format() -> String {
+    DEFAULT_TIME_FORMAT.to_owned()
+}
+
+fn default_preview_chars() -> usize {
+    DEFAULT_PREVIEW_CHARS
+}
+
+fn default_preview_share() -> f32 {
+    0.7
+}
+
+fn default_split_height() -> f32 {
+    DEFAULT_SPLIT_HEIGHT
+}
+
+fn default_hide_time_when_narrow() -> bool {
+    true
+}
+
+fn default_show_hotkey() -> String {
+    "Ctrl+Alt+K".to_owned()
+}
+
+fn default_panic_hotkey() -> String {
+    "Ctrl+Alt+Shift+K".to_owned()
+}
+
+fn default_clear_shortcut() -> String {
+    "Ctrl+Shift+Delete".to_owned()
+}
+
+fn default_auto_pin_threshold() -> u32 {
+    10
+}
+
+fn default_min_capture_chars() -> usize {
+    1
+}
+
+fn default_idle_repaint_secs() -> u64 {
+    3
+}
+
+fn default_occurrences() -> u32 {
+    1
+}
+
+fn default_dedup_includes_pinned() -> bool {
+    true
+}
+
+fn default_max_trash() -> usize {
+    50
+}
+
+fn default_trash_retention_days() -> u64 {
+    30
+}
+
+fn default_auto_backup() -> bool {
+    true
+}
+
+fn default_backup_interval_mins() -> u64 {
+    60
+}
+
+fn default_max_backups() -> usize {
+    10
+}
+
+fn default_preview_imports() -> bool {
+    true
+}
+
+/// Which action buttons are shown inline on each row
+///
+/// Hidden actions stay available from the row's context menu.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct InlineActions {
+    copy: bool,
+    delete: bool,
+    pin: bool,
+}
+
+impl Default for InlineActions {
+    fn default() -> Self {
+        Self {
+            copy: true,
+            delete: true,
+            pin: true,
+        }
+    }
+}
+
+/// Split a comma-separated list, dropping empty items
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Where the data folder was found
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum StorageKind {
+    /// The platform config directory
+    #[default]
+    Config,
+    /// Next to the executable, because portable mode is on
+    Portable,
+    /// Next to the executable or in the working directory, because the
+    /// config directory is unavailable
+    Fallback,
+    /// A temporary folder holding sample data, because `--demo` was passed
+    Demo,
+}
+
+/// Marker file next to the executable that turns on portable mode
+const PORTABLE_MARKER: &str = "portable.txt";
+/// Command-line flag that turns on portable mode
+const PORTABLE_FLAG: &str = "--portable";
+
+/// Returns the `klippy-data` folder next to the executable
+fn exe_data_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("klippy-data")))
+}
+
+/// Returns true if data should be kept next to the executable, either
+/// because `portable.txt` sits beside it or `--portable` was passed
+fn is_portable() -> bool {
+    let marker = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(PORTABLE_MARKER)))
+        .is_some_and(|marker| marker.is_file());
+    marker || std::env::args().skip(1).any(|arg| arg == PORTABLE_FLAG)
+}
+
+/// Returns the folder to keep data in, and how it was chosen
+///
+/// In portable mode only `klippy-data` next to the executable is used, so
+/// nothing is written to the host machine. Otherwise prefers the platform
+/// config directory. Without one (e.g. no home directory in a container),
+/// uses `klippy-data` next to the executable, then in the working directory.
+/// Returns None if none of them can be created. With `--demo`, only the
+/// temporary sample data folder is used.
+fn storage_dir() -> Option<(PathBuf, StorageKind)> {
+    if demo::is_demo() {
+        let dir = demo::data_dir();
+        return fs::create_dir_all(&dir)
+            .is_ok()
+            .then_some((dir, StorageKind::Demo));
+    }
+    if is_portable() {
+        return exe_data_dir()
+            .filter(|dir| fs::create_dir_all(dir).is_ok())
+            .map(|dir| (dir, StorageKind::Portable));
+    }
+
+    let config = ProjectDirs::from("com", "klippy", "klippy").map(|d| d.config_dir().to_owned());
+    let fallbacks = [
+        exe_data_dir(),
+        std::env::current_dir()
+            .ok()
+            .map(|dir| dir.join("klippy-data")),
+    ];
+
+    std::iter::once((config, StorageKind::Config))
+        .chain(
+            fallbacks
+                .into_iter()
+                .map(|dir| (dir, StorageKind::Fallback)),
+        )
+        .filter_map(|(dir, kind)| Some((dir?, kind)))
+        .find(|(dir, _)| fs::create_dir_all(dir).is_ok())
+}
+
+/// User preferences
+///
+/// Stored in the data file alongside the history, but separate from it so
+/// settings can be exported and imported on their own.
+#[derive(Serialize, Deserialize)]
+struct Settings {
+    /// Maximum number of entries kept in the trash
+    #[serde(default = "default_max_trash")]
+    max_trash: usize,
+    /// Days after which trashed entries are purged
+    #[serde(default = "default_trash_retention_days")]
+    trash_retention_days: u64,
+    /// Maximum number of entries to keep
+    max_entries: usize,
+    /// Maximum total size of the history in megabytes, if limited
+    #[serde(default)]
+    max_total_mb: Option<u64>,
+    /// Percentage of the entry or size limit at which the top bar warns
+    #[serde(default = "default_limit_warning")]
+    limit_warning: Option<u8>,
+    /// What happens to new captures once pinned entries fill the history
+    #[serde(default)]
+    full_history: FullHistory,
+    /// Maximum number of pinned entries, if limited
+    #[serde(default)]
+    max_pinned: Option<usize>,
+    /// Ignore captures shorter than this many characters (ignoring surrounding whitespace)
+    #[serde(default = "default_min_capture_chars")]
+    min_capture_chars: usize,
+    /// How status messages are shown
+    #[serde(default)]
+    status_style: StatusStyle,
+    /// Corner toasts are stacked in
+    #[serde(default)]
+    toast_corner: ToastCorner,
+    /// Seconds between clipboard checks once the app is idle
+    #[serde(default = "default_idle_repaint_secs")]
+    idle_repaint_secs: u64,
+    /// Order in which entries are listed
+    #[serde(default)]
+    sort_mode: SortMode,
+    /// List pinned entries above unpinned ones, each group in the sort order
+    #[serde(default)]
+    pinned_first: bool,
+    /// List matching pinned entries first while searching, even when
+    /// `pinned_first` is off
+    #[serde(default)]
+    pinned_first_in_search: bool,
+    /// List entries in groups by content kind, each group in the sort order
+    #[serde(default)]
+    group_by_kind: bool,
+    /// Show the details panel for the selected entry
+    #[serde(default)]
+    show_details: bool,
+    /// Where the details panel goes
+    #[serde(default)]
+    details_layout: DetailsLayout,
+    /// Named queries offered in the saved searches menu
+    #[serde(default)]
+    saved_searches: Vec<SavedSearch>,
+    /// Only ignore re-copied content within this many seconds (None ignores forever)
+    #[serde(default)]
+    duplicate_window_secs: Option<u64>,
+    /// Count re-captures of an existing entry and move it to the top, instead
+    /// of ignoring them
+    #[serde(default)]
+    count_duplicates: bool,
+    /// Whether pinned entries count when checking for duplicates
+    ///
+    /// When false, re-copying a pinned entry's content creates a fresh
+    /// unpinned entry, and the duplicate window only ever moves unpinned
+    /// entries back to the top.
+    #[serde(default = "default_dedup_includes_pinned")]
+    dedup_includes_pinned: bool,
+    /// Update the latest entry in place when a capture only differs from it
+    /// by leading/trailing whitespace
+    #[serde(default)]
+    merge_whitespace_changes: bool,
+    /// Store captures without leading/trailing whitespace, keeping the
+    /// untrimmed text alongside so it can still be copied
+    #[serde(default)]
+    trim_on_capture: bool,
+    /// Replace a recent entry with a new capture that's nearly identical to it
+    #[serde(default)]
+    merge_similar: bool,
+    /// Similarity (0.0 to 1.0) above which captures are merged
+    #[serde(default = "default_similarity_threshold")]
+    similarity_threshold: f32,
+    /// Whether to back up the data file periodically, and on exit when the
+    /// newest backup is older than the interval
+    #[serde(default = "default_auto_backup")]
+    auto_backup: bool,
+    /// Minutes between automatic backups
+    #[serde(default = "default_backup_interval_mins")]
+    backup_interval_mins: u64,
+    /// Number of backups to keep before the oldest is removed
+    #[serde(default = "default_max_backups")]
+    max_backups: usize,
+    /// What happens to copied text that isn't valid UTF-8
+    #[serde(default)]
+    invalid_text: InvalidText,
+    /// Whether to compact the history every `COMPACT_INTERVAL_DAYS`
+    #[serde(default)]
+    auto_compact: bool,
+    /// Log routine details to the terminal, for troubleshooting
+    #[serde(default)]
+    verbose_logging: bool,
+    /// How the history is written to disk
+    #[serde(default)]
+    storage_format: StorageFormat,
+    /// Read the clipboard back after copying to check it changed
+    #[serde(default)]
+    verify_copies: bool,
+    /// Record captures, copies and removals in the capture log
+    #[serde(default)]
+    capture_log: bool,
+    /// Whether to list incoming entries for review before a merge import
+    #[serde(default = "default_preview_imports")]
+    preview_imports: bool,
+    /// Separator placed between clipboard contents and appended entries,
+    /// with `\n` and `\t` escapes
+    #[serde(default = "default_append_separator")]
+    append_separator: String,
+    /// Action buttons shown inline on each row
+    #[serde(default)]
+    inline_actions: InlineActions,
+    /// What a single click on a row does
+    #[serde(default)]
+    click_action: ClickAction,
+    /// Require a double-click on a row to copy it
+    #[serde(default)]
+    double_click_to_copy: bool,
+    /// Also put copied entries in the PRIMARY selection, for middle-click paste
+    #[serde(default)]
+    copy_to_primary: bool,
+    /// Add a marker entry when the clipboard is emptied, e.g. after cutting text
+    #[serde(default)]
+    record_clears: bool,
+    /// Briefly highlight newly captured rows
+    #[serde(default = "default_flash_captures")]
+    flash_captures: bool,
+    /// Draw older unpinned entries fainter the older they get
+    #[serde(default)]
+    fade_old_entries: bool,
+    /// Turn off the capture flash and egui's animations
+    #[serde(default)]
+    reduced_motion: Preference,
+    /// Strengthen text and border contrast
+    #[serde(default)]
+    high_contrast: Preference,
+    /// Minimum characters typed before the search filters the list
+    #[serde(default = "default_search_min_chars")]
+    search_min_chars: usize,
+    /// Pause in typing, in milliseconds, before the search is applied
+    #[serde(default)]
+    search_debounce_ms: u64,
+    /// Scroll the list back to the top when a new entry is captured
+    #[serde(default)]
+    scroll_to_new: bool,
+    /// Restore the list's scroll position on the next launch
+    #[serde(default)]
+    remember_scroll: bool,
+    /// Ask before deleting a single entry
+    #[serde(default)]
+    confirm_delete: bool,
+    /// Refuse to delete pinned entries until they're unpinned
+    #[serde(default)]
+    protect_pinned: bool,
+    /// Pin entries automatically once they've been copied `auto_pin_threshold` times
+    #[serde(default)]
+    auto_pin: bool,
+    /// Number of copies after which an entry is pinned automatically
+    #[serde(default = "default_auto_pin_threshold")]
+    auto_pin_threshold: u32,
+    /// Drop a single trailing newline when copying an entry out
+    #[serde(default)]
+    strip_trailing_newline: bool,
+    /// Accent color for highlights, selections and the status text, as sRGB
+    #[serde(default = "default_accent_color")]
+    accent_color: [u8; 3],
+    /// Light or dark appearance
+    #[serde(default)]
+    appearance: Appearance,
+    /// chrono format string for row timestamps
+    #[serde(default = "default_time_format")]
+    time_format: String,
+    /// Share of the row, after the timestamp, given to the preview column
+    #[serde(default = "default_preview_share")]
+    preview_share: f32,
+    /// Maximum number of characters shown in a row preview
+    #[serde(default = "default_preview_chars")]
+    preview_chars: usize,
+    /// Hide the timestamp column when the window is narrow
+    #[serde(default = "default_hide_time_when_narrow")]
+    hide_time_when_narrow: bool,
+    /// Most extra lines shown when a multi-line row is expanded
+    #[serde(default = "default_max_expanded_lines")]
+    max_expanded_lines: usize,
+    /// Paste into the focused application after a hotkey copies an entry
+    #[serde(default = "default_auto_paste")]
+    auto_paste: bool,
+    /// Whether the paste ring hotkey is registered
+    #[serde(default)]
+    paste_ring_enabled: bool,
+    /// Number of most recent entries the paste ring cycles through
+    #[serde(default = "default_paste_ring_size")]
+    paste_ring_size: usize,
+    /// Global hotkey that pastes the next paste ring entry
+    #[serde(default = "default_paste_ring_hotkey")]
+    paste_ring_hotkey: String,
+    /// Number of numbered slots offered, from 1 up
+    #[serde(default = "default_slot_count")]
+    slot_count: u8,
+    /// Keys that make up the slot hotkeys
+    #[serde(default)]
+    slot_keys: SlotKeys,
+    /// What closing the window does
+    #[serde(default)]
+    close_action: CloseAction,
+    /// Global hotkey that brings back a hidden window
+    #[serde(default = "default_show_hotkey")]
+    show_hotkey: String,
+    /// Whether the panic hotkey is registered
+    #[serde(default)]
+    panic_enabled: bool,
+    /// Global hotkey that hides the window and clears the system clipboard
+    #[serde(default = "default_panic_hotkey")]
+    panic_hotkey: String,
+    /// In-app shortcuts changed from their defaults
+    #[serde(default)]
+    keybindings: keymap::Bindings,
+    /// Obscure the preview of entries that look like secrets until revealed
+    #[serde(default)]
+    mask_secrets: bool,
+    /// Which kinds of secrets are masked
+    #[serde(default)]
+    secret_rules: SecretRules,
+    /// Which copies empty the system clipboard again after `wipe_after_secs`
+    #[serde(default)]
+    clipboard_wipe: ClipboardWipe,
+    /// Seconds after copying before the system clipboard is emptied
+    #[serde(default = "default_wipe_after_secs")]
+    wipe_after_secs: u64,
+    /// Only capture while one of these apps is in the foreground, if any are listed
+    #[serde(default)]
+    capture_allowlist: Vec<String>,
+    /// Never capture while one of these apps is in the foreground
+    #[serde(default)]
+    capture_denylist: Vec<String>,
+}
+
+/// Represents the main application state
+#[derive(Serialize, Deserialize)]
+struct ClipboardManager {
+    /// List of clipboard entries
+    entries: Vec<ClipboardEntry>,
+    /// Recently deleted entries, most recently deleted first
+    #[serde(default)]
+    trash: Vec<ClipboardEntry>,
+    /// Preferences, kept apart from the history so they can be exported
+    #[serde(flatten)]
+    settings: Settings,
+    /// Identifier given to the next new entry
+    #[serde(default)]
+    next_id: u64,
+    /// Whether new clipboard content is captured; paused until resumed, even
+    /// across restarts
+    #[serde(default = "default_monitoring_enabled")]
+    monitoring_enabled: bool,
+    /// Scroll position of the list, saved for the next launch
+    #[serde(default)]
+    scroll_offset: f32,
+    /// Height of the details pane in the split layout
+    #[serde(default = "default_split_height")]
+    split_height: f32,
+    /// Entries bound to the numbered slot hotkeys, by slot (1-9)
+    #[serde(default)]
+    slot_bindings: BTreeMap<u8, u64>,
+    /// Clear unpinned shortcut from data files written before `keybindings`
+    #[serde(default = "default_clear_shortcut", skip_serializing)]
+    clear_shortcut: String,
+    /// Free-form notes kept apart from the captured history
+    #[serde(default)]
+    scratchpad: String,
+    /// Window position and size, restored on the next launch
+    #[serde(default)]
+    window_geometry: Option<WindowGeometry>,
+    /// Whether the window shows the mini strip instead of the full layout
+    #[serde(default)]
+    mini_mode: bool,
+    /// Mini strip position and size, kept apart from the full window's
+    #[serde(default)]
+    mini_geometry: Option<WindowGeometry>,
+    /// When the history was last compacted
+    #[serde(default)]
+    last_compact: Option<DateTime<Local>>,
+    /// Counts snapshots of the data file, so a journal left over from an
+    /// earlier one is recognised and ignored
+    #[serde(default)]
+    snapshot_generation: u64,
+    /// When this run of the app started
+    #[serde(skip, default = "Local::now")]
+    session_start: DateTime<Local>,
+    /// When the last automatic backup was made (or the app started)
+    #[serde(skip, default = "Instant::now")]
+    last_backup: Instant,
+    /// Backups listed in the settings, newest first; listed again when the
+    /// settings are next shown after this is cleared
+    #[serde(skip)]
+    backups: Option<Vec<PathBuf>>,
+    /// Consecutive failed clipboard reads
+    #[serde(skip)]
+    read_errors: u32,
+    /// Earliest time to read the clipboard again while backing off
+    #[serde(skip)]
+    next_read: Option<Instant>,
+    /// Last user input or capture, which keeps the clipboard checked often
+    #[serde(skip, default = "Instant::now")]
+    last_activity: Instant,
+    /// Path to save application data
+    #[serde(skip)]
+    save_path: Option<PathBuf>,
+    /// Clipboard context for interaction with system clipboard
+    #[serde(skip)]
+    clipboard_ctx: Option<ClipboardContext>,
+    /// Current clipboard content for change detection
+    #[serde(skip)]
+    current_clipboard: String,
+    /// Search term as typed in the search box
+    #[serde(skip)]
+    search_term: String,
+    /// Search term the list is filtered by, once past the minimum length and debounce
+    #[serde(skip)]
+    applied_search: String,
+    /// When the search box was last edited
+    #[serde(skip)]
+    search_edited: Option<Instant>,
+    /// Only list entries captured since the app started
+    #[serde(skip)]
+    session_only: bool,
+    /// Status messages being shown, oldest first, with their seconds left
+    #[serde(skip)]
+    status_messages: Vec<(String, f32)>,
+    /// Input time of the last frame that aged the status messages
+    #[serde(skip)]
+    status_time: Option<f64>,
+    /// Copied text to remove from the system clipboard, and when
+    #[serde(skip)]
+    pending_wipe: Option<(String, Instant)>,
+    /// What's on disk, when saving appends to the JSON Lines journal
+    #[serde(skip)]
+    journal: Option<journal::Journal>,
+    #[serde(skip)]
+    show_settings_window: bool,
+    #[serde(skip)]
+    show_trash_window: bool,
+    /// Registered global hotkeys
+    #[serde(skip)]
+    hotkeys: Option<Hotkeys>,
+    /// Position of the next entry in the paste ring
+    #[serde(skip)]
+    ring_position: usize,
+    /// Full-content viewer, when open
+    #[serde(skip)]
+    viewer: Option<ContentViewer>,
+    /// Content of the selected entry in the split layout
+    #[serde(skip)]
+    split_viewer: Option<ContentViewer>,
+    /// Running total of the bytes stored across all entries
+    #[serde(skip)]
+    total_bytes: usize,
+    /// Entries read from a file, waiting for confirmation to replace the history
+    #[serde(skip)]
+    pending_import: Option<Vec<ClipboardEntry>>,
+    /// Entries read from a file, waiting for review before being merged
+    #[serde(skip)]
+    import_preview: Option<ImportPreview>,
+    /// First-run setup, shown until completed or skipped
+    #[serde(skip)]
+    setup: Option<Setup>,
+    /// Content kind groups folded away while grouping by kind
+    #[serde(skip)]
+    collapsed_groups: HashSet<ContentKind>,
+    /// Action waiting for its new shortcut to be pressed
+    #[serde(skip)]
+    recording_key: Option<KeyAction>,
+    /// Move the keyboard focus to the search box next frame
+    #[serde(skip)]
+    focus_search: bool,
+    /// Ids of masked entries revealed this session
+    #[serde(skip)]
+    revealed: HashSet<u64>,
+    /// Number of clipboard changes captured this session
+    #[serde(skip)]
+    session_captures: usize,
+    /// Title last sent to the window, and when
+    #[serde(skip)]
+    window_title: Option<(String, Instant)>,
+    /// Set when the user explicitly quits, so closing isn't intercepted
+    #[serde(skip)]
+    quitting: bool,
+    /// Until when the window geometry isn't recorded, after switching layouts
+    #[serde(skip)]
+    geometry_settles: Option<Instant>,
+    /// Entry waiting for delete confirmation
+    #[serde(skip)]
+    pending_delete: Option<u64>,
+    /// Unpinned entries waiting for confirmation to be cleared
+    #[serde(skip)]
+    pending_clear: Option<ClearScope>,
+    /// Age in days offered for clearing old unpinned entries
+    #[serde(skip)]
+    clear_older_days: u64,
+    /// "Copy as link" prompt, when open
+    #[serde(skip)]
+    link_prompt: Option<LinkPrompt>,
+    /// Name typed for saving the current search
+    #[serde(skip)]
+    new_search_name: String,
+    /// Saved search being renamed, and the new name typed so far
+    #[serde(skip)]
+    renaming_search: Option<(usize, String)>,
+    /// Whether Klippy is registered to launch on login, read from the system
+    #[serde(skip)]
+    autostart: bool,
+    /// Ids of multi-line entries expanded in the list
+    #[serde(skip)]
+    expanded: HashSet<u64>,
+    /// Watches the data file for changes made by other programs
+    #[serde(skip)]
+    data_watcher: Option<DataWatcher>,
+    /// Modification times of the data file and journal after our last save
+    #[serde(skip)]
+    saved_mtimes: [Option<SystemTime>; 2],
+    /// When the data was last saved and whether it succeeded
+    #[serde(skip)]
+    last_save: Option<(DateTime<Local>, Result<(), String>)>,
+    /// Where the data folder was found
+    #[serde(skip)]
+    storage_kind: StorageKind,
+    /// Entry highlighted for keyboard actions
+    #[serde(skip)]
+    selected: Option<u64>,
+    /// Size counts of the entry in the details panel
+    #[serde(skip)]
+    detail_counts: Option<details::Counts>,
+    /// Scroll the selected entry into view on the next frame
+    #[serde(skip)]
+    scroll_to_selected: bool,
+    /// Scroll the list to the top on the next frame
+    #[serde(skip)]
+    scroll_to_top: bool,
+    /// Scroll the list to the saved position on the first frame
+    #[serde(skip)]
+    restore_scroll: bool,
+    /// Newest captured entry and when it was captured, for the highlight flash
+    #[serde(skip)]
+    flash: Option<(u64, Instant)>,
+    /// PRIMARY selection connection, opened on first use
+    #[serde(skip)]
+    primary: Option<PrimarySelection>,
+    /// Entry marked as the first side of a comparison
+    #[serde(skip)]
+    compare_base: Option<u64>,
+    /// Comparison window, when open
+    #[serde(skip)]
+    compare: Option<CompareView>,
+    /// User-defined transforms offered in the context menu
+    #[serde(skip)]
+    transforms: Vec<transforms::Transform>,
+    /// Transform whose result is being waited for
+    #[serde(skip)]
+    running_transform: Option<transforms::Running>,
+    /// Import of the OS clipboard history being read
+    #[serde(skip)]
+    os_import: Option<os_history::Import>,
+    /// Formats of new captures still being listed
+    #[serde(skip)]
+    format_listings: Vec<formats::Listing>,
+    /// Tasks last registered in the Windows jump list
+    #[serde(skip)]
+    jump_list: Option<Vec<jump_list::Task>>,
+    /// Command palette, when open
+    #[serde(skip)]
+    palette: Option<CommandPalette>,
+    /// Quick open overlay, when open
+    #[serde(skip)]
+    quick_open: Option<QuickOpen>,
+    /// Accessibility preferences reported by the system
+    #[serde(skip)]
+    system_preferences: SystemPreferences,
+}
+
+impl Settings {
+    /// Bring values edited outside the settings window back within the
+    /// ranges the window allows
+    fn sanitize(&mut self) {
+        self.max_entries = self.max_entries.clamp(10, 500);
+        self.max_pinned = self.max_pinned.map(|max| max.clamp(1, 500));
+        self.limit_warning = self.limit_warning.map(|percent| percent.clamp(50, 100));
+        self.min_capture_chars = self.min_capture_chars.clamp(1, 100);
+        self.idle_repaint_secs = self.idle_repaint_secs.clamp(1, 60);
+        self.similarity_threshold = self.similarity_threshold.clamp(0.5, 0.99);
+        self.preview_chars = self.preview_chars.clamp(MIN_PREVIEW_CHARS, 200);
+        self.preview_share = self.preview_share.clamp(0.4, 0.9);
+        self.max_expanded_lines = self.max_expanded_lines.clamp(1, 100);
+        self.search_min_chars = self.search_min_chars.clamp(1, 10);
+        self.paste_ring_size = self.paste_ring_size.clamp(2, 20);
+        self.max_trash = self.max_trash.min(500);
+        self.trash_retention_days = self.trash_retention_days.clamp(1, 365);
+        if !is_valid_time_format(&self.time_format) {
+            self.time_format = default_time_format();
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            max_trash: default_max_trash(),
+            trash_retention_days: default_trash_retention_days(),
+            max_entries: 50,
+            max_total_mb: None,
+            limit_warning: default_limit_warning(),
+            full_history: FullHistory::default(),
+            max_pinned: None,
+            min_capture_chars: default_min_capture_chars(),
+            idle_repaint_secs: default_idle_repaint_secs(),
+            status_style: StatusStyle::default(),
+            toast_corner: ToastCorner::default(),
+            sort_mode: SortMode::default(),
+            pinned_first: false,
+            pinned_first_in_search: false,
+            group_by_kind: false,
+            show_details: false,
+            details_layout: DetailsLayout::default(),
+            saved_searches: Vec::new(),
+            duplicate_window_secs: None,
+            count_duplicates: false,
+            dedup_includes_pinned: default_dedup_includes_pinned(),
+            merge_whitespace_changes: false,
+            trim_on_capture: false,
+            merge_similar: false,
+            similarity_threshold: default_similarity_threshold(),
+            auto_backup: default_auto_backup(),
+            backup_interval_mins: default_backup_interval_mins(),
+            max_backups: default_max_backups(),
+            invalid_text: InvalidText::default(),
+            auto_compact: false,
+            verbose_logging: false,
+            storage_format: StorageFormat::default(),
+            verify_copies: false,
+            capture_log: false,
+            preview_imports: default_preview_imports(),
+            append_separator: default_append_separator(),
+            inline_actions: InlineActions::default(),
+            click_action: ClickAction::default(),
+            double_click_to_copy: false,
+            copy_to_primary: false,
+            record_clears: false,
+            flash_captures: default_flash_captures(),
+            fade_old_entries: false,
+            reduced_motion: Preference::System,
+            high_contrast: Preference::System,
+            search_min_chars: default_search_min_chars(),
+            search_debounce_ms: 0,
+            scroll_to_new: false,
+            remember_scroll: false,
+            confirm_delete: false,
+            protect_pinned: false,
+            auto_pin: false,
+            auto_pin_threshold: default_auto_pin_threshold(),
+            strip_trailing_newline: false,
+            accent_color: default_accent_color(),
+            appearance: Appearance::default(),
+            time_format: default_time_format(),
+            preview_share: default_preview_share(),
+            preview_chars: default_preview_chars(),
+            hide_time_when_narrow: default_hide_time_when_narrow(),
+            max_expanded_lines: default_max_expanded_lines(),
+            auto_paste: default_auto_paste(),
+            paste_ring_enabled: false,
+            paste_ring_size: default_paste_ring_size(),
+            paste_ring_hotkey: default_paste_ring_hotkey(),
+            slot_count: default_slot_count(),
+            slot_keys: SlotKeys::default(),
+            close_action: CloseAction::default(),
+            show_hotkey: default_show_hotkey(),
+            panic_enabled: false,
+            panic_hotkey: default_panic_hotkey(),
+            keybindings: keymap::Bindings::new(),
+            mask_secrets: false,
+            capture_allowlist: Vec::new(),
+            capture_denylist: Vec::new(),
+            secret_rules: SecretRules::default(),
+            clipboard_wipe: ClipboardWipe::default(),
+            wipe_after_secs: default_wipe_after_secs(),
+        }
+    }
+}
+
+impl Default for ClipboardManager {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            trash: Vec::new(),
+            settings: Settings::default(),
+            collapsed_groups: HashSet::new(),
+            setup: None,
+            next_id: 1,
+            monitoring_enabled: default_monitoring_enabled(),
+            scroll_offset: 0.0,
+            split_height: default_split_height(),
+            slot_bindings: BTreeMap::new(),
+            clear_shortcut: default_clear_shortcut(),
+            recording_key: None,
+            focus_search: false,
+            scratchpad: String::new(),
+            window_geometry: None,
+            mini_mode: false,
+            mini_geometry: None,
+            last_compact: None,
+            snapshot_generation: 0,
+            session_start: Local::now(),
+            last_backup: Instant::now(),
+            backups: None,
+            read_errors: 0,
+            next_read: None,
+            last_activity: Instant::now(),
+            save_path: None,
+            clipboard_ctx: None,
+            current_clipboard: String::new(),
+            search_term: String::new(),
+            applied_search: String::new(),
+            search_edited: None,
+            session_only: false,
+            status_messages: Vec::new(),
+            status_time: None,
+            pending_wipe: None,
+            journal: None,
+            show_settings_window: false,
+            show_trash_window: false,
+            hotkeys: None,
+            ring_position: 0,
+            viewer: None,
+            split_viewer: None,
+            total_bytes: 0,
+            pending_import: None,
+            import_preview: None,
+            revealed: HashSet::new(),
+            session_captures: 0,
+            window_title: None,
+            quitting: false,
+            geometry_settles: None,
+            pending_delete: None,
+            pending_clear: None,
+            clear_older_days: 30,
+            link_prompt: None,
+            new_search_name: String::new(),
+            renaming_search: None,
+            autostart: false,
+            expanded: HashSet::new(),
+            data_watcher: None,
+            saved_mtimes: [None; 2],
+            last_save: None,
+            storage_kind: StorageKind::Config,
+            selected: None,
+            detail_counts: None,
+            scroll_to_selected: false,
+            scroll_to_top: false,
+            restore_scroll: false,
+            flash: None,
+            primary: None,
+            compare_base: None,
+            compare: None,
+            transforms: Vec::new(),
+            running_transform: None,
+            os_import: None,
+            format_listings: Vec::new(),
+            jump_list: None,
+            palette: None,
+            quick_open: None,
+            system_preferences: SystemPreferences::default(),
+        }
+    }
+}
+
+impl ClipboardManager {
+    /// Initialize the application with saved data if available
+    fn new() -> Self {
+        let mut app = Self {
+            autostart: autostart::is_enabled(),
+            ..Self::default()
+        };
+
+        match ClipboardProvider::new() {
+            Ok(ctx) => app.clipboard_ctx = Some(ctx),
+            Err(e) => app.report(&Error::Clipboard(e.to_string()), 8.0),
+        }
+
+        // Set up save path: next to the executable in portable mode, otherwise
+        // the config directory, falling back to a folder next to the executable
+        // or the working directory when there's no usable config directory
+        match storage_dir() {
+            Some((dir, kind)) => {
+                app.save_path = Some(dir.join("data.json"));
+                app.storage_kind = kind;
+                if kind == StorageKind::Fallback {
+                    let message =
+                        format!("Config directory unavailable, saving to {}", dir.display());
+                    log::warn!("{}", message);
+                    app.set_status(&message, 8.0);
+                }
+                if kind == StorageKind::Demo {
+                    app.set_status(
+                        "Demo mode: showing sample data, your history is untouched",
+                        8.0,
+                    );
+                }
+            }
+            None if is_portable() => {
+                let message = "Portable folder isn't writable, history won't be saved";
+                log::warn!("{}", message);
+                app.set_status(message, 8.0);
+            }
+            None => {
+                let message = "No writable storage location, history won't be saved";
+                log::warn!("{}", message);
+                app.set_status(message, 8.0);
+            }
+        }
+
+        // Without a data file this is the first run, so offer the setup
+        if app.save_path.as_ref().is_some_and(|path| !path.exists()) {
+            app.setup = Some(Setup::new(
+                app.settings.appearance,
+                app.settings.max_entries,
+                &app.settings.show_hotkey,
+            ));
+        }
+
+        // Load saved data
+        if let Some(path) = app.save_path.clone().filter(|path| path.exists()) {
+            match fs::read_to_string(&path) {
+                Ok(data) => match serde_json::from_str::<ClipboardManager>(&data) {
+                    Ok(mut loaded) => {
+                        let replayed = journal::replay(&mut loaded, &journal::path_for(&path));
+                        if replayed > 0 {
+                            log::debug!("Applied {} journal lines", replayed);
+                        }
+                        app.apply_loaded(loaded);
+                    }
+                    Err(e) => {
+                        log::error!("{}", Error::Json(e));
+                        app.recover_data(&path, &data);
+                    }
+                },
+                // Saving now would overwrite history we just couldn't read
+                Err(e) => {
+                    app.report(&Error::Read(path, e), 10.0);
+                    app.save_path = None;
+                }
+            }
+        }
+        logging::set_verbose(app.settings.verbose_logging);
+
+        app.reload_transforms();
+
+        if !app.monitoring_enabled {
+            app.set_status("Capturing is paused: nothing you copy is recorded", 8.0);
+        }
+
+        app
+    }
+
+    /// Salvage what we can from a data file that failed to parse
+    ///
+    /// Older versions didn't write the file atomically, so it may have been
+    /// cut off. The damaged file is copied aside first, since the next save
+    /// overwrites it, then the complete entries before the damage are kept.
+    /// Settings fall back to their defaults.
+    fn recover_data(&mut self, path: &Path, data: &str) {
+        let copy = path.with_extension(format!(
+            "damaged-{}.json",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let kept = match fs::copy(path, &copy) {
+            Ok(_) => format!("the damaged file was kept as {}", copy.display()),
+            Err(e) => format!("the damaged file couldn't be kept: {}", e),
+        };
+
+        self.entries = transfer::recover_entries(data);
+        self.assign_missing_ids();
+        self.normalize_order();
+        self.recompute_total_bytes();
+
+        let message = format!(
+            "Data file was damaged: recovered {} entries, {}",
+            self.entries.len(),
+            kept
+        );
+        log::warn!("{}", message);
+        self.set_status(&message, 10.0);
+    }
+
+    /// Give entries from older data files (without ids) a unique id
+    fn assign_missing_ids(&mut self) {
+        let max_id = self
+            .entries
+            .iter()
+            .chain(&self.trash)
+            .map(|e| e.id)
+            .max()
+            .unwrap_or(0);
+        self.next_id = self.next_id.max(max_id + 1);
+
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..self.entries.len() {
+            if self.entries[i].id == 0 || !seen.insert(self.entries[i].id) {
+                self.entries[i].id = self.next_id;
+                self.next_id += 1;
+            }
+        }
+    }
+
+    /// Take over the history and settings read from the data file
+    fn apply_loaded(&mut self, loaded: ClipboardManager) {
+        self.entries = loaded.entries;
+        self.settings = loaded.settings;
+        self.trash = loaded.trash;
+        self.next_id = loaded.next_id;
+        self.monitoring_enabled = loaded.monitoring_enabled;
+        self.scroll_offset = loaded.scroll_offset;
+        self.split_height = loaded.split_height;
+        self.restore_scroll = self.settings.remember_scroll;
+        self.slot_bindings = loaded.slot_bindings;
+        if loaded.clear_shortcut != default_clear_shortcut() {
+            self.settings
+                .keybindings
+                .entry(KeyAction::ClearUnpinned)
+                .or_insert(loaded.clear_shortcut);
+        }
+        self.scratchpad = loaded.scratchpad;
+        self.window_geometry = loaded.window_geometry;
+        self.mini_mode = loaded.mini_mode;
+        self.mini_geometry = loaded.mini_geometry;
+        self.last_compact = loaded.last_compact;
+        self.snapshot_generation = loaded.snapshot_generation;
+        self.assign_missing_ids();
+        self.normalize_order();
+        self.recompute_total_bytes();
+        self.purge_trash();
+    }
+
+    /// Put loaded entries in a consistent order: newest first, by capture time
+    ///
+    /// Capturing relies on the newest entry being first, so pinned entries
+    /// stay in capture order here; `pinned_first` only changes the listing.
+    fn normalize_order(&mut self) {
+        self.entries
+            .sort_by_key(|e| std::cmp::Reverse(e.capture_order()));
+    }
+
+    /// Recalculate the running byte total after bulk changes to the entries
+    fn recompute_total_bytes(&mut self) {
+        self.total_bytes = self.entries.iter().map(|e| e.content.len()).sum();
+    }
+
+    /// Returns the current index of the entry with the given id
+    fn entry_index(&self, id: u64) -> Option<usize> {
+        self.entries.iter().position(|e| e.id == id)
+    }
+
+    /// Save application data to disk, reporting failures in the status bar
+    fn save_data(&mut self) {
+        if let Err(message) = self.try_save() {
+            log::error!("{}", message);
+            self.set_status(&message, 5.0);
+        }
+    }
+
+    /// Save application data to disk, remembering the outcome for diagnostics
+    fn try_save(&mut self) -> Result<(), String> {
+        let result = self.write_data().map_err(String::from);
+        if self.save_path.is_some() {
+            self.last_save = Some((Local::now(), result.clone()));
+            self.update_jump_list();
+        }
+        result
+    }
+
+    /// Offer the first pinned entries in the jump list, if they've changed
+    ///
+    /// Entries that look like secrets are listed without their content, since
+    /// the jump list shows whenever the taskbar button is right-clicked.
+    fn update_jump_list(&mut self) {
+        if !jump_list::is_supported() {
+            return;
+        }
+        let tasks: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.pinned)
+            .take(jump_list::MAX_TASKS)
+            .enumerate()
+            .map(|(index, entry)| jump_list::Task {
+                title: if entry.looks_like_secret(&self.settings.secret_rules) {
+                    format!("Pinned entry {} (hidden)", index + 1)
+                } else {
+                    entry.preview(JUMP_LIST_PREVIEW_CHARS)
+                },
+                position: index + 1,
+            })
+            .collect();
+        if self.jump_list.as_ref() != Some(&tasks) {
+            jump_list::update(tasks.clone());
+            self.jump_list = Some(tasks);
+        }
+    }
+
+    /// Write application data to disk
+    ///
+    /// In the JSON Lines format only the changes are appended to the journal,
+    /// until it's full or nothing is known about what's on disk yet; clearing
+    /// `journal` forces a full snapshot.
+    fn write_data(&mut self) -> Result<(), Error> {
+        let Some(path) = self.save_path.clone() else {
+            return Ok(());
+        };
+        if self.settings.storage_format == StorageFormat::JsonLines {
+            if let Some(mut journal) = self.journal.take().filter(|j| !journal::is_full(j)) {
+                let result = journal::append(&mut journal, self, &journal::path_for(&path));
+                self.journal = Some(journal);
+                self.saved_mtimes = data_mtimes(&path);
+                return result;
+            }
+        }
+        self.write_snapshot(&path)
+    }
+
+    /// Write all application data to the data file, emptying any journal
+    ///
+    /// The data is written to a temporary file that then replaces the data
+    /// file, so a crash midway leaves the previous snapshot intact. Each
+    /// snapshot gets a new generation, and a journal that survives a crash
+    /// before it's removed belongs to an older one, so it isn't replayed.
+    fn write_snapshot(&mut self, path: &Path) -> Result<(), Error> {
+        self.snapshot_generation += 1;
+        let json = serde_json::to_string(self)?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, json)
+            .and_then(|()| fs::rename(&temp, path))
+            .map_err(|e| Error::Write(path.to_path_buf(), e))?;
+
+        let journal_path = journal::path_for(path);
+        if journal_path.exists() {
+            fs::remove_file(&journal_path).map_err(|e| Error::Write(journal_path, e))?;
+        }
+        // Remember our own write so the watcher doesn't reload it
+        self.saved_mtimes = data_mtimes(path);
+        self.journal = match self.settings.storage_format {
+            StorageFormat::Json => None,
+            StorageFormat::JsonLines => Some(journal::after_snapshot(self)?),
+        };
+        Ok(())
+    }
+
+    /// Write the data to the temp folder when the normal save fails, returning its path
+    fn emergency_save(&self) -> Result<PathBuf, Error> {
+        let path = std::env::temp_dir().join(format!(
+            "klippy-data-{}.json",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let json = serde_json::to_string(self)?;
+        match fs::write(&path, json) {
+            Ok(()) => Ok(path),
+            Err(e) => Err(Error::Write(path, e)),
+        }
+    }
+
+    /// Start watching the data file for external changes
+    fn start_watching(&mut self, ctx: &Context) {
+        let Some(path) = &self.save_path else {
+            return;
+        };
+        match DataWatcher::new(&[path.clone(), journal::path_for(path)], ctx) {
+            Ok(watcher) => {
+                self.saved_mtimes = data_mtimes(path);
+                self.data_watcher = Some(watcher);
+            }
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Merge in entries from the data file if another program changed it
+    ///
+    /// Entries are merged rather than replaced, so nothing captured locally is
+    /// lost. Local entries missing from the file are kept and reported, since
+    /// they may have been deleted elsewhere. Entries on both sides have their
+    /// details reconciled with `reconcile_entry`.
+    fn reload_if_changed(&mut self) {
+        if !self.data_watcher.as_ref().is_some_and(DataWatcher::poll) {
+            return;
+        }
+        let Some(path) = self.save_path.clone() else {
+            return;
+        };
+        let mtimes = data_mtimes(&path);
+        if mtimes[0].is_none() || mtimes == self.saved_mtimes {
+            return;
+        }
+
+        // A partially written file fails to parse; the next write wakes us
+        // again. Changes only in the journal so far are included.
+        let Some(mut loaded) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<ClipboardManager>(&data).ok())
+        else {
+            return;
+        };
+        journal::replay(&mut loaded, &journal::path_for(&path));
+        let incoming = loaded.entries;
+        let local_only = self
+            .entries
+            .iter()
+            .filter(|e| !incoming.iter().any(|i| i.content == e.content))
+            .count();
+        let mut updated = 0;
+        for other in &incoming {
+            if let Some(entry) = self.entries.iter_mut().find(|e| e.content == other.content) {
+                if reconcile_entry(entry, other) {
+                    updated += 1;
+                }
+            }
+        }
+        let before = self.entries.len();
+        self.merge_entries(incoming);
+        let added = self.entries.len().saturating_sub(before);
+
+        let mut summary = format!("Data file changed on disk: merged {} entries", added);
+        if updated > 0 {
+            summary.push_str(&format!(", updated {}", updated));
+        }
+        if local_only > 0 {
+            self.set_status(
+                &format!(
+                    "{}, kept {} local entries missing from it",
+                    summary, local_only
+                ),
+                5.0,
+            );
+        } else {
+            self.set_status(&summary, 3.0);
+        }
+    }
+
+    /// Add a new entry to the clipboard history
+    fn add_entry(&mut self, content: String) {
+        self.add_capture(content, false);
+    }
+
+    /// Add new clipboard content, recording whether invalid bytes were
+    /// replaced to store it
+    fn add_capture(&mut self, content: String, lossy: bool) {
+        // Don't add empty content
+        if content.trim().is_empty() {
+            return;
+        }
+
+        // Keep the untrimmed text only when trimming changed something
+        let (content, raw) = match content.trim() {
+            trimmed if self.settings.trim_on_capture && trimmed.len() != content.len() => {
+                (trimmed.to_owned(), Some(content))
+            }
+            _ => (content, None),
+        };
+
+        // Collapse repeated captures of the same text with different padding
+        // into the latest entry. The duplicate settings apply as they do to
+        // exact copies: pinned entries are only updated if they count as
+        // duplicates, and the re-capture is counted when counting duplicates.
+        // With trimming on the text is already equal, so only the untrimmed
+        // version kept alongside it changes.
+        if self.settings.merge_whitespace_changes {
+            let include_pinned = self.settings.dedup_includes_pinned;
+            if let Some(latest) = self.entries.first_mut().filter(|e| {
+                !e.locked
+                    && (include_pinned || !e.pinned)
+                    && e.content.trim() == content.trim()
+                    && (e.content != content || e.raw != raw)
+            }) {
+                self.total_bytes = self.total_bytes + content.len() - latest.content.len();
+                latest.set_content(content);
+                latest.raw = raw;
+                latest.lossy = lossy;
+                latest.timestamp = Local::now();
+                if self.settings.count_duplicates {
+                    latest.occurrences += 1;
+                }
+                self.save_data();
+                return;
+            }
+        }
+
+        // Ignore duplicates, unless a duplicate window is set and the existing
+        // entry is older than it, in which case it moves back to the top.
+        // When counting duplicates, every re-capture counts and moves it up.
+        if let Some(pos) = self.entries.iter().position(|e| {
+            e.content == content && (self.settings.dedup_includes_pinned || !e.pinned)
+        }) {
+            if self.settings.count_duplicates {
+                let mut entry = self.entries.remove(pos);
+                entry.occurrences += 1;
+                entry.timestamp = Local::now();
+                self.flash = Some((entry.id, Instant::now()));
+                self.entries.insert(0, entry);
+                self.scroll_to_top = self.settings.scroll_to_new;
+                self.save_data();
+                return;
+            }
+            let expired = self.settings.duplicate_window_secs.is_some_and(|secs| {
+                (Local::now() - self.entries[pos].timestamp).num_seconds() >= secs as i64
+            });
+            if expired {
+                let mut entry = self.entries.remove(pos);
+                entry.timestamp = Local::now();
+                self.entries.insert(0, entry);
+                self.save_data();
+            }
+            return;
+        }
+
+        if self.history_blocked() {
+            self.set_status(
+                "Not captured: every entry is pinned, unpin some or raise the limit",
+                4.0,
+            );
+            return;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut entry = ClipboardEntry::new(id, content);
+        entry.raw = raw;
+        entry.lossy = lossy;
+
+        // Replace a nearly identical recent entry, keeping the older version
+        // in the trash so it can still be restored
+        if self.settings.merge_similar {
+            if let Some(pos) = self.entries.iter().take(SIMILARITY_WINDOW).position(|e| {
+                !e.pinned
+                    && !e.locked
+                    && detect::similarity(&e.content, &entry.content)
+                        >= self.settings.similarity_threshold
+            }) {
+                let previous = self.entries.remove(pos);
+                self.total_bytes -= previous.content.len();
+                entry.revisions = previous.revisions + 1;
+                self.move_to_trash(vec![previous]);
+                self.set_status(
+                    "Merged with a similar entry (previous version in trash)",
+                    2.0,
+                );
+            }
+        }
+
+        self.total_bytes += entry.content.len();
+        self.flash = Some((entry.id, Instant::now()));
+        self.entries.insert(0, entry);
+        self.evict_excess();
+        self.scroll_to_top = self.settings.scroll_to_new;
+
+        // Save data after changes
+        self.save_data();
+    }
+
+    /// Record that the clipboard was emptied, unless the latest entry already says so
+    fn add_clear_marker(&mut self) {
+        if self.entries.first().is_some_and(|e| e.cleared) || self.history_blocked() {
+            return;
+        }
+
+        let mut marker = ClipboardEntry::new(self.next_id, String::new());
+        marker.cleared = true;
+        self.next_id += 1;
+        self.entries.insert(0, marker);
+        self.evict_excess();
+        self.save_data();
+    }
+
+    /// Expand a multi-line row in the list, or collapse it if it's expanded
+    fn toggle_expanded(&mut self, id: u64) {
+        if !self.expanded.remove(&id) {
+            self.expanded.insert(id);
+        }
+    }
+
+    /// Returns how full the history is, as a percentage of the limit it's
+    /// closest to and a description, once that reaches the warning threshold
+    fn limit_usage(&self) -> Option<(usize, String)> {
+        let threshold = self.settings.limit_warning?;
+        let max_entries = self.settings.max_entries.max(1);
+        let mut usage = (
+            self.entries.len() * 100 / max_entries,
+            format!("{} of {} entries", self.entries.len(), max_entries),
+        );
+        if let Some(mb) = self.settings.max_total_mb.filter(|&mb| mb > 0) {
+            let percent = self.total_bytes * 100 / (mb as usize * 1024 * 1024);
+            if percent > usage.0 {
+                usage = (
+                    percent,
+                    format!("{} of {} MB", format_size(self.total_bytes), mb),
+                );
+            }
+        }
+        (usage.0 >= threshold as usize).then_some(usage)
+    }
+
+    /// Returns true if pinned entries fill the history and new captures aren't stored
+    fn history_blocked(&self) -> bool {
+        self.settings.full_history == FullHistory::Block
+            && self.pinned_count() >= self.settings.max_entries
+    }
+
+    /// Remove oldest entries if we exceed max_entries or the size limit (unless pinned)
+    ///
+    /// Once pinned entries fill the history in `FullHistory::Grow` mode, the
+    /// entry limit is suspended until enough are unpinned.
+    fn evict_excess(&mut self) {
+        let max_bytes = self
+            .settings
+            .max_total_mb
+            .map(|mb| mb as usize * 1024 * 1024);
+        let growing = self.settings.full_history == FullHistory::Grow
+            && self.pinned_count() >= self.settings.max_entries;
+        loop {
+            let over_count = self.entries.len() > self.settings.max_entries && !growing;
+            let over_size = max_bytes.is_some_and(|max| self.total_bytes > max);
+            if !over_count && !over_size {
+                break;
+            }
+
+            // Find the oldest non-pinned entry. The size limit never removes the
+            // newest entry, so a single clip larger than the limit is still kept.
+            let skip = usize::from(!over_count);
+            if let Some(idx) = self.entries.iter().skip(skip).rposition(|e| !e.pinned) {
+                let removed = self.entries.remove(idx + skip);
+                self.total_bytes -= removed.content.len();
+                self.log_event(capture_log::Event::Evicted, &removed.content);
+            } else {
+                // All entries are pinned, can't remove any
+                break;
+            }
+        }
+    }
+
+    /// Copy content to the clipboard
+    ///
+    /// `entry` is the id of the entry the content comes from, if any; only
+    /// that entry is marked as used. Other text, such as the scratchpad or an
+    /// appended clipboard, passes None.
+    fn copy_to_clipboard(&mut self, entry: Option<u64>, content: &str) -> bool {
+        self.copy_with_newline(entry, content, !self.settings.strip_trailing_newline)
+    }
+
+    /// Copy content to the system clipboard, with or without a single trailing newline
+    ///
+    /// The stored entry keeps its content either way.
+    fn copy_with_newline(&mut self, entry: Option<u64>, content: &str, keep_newline: bool) -> bool {
+        let text = if keep_newline {
+            content
+        } else {
+            detect::strip_trailing_newline(content)
+        };
+        match self.write_clipboard(text) {
+            Ok(()) => {
+                self.current_clipboard = text.to_owned();
+                if self.settings.copy_to_primary {
+                    if self.primary.is_none() {
+                        self.primary = PrimarySelection::new();
+                    }
+                    if let Some(primary) = &mut self.primary {
+                        primary.set(text);
+                    }
+                }
+                self.log_event(capture_log::Event::Copied, text);
+                let index = entry.and_then(|id| self.entry_index(id));
+                self.schedule_wipe(index, content, text);
+                self.record_copy(index);
+                true
+            }
+            Err(Error::Unchanged) => {
+                self.report(&Error::Unchanged, 4.0);
+                false
+            }
+            Err(e) => {
+                log::warn!("{}", e);
+                self.set_status("Failed to copy to clipboard", 2.0);
+                false
+            }
+        }
+    }
+
+    /// Update the entry at `index` after it was copied
+    ///
+    /// The copy counts as a use, which may pin the entry automatically.
+    /// One-shot entries are removed instead.
+    fn record_copy(&mut self, index: Option<usize>) {
+        // One-shot entries are gone for good once copied, not trashed
+        if let Some(pos) = index.filter(|&i| self.entries[i].one_shot && !self.entries[i].locked) {
+            let removed = self.entries.remove(pos);
+            self.total_bytes -= removed.content.len();
+            self.save_data();
+            self.set_status("Copied one-shot entry and removed it", 3.0);
+            return;
+        }
+
+        let mut auto_pinned = None;
+        let can_pin = !self.pin_limit_reached();
+        if let Some(entry) = index.map(|i| &mut self.entries[i]) {
+            entry.last_used = Some(Local::now());
+            entry.use_count += 1;
+            if self.settings.auto_pin
+                && can_pin
+                && !entry.pinned
+                && entry.use_count >= self.settings.auto_pin_threshold
+            {
+                entry.pinned = true;
+                auto_pinned = Some(entry.use_count);
+            }
+            self.save_data();
+        }
+        match auto_pinned {
+            Some(count) => self.set_status(
+                &format!("Copied and pinned automatically after {} copies", count),
+                3.0,
+            ),
+            None => self.set_status("Copied to clipboard", 2.0),
+        }
+    }
+
+    /// Arrange for the clipboard to be emptied after copying `content` as
+    /// `text`, if the entry at `index` or the wipe setting asks for it
+    fn schedule_wipe(&mut self, index: Option<usize>, content: &str, text: &str) {
+        let marked = index.is_some_and(|i| self.entries[i].clear_after_copy);
+        let wipe = match self.settings.clipboard_wipe {
+            ClipboardWipe::Marked => marked,
+            ClipboardWipe::Secrets => {
+                marked || detect::looks_like_secret(content, &self.settings.secret_rules)
+            }
+            ClipboardWipe::All => true,
+        };
+        self.pending_wipe = wipe.then(|| {
+            let delay = Duration::from_secs(self.settings.wipe_after_secs);
+            (text.to_owned(), Instant::now() + delay)
+        });
+    }
+
+    /// Empty the clipboard once a scheduled wipe is due
+    ///
+    /// Nothing is cleared if the clipboard no longer holds the copied text,
+    /// so something copied since is left alone.
+    fn run_pending_wipe(&mut self, ctx: &Context) {
+        let Some((text, due)) = self.pending_wipe.take() else {
+            return;
+        };
+        let remaining = due.saturating_duration_since(Instant::now());
+        if !remaining.is_zero() {
+            ctx.request_repaint_after(remaining);
+            self.pending_wipe = Some((text, due));
+            return;
+        }
+
+        let Some(clipboard) = &mut self.clipboard_ctx else {
+            return;
+        };
+        if clipboard.get_contents().ok().as_deref() != Some(text.as_str()) {
+            return;
+        }
+        // Forget the contents first so the empty clipboard isn't recorded
+        // as a clear
+        self.current_clipboard.clear();
+        if clipboard.set_contents(String::new()).is_err() {
+            self.set_status("Failed to clear the clipboard", 3.0);
+            return;
+        }
+        if let Some(primary) = &mut self.primary {
+            primary.set("");
+        }
+        self.set_status("Clipboard cleared", 2.0);
+    }
+
+    /// Put text on the system clipboard
+    ///
+    /// Some backends report success without the clipboard changing. With
+    /// `verify_copies` on, the clipboard is read back afterwards and the write
+    /// is retried once if it doesn't hold the text.
+    fn write_clipboard(&mut self, text: &str) -> Result<(), Error> {
+        let Some(ctx) = &mut self.clipboard_ctx else {
+            return Err(Error::Clipboard("not connected".to_owned()));
+        };
+        let write = |ctx: &mut ClipboardContext| {
+            ctx.set_contents(text.to_owned())
+                .map_err(|e| Error::Clipboard(e.to_string()))
+        };
+        let holds_text = |ctx: &mut ClipboardContext| ctx.get_contents().is_ok_and(|c| c == text);
+
+        write(ctx)?;
+        if !self.settings.verify_copies || holds_text(ctx) {
+            return Ok(());
+        }
+        log::debug!("Clipboard didn't change after copying, retrying");
+        write(ctx)?;
+        if holds_text(ctx) {
+            Ok(())
+        } else {
+            Err(Error::Unchanged)
+        }
+    }
+
+    /// Check for new clipboard content
+    ///
+    /// After repeated read errors polling backs off, up to `MAX_READ_BACKOFF`
+    /// between attempts, and returns to every frame once a read succeeds.
+    fn check_clipboard(&mut self, ctx: &Context) {
+        if !self.monitoring_enabled || self.next_read.is_some_and(|at| Instant::now() < at) {
+            return;
+        }
+        if let Some(clipboard) = &mut self.clipboard_ctx {
+            let content = match clipboard.get_contents() {
+                Ok(content) => content,
+                Err(e) => {
+                    if !self.capture_invalid_text(ctx) {
+                        log::debug!("{}", Error::Clipboard(e.to_string()));
+                        self.record_read_error();
+                    }
+                    return;
+                }
+            };
+            if self.read_errors >= READ_ERROR_LIMIT {
+                self.set_status("Clipboard capture resumed", 3.0);
+            }
+            self.read_errors = 0;
+            self.next_read = None;
+            if !content.is_empty() && content != self.current_clipboard {
+                self.capture_new(content, false, ctx);
+            } else if content.is_empty()
+                && !self.current_clipboard.is_empty()
+                && self.settings.record_clears
+            {
+                // Only a successful empty read after non-empty content counts;
+                // failed reads (e.g. the owning app just closed) are ignored
+                self.current_clipboard.clear();
+                self.add_clear_marker();
+            }
+        }
+    }
+
+    /// Remember and capture clipboard content that differs from what was
+    /// last seen, marking it as lossily converted if `lossy`
+    ///
+    /// The formats it was offered in are listed in the background and added
+    /// to the new entry by `finish_format_listings`.
+    fn capture_new(&mut self, content: String, lossy: bool, ctx: &Context) {
+        self.current_clipboard = content.clone();
+        self.last_activity = Instant::now();
+        // A manual copy starts the paste ring over
+        self.ring_position = 0;
+        // Short selections are remembered as seen but not stored
+        if content.trim().chars().count() < self.settings.min_capture_chars {
+            return;
+        }
+        if let Some(app) = self.blocked_source() {
+            self.set_status(&format!("Not captured: copied in {}", app), 2.0);
+            return;
+        }
+        self.session_captures += 1;
+        self.log_event(capture_log::Event::Captured, &content);
+        let next_id = self.next_id;
+        self.add_capture(content, lossy);
+        if self.next_id != next_id && formats::is_supported() {
+            self.format_listings
+                .push(formats::Listing::start(next_id, ctx));
+        }
+    }
+
+    /// Record the formats of new captures once they've been listed
+    fn finish_format_listings(&mut self) {
+        let mut listed = Vec::new();
+        self.format_listings.retain(|listing| match listing.poll() {
+            Some(formats) => {
+                listed.push((listing.id, formats));
+                false
+            }
+            None => true,
+        });
+
+        let mut changed = false;
+        for (id, formats) in listed {
+            if let Some(index) = self.entry_index(id).filter(|_| !formats.is_empty()) {
+                self.entries[index].formats = formats;
+                changed = true;
+            }
+        }
+        if changed {
+            self.save_data();
+        }
+    }
+
+    /// Handle clipboard text the backend failed to read because it isn't
+    /// valid UTF-8, returning false if that's not why the read failed
+    ///
+    /// The raw bytes are read with the platform's clipboard tools. While the
+    /// same text stays on the clipboard it's only read again every
+    /// `INVALID_TEXT_RECHECK`, rather than every frame.
+    fn capture_invalid_text(&mut self, ctx: &Context) -> bool {
+        let Some(bytes) = formats::read_raw() else {
+            return false;
+        };
+        let (text, lossy) = formats::decode(bytes);
+        if !lossy {
+            return false;
+        }
+
+        if self.read_errors >= READ_ERROR_LIMIT {
+            self.set_status("Clipboard capture resumed", 3.0);
+        }
+        self.read_errors = 0;
+        self.next_read = Some(Instant::now() + INVALID_TEXT_RECHECK);
+        if text == self.current_clipboard {
+            return true;
+        }
+        match self.settings.invalid_text {
+            InvalidText::Convert => self.capture_new(text, true, ctx),
+            InvalidText::Skip => {
+                self.current_clipboard = text;
+                self.set_status("Not captured: the copied text isn't valid UTF-8", 3.0);
+            }
+        }
+        true
+    }
+
+    /// Pause or resume capturing
+    ///
+    /// Whatever is on the clipboard when capturing resumes was copied while
+    /// paused, so it's remembered as seen rather than captured.
+    fn set_monitoring(&mut self, enabled: bool) {
+        if enabled {
+            if let Some(ctx) = &mut self.clipboard_ctx {
+                self.current_clipboard = ctx.get_contents().unwrap_or_default();
+            }
+        }
+        self.monitoring_enabled = enabled;
+        self.save_data();
+        if enabled {
+            self.set_status("Capturing resumed", 2.0);
+        } else {
+            self.set_status("Capturing paused: nothing you copy is recorded", 3.0);
+        }
+    }
+
+    /// Returns the foreground app's name if the app filters rule out capturing from it
+    ///
+    /// Names match when they contain a listed name, ignoring case. If the
+    /// foreground app can't be determined nothing is filtered.
+    fn blocked_source(&self) -> Option<String> {
+        if self.settings.capture_allowlist.is_empty() && self.settings.capture_denylist.is_empty() {
+            return None;
+        }
+        let names = foreground::active_app()?;
+        let listed = |list: &[String]| {
+            list.iter().any(|pattern| {
+                let pattern = pattern.to_lowercase();
+                names
+                    .iter()
+                    .any(|name| name.to_lowercase().contains(&pattern))
+            })
+        };
+
+        let blocked = listed(&self.settings.capture_denylist)
+            || (!self.settings.capture_allowlist.is_empty()
+                && !listed(&self.settings.capture_allowlist));
+        blocked.then(|| names.last().cloned().unwrap_or_default())
+    }
+
+    /// Count a failed clipboard read, backing off once they keep failing
+    fn record_read_error(&mut self) {
+        self.read_errors += 1;
+        if self.read_errors < READ_ERROR_LIMIT {
+            return;
+        }
+        if self.read_errors == READ_ERROR_LIMIT {
+            log::warn!("Clipboard reads keep failing, polling less often");
+            self.set_status(
+                "Can't read the clipboard (it may be empty); checking less often",
+                5.0,
+            );
+        }
+        let doublings = (self.read_errors - READ_ERROR_LIMIT).min(5);
+        let backoff = (Duration::from_millis(500) * 2u32.pow(doublings)).min(MAX_READ_BACKOFF);
+        self.next_read = Some(Instant::now() + backoff);
+    }
+
+    /// Apply the typed search once it's long enough and typing has paused
+    ///
+    /// Searches shorter than the minimum length show every entry. With the
+    /// default settings (1 character, no delay) every keystroke applies.
+    fn update_search(&mut self, ctx: &Context) {
+        if self.applied_search == self.search_term {
+            return;
+        }
+
+        let typed = self.search_term.trim().chars().count();
+        if typed > 0 && typed < self.settings.search_min_chars {
+            self.applied_search.clear();
+            return;
+        }
+
+        let debounce = Duration::from_millis(self.settings.search_debounce_ms);
+        let elapsed = self
+            .search_edited
+            .map_or(debounce, |edited| edited.elapsed());
+        if typed == 0 || elapsed >= debounce {
+            self.applied_search = self.search_term.clone();
+        } else {
+            ctx.request_repaint_after(debounce - elapsed);
+        }
+    }
+
+    /// Returns the configured accent color
+    fn accent(&self) -> Color32 {
+        let [r, g, b] = self.settings.accent_color;
+        Color32::from_rgb(r, g, b)
+    }
+
+    /// Returns true if animations should be avoided
+    fn reduced_motion(&self) -> bool {
+        self.settings
+            .reduced_motion
+            .resolve(self.system_preferences.reduced_motion)
+    }
+
+    /// Returns true if stronger contrast is wanted
+    fn high_contrast(&self) -> bool {
+        self.settings
+            .high_contrast
+            .resolve(self.system_preferences.high_contrast)
+    }
+
+    /// Apply the accent color and accessibility preferences to egui's style
+    fn apply_style(&self, ctx: &Context) {
+        let accent = self.accent();
+        let reduced_motion = self.reduced_motion();
+        let high_contrast = self.high_contrast();
+        ctx.set_theme(self.settings.appearance.preference());
+        ctx.all_styles_mut(|style| {
+            style.animation_time = if reduced_motion {
+                0.0
+            } else {
+                egui::Style::default().animation_time
+            };
+
+            let base = if style.visuals.dark_mode {
+                egui::Visuals::dark()
+            } else {
+                egui::Visuals::light()
+            };
+            let visuals = &mut style.visuals;
+            if high_contrast {
+                let strong = if visuals.dark_mode {
+                    Color32::WHITE
+                } else {
+                    Color32::BLACK
+                };
+                visuals.override_text_color = Some(strong);
+                visuals.window_stroke = Stroke::new(1.5, strong);
+                visuals.widgets.noninteractive.bg_stroke =
+                    Stroke::new(1.0, strong.gamma_multiply(0.6));
+                visuals.widgets.inactive.bg_stroke = Stroke::new(1.0, strong.gamma_multiply(0.8));
+            } else {
+                visuals.override_text_color = base.override_text_color;
+                visuals.window_stroke = base.window_stroke;
+                visuals.widgets.noninteractive.bg_stroke = base.widgets.noninteractive.bg_stroke;
+                visuals.widgets.inactive.bg_stroke = base.widgets.inactive.bg_stroke;
+            }
+
+            visuals.hyperlink_color = accent;
+            visuals.selection.bg_fill = accent.gamma_multiply(0.4);
+            visuals.selection.stroke.color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+            visuals.widgets.active.bg_stroke.color = accent;
+        });
+    }
+
+    /// Show the entry count and session captures in the window title
+    ///
+    /// Updates are throttled to `TITLE_INTERVAL` so bursts of copies don't
+    /// flood the window manager.
+    fn update_title(&mut self, ctx: &Context) {
+        let title = format!(
+            "Klippy — {} entries · {} captured this session",
+            self.entries.len(),
+            self.session_captures
+        );
+        if let Some((current, updated)) = &self.window_title {
+            if *current == title {
+                return;
+            }
+            let elapsed = updated.elapsed();
+            if elapsed < TITLE_INTERVAL {
+                ctx.request_repaint_after(TITLE_INTERVAL - elapsed);
+                return;
+            }
+        }
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+        self.window_title = Some((title, Instant::now()));
+    }
+
+    /// Start listening for global hotkeys
+    fn start_hotkeys(&mut self, ctx: &egui::Context) {
+        match Hotkeys::new(ctx) {
+            Ok(hotkeys) => {
+                self.hotkeys = Some(hotkeys);
+                self.apply_hotkeys();
+            }
+            Err(message)
+                if self.settings.paste_ring_enabled
+                    || self.settings.panic_enabled
+                    || self.settings.close_action == CloseAction::Hide
+                    || !self.slot_bindings.is_empty() =>
+            {
+                self.set_status(&message, 3.0)
+            }
+            Err(_) => {}
+        }
+    }
+
+    /// Register global hotkeys to match the current settings
+    fn apply_hotkeys(&mut self) {
+        let Some(hotkeys) = &mut self.hotkeys else {
+            return;
+        };
+
+        hotkeys.unregister_all();
+        let mut errors = Vec::new();
+        if self.settings.paste_ring_enabled {
+            if let Err(message) =
+                hotkeys.register(&self.settings.paste_ring_hotkey, HotkeyAction::PasteRing)
+            {
+                errors.push(message);
+            }
+        }
+        if self.settings.close_action == CloseAction::Hide {
+            if let Err(message) =
+                hotkeys.register(&self.settings.show_hotkey, HotkeyAction::ShowWindow)
+            {
+                errors.push(message);
+            }
+        }
+        if self.settings.panic_enabled {
+            if let Err(message) = hotkeys.register(&self.settings.panic_hotkey, HotkeyAction::Panic)
+            {
+                errors.push(message);
+            }
+        }
+        // Bindings beyond the slot count are kept, but not active, in case
+        // the count goes back up
+        for &slot in self.slot_bindings.keys() {
+            if slot > self.settings.slot_count {
+                continue;
+            }
+            let hotkey = self.settings.slot_keys.hotkey(slot);
+            if let Err(message) = hotkeys.register(&hotkey, HotkeyAction::CopySlot(slot)) {
+                errors.push(message);
+            }
+        }
+        if !errors.is_empty() {
+            self.set_status(&errors.join("; "), 3.0);
+        }
+    }
+
+    /// Bind an entry to a numbered slot hotkey, or clear the slot
+    fn bind_slot(&mut self, slot: u8, id: Option<u64>) {
+        match id {
+            Some(id) => {
+                // An entry has at most one slot
+                self.slot_bindings.retain(|_, bound| *bound != id);
+                self.slot_bindings.insert(slot, id);
+            }
+            None => {
+                self.slot_bindings.remove(&slot);
+            }
+        }
+        self.apply_hotkeys();
+        self.save_data();
+    }
+
+    /// Returns the active slot an entry is bound to, if any
+    fn slot_of(&self, id: u64) -> Option<u8> {
+        self.slot_bindings
+            .iter()
+            .find(|(&slot, &bound)| bound == id && slot <= self.settings.slot_count)
+            .map(|(&slot, _)| slot)
+    }
+
+    /// Copy the entry with the given id to the clipboard
+    fn copy_entry(&mut self, id: u64) {
+        if let Some(index) = self.entry_index(id) {
+            let content = self.entries[index].content.clone();
+            self.copy_to_clipboard(Some(id), &content);
+        }
+    }
+
+    /// Returns true if the entry's content is hidden as a likely secret
+    fn is_masked(&self, entry: &ClipboardEntry) -> bool {
+        self.settings.mask_secrets
+            && !self.revealed.contains(&entry.id)
+            && entry.looks_like_secret(&self.settings.secret_rules)
+    }
+
+    /// Open the entry with the given id in the content viewer
+    ///
+    /// Masked entries have to be revealed first.
+    fn view_entry(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        if self.is_masked(&self.entries[index]) {
+            self.set_status("Reveal this entry before viewing it", 2.0);
+            return;
+        }
+        self.viewer = Some(ContentViewer::new(&self.entries[index]));
+    }
+
+    /// Copy (and paste) the entry bound to a numbered slot
+    fn copy_slot(&mut self, slot: u8) {
+        let Some(index) = self
+            .slot_bindings
+            .get(&slot)
+            .and_then(|&id| self.entry_index(id))
+        else {
+            self.set_status(&format!("No entry is bound to slot {}", slot), 2.0);
+            return;
+        };
+
+        let id = self.entries[index].id;
+        let content = self.entries[index].content.clone();
+        if self.copy_to_clipboard(Some(id), &content) && self.settings.auto_paste {
+            if let Err(message) = paste::send_paste() {
+                self.set_status(&message, 3.0);
+            }
+        }
+    }
+
+    /// Run the actions of any global hotkeys pressed since the last frame
+    fn handle_hotkeys(&mut self, ctx: &Context) {
+        let actions = self.hotkeys.as_ref().map(Hotkeys::poll).unwrap_or_default();
+        for action in actions {
+            match action {
+                HotkeyAction::PasteRing => self.paste_ring_next(),
+                HotkeyAction::CopySlot(slot) => self.copy_slot(slot),
+                HotkeyAction::ShowWindow => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
+                HotkeyAction::Panic => self.panic(ctx),
+            }
+        }
+    }
+
+    /// Get the history out of sight at once: hide the window, close anything
+    /// showing entry content and empty the system clipboard and the PRIMARY
+    /// selection
+    ///
+    /// The window is hidden when the show hotkey can bring it back, and
+    /// minimized otherwise. Revealed secrets are masked again.
+    fn panic(&mut self, ctx: &Context) {
+        self.viewer = None;
+        self.split_viewer = None;
+        self.compare = None;
+        self.quick_open = None;
+        self.palette = None;
+        self.link_prompt = None;
+        self.import_preview = None;
+        self.show_trash_window = false;
+        self.selected = None;
+        self.expanded.clear();
+        self.revealed.clear();
+        if self.settings.close_action == CloseAction::Hide {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+
+        // Forget the old contents first so the empty clipboard isn't
+        // recorded as a clear
+        self.current_clipboard.clear();
+        let cleared = self
+            .clipboard_ctx
+            .as_mut()
+            .is_some_and(|clipboard| clipboard.set_contents(String::new()).is_ok());
+        if !cleared {
+            self.set_status("Failed to clear the clipboard", 3.0);
+        }
+        if self.primary.is_none() {
+            self.primary = PrimarySelection::new();
+        }
+        if let Some(primary) = &mut self.primary {
+            primary.set("");
+        }
+    }
+
+    /// Apply the close action when the window's close button is pressed
+    ///
+    /// Minimizing or hiding cancels the close, so the app keeps running and
+    /// capturing in the background.
+    fn handle_close_request(&mut self, ctx: &Context) {
+        if self.quitting || !ctx.input(|i| i.viewport().close_requested()) {
+            return;
+        }
+
+        match self.settings.close_action {
+            CloseAction::Quit => {}
+            CloseAction::Minimize => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            CloseAction::Hide => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+            }
+        }
+    }
+
+    /// Switch between the full window and the mini strip
+    ///
+    /// Each layout reopens at its own remembered position and size, and the
+    /// strip stays on top of other windows.
+    fn set_mini_mode(&mut self, ctx: &Context, mini: bool) {
+        if mini == self.mini_mode {
+            return;
+        }
+        self.mini_mode = mini;
+
+        let (geometry, default_size) = if mini {
+            (self.mini_geometry, mini::DEFAULT_SIZE)
+        } else {
+            (self.window_geometry, DEFAULT_WINDOW_SIZE)
+        };
+        let size = geometry.map_or(default_size, |g| [g.width, g.height]);
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size.into()));
+        if let Some(geometry) = geometry.filter(WindowGeometry::is_on_screen) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+                geometry.x, geometry.y,
+            )));
+        }
+        let level = if mini {
+            egui::WindowLevel::AlwaysOnTop
+        } else {
+            egui::WindowLevel::Normal
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        self.geometry_settles = Some(Instant::now() + GEOMETRY_SETTLE);
+        self.save_data();
+    }
+
+    /// Show the mini strip in place of the full layout
+    fn mini_ui(&mut self, ctx: &Context) {
+        let action = egui::CentralPanel::default()
+            .show(ctx, |ui| {
+                let mask = self.settings.mask_secrets;
+                mini::show(ui, &self.entries, |entry| {
+                    mask && !self.revealed.contains(&entry.id)
+                        && entry.looks_like_secret(&self.settings.secret_rules)
+                })
+            })
+            .inner;
+        match action {
+            Some(mini::Action::Copy(id)) => {
+                if let Some(index) = self.entry_index(id) {
+                    let content = self.entries[index].content.clone();
+                    self.copy_to_clipboard(Some(id), &content);
+                }
+            }
+            Some(mini::Action::Exit) => self.set_mini_mode(ctx, false),
+            None => {}
+        }
+    }
+
+    /// Save and exit, regardless of the close action
+    fn quit(&mut self, ctx: &Context) {
+        self.quitting = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+
+    /// Copy (and paste) the current paste ring entry, then advance the ring
+    ///
+    /// The ring cycles through the most recent entries from oldest to newest,
+    /// so values copied in order are pasted back in the same order.
+    fn paste_ring_next(&mut self) {
+        let ring_len = self.entries.len().min(self.settings.paste_ring_size);
+        if ring_len == 0 {
+            self.set_status("Paste ring is empty", 2.0);
+            return;
+        }
+
+        let position = self.ring_position % ring_len;
+        let entry = &self.entries[ring_len - 1 - position];
+        let (id, content) = (entry.id, entry.content.clone());
+        if self.copy_to_clipboard(Some(id), &content) {
+            self.ring_position = (position + 1) % ring_len;
+            if self.settings.auto_paste {
+                if let Err(message) = paste::send_paste() {
+                    self.set_status(&message, 3.0);
+                    return;
+                }
+            }
+            self.set_status(&format!("Paste ring {}/{}", position + 1, ring_len), 2.0);
+        }
+    }
+
+    /// Log an error and show it as a status message for `timer` seconds
+    fn report(&mut self, error: &Error, timer: f32) {
+        log::error!("{}", error);
+        self.set_status(&error.to_string(), timer);
+    }
+
+    /// Show a status message for `timer` seconds
+    ///
+    /// On the status line a new message replaces the current one. As toasts
+    /// they stack up to `MAX_TOASTS`, and repeating a message that's still
+    /// shown restarts its timer instead of adding it again.
+    fn set_status(&mut self, message: &str, timer: f32) {
+        if self.settings.status_style == StatusStyle::Line {
+            self.status_messages.clear();
+        }
+        self.status_messages.retain(|(shown, _)| shown != message);
+        self.status_messages.push((message.to_owned(), timer));
+        if self.status_messages.len() > MAX_TOASTS {
+            self.status_messages.remove(0);
+        }
+    }
+
+    /// Update the status message timers
+    fn update_status(&mut self, ctx: &egui::Context) {
+        let current_time = ctx.input(|i| i.time);
+        age_status_messages(&mut self.status_messages, self.status_time, current_time);
+        self.status_time = Some(current_time);
+
+        // Repaint when the next message is due to disappear, and smoothly
+        // while a toast fades out
+        if let Some(next) = self
+            .status_messages
+            .iter()
+            .map(|(_, t)| *t)
+            .reduce(f32::min)
+        {
+            let fades = self.settings.status_style == StatusStyle::Toasts && !self.reduced_motion();
+            let wait = if !fades {
+                next
+            } else if next > TOAST_FADE {
+                next - TOAST_FADE
+            } else {
+                1.0 / 30.0
+            };
+            ctx.request_repaint_after(Duration::from_secs_f32(wait));
+        }
+    }
+
+    /// Show status messages as toasts stacked in the chosen corner of `area`
+    fn show_toasts(&self, ctx: &Context, area: egui::Rect) {
+        if self.settings.status_style != StatusStyle::Toasts || self.status_messages.is_empty() {
+            return;
+        }
+
+        let align = self.settings.toast_corner.align();
+        let top = align.y() == egui::Align::Min;
+        let position = align.pos_in_rect(&area.shrink(12.0));
+        let fades = !self.reduced_motion();
+        egui::Area::new(egui::Id::new("toasts"))
+            .order(egui::Order::Foreground)
+            .pivot(align)
+            .fixed_pos(position)
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(align.x()), |ui| {
+                    // Newest message nearest the corner
+                    let messages: Vec<&(String, f32)> = if top {
+                        self.status_messages.iter().rev().collect()
+                    } else {
+                        self.status_messages.iter().collect()
+                    };
+                    for (message, timer) in messages {
+                        ui.scope(|ui| {
+                            if fades {
+                                ui.multiply_opacity((timer / TOAST_FADE).min(1.0));
+                            }
+                            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                                ui.set_max_width(320.0);
+                                ui.label(RichText::new(message).color(self.accent()));
+                            });
+                        });
+                    }
+                });
+            });
+    }
+
+    /// Schedule the next frame, which is also the next clipboard check
+    ///
+    /// Checks run every `ACTIVE_REPAINT` for a while after input or a
+    /// capture, then back off to the configured idle interval so an idle
+    /// window doesn't keep the CPU awake.
+    fn schedule_repaint(&mut self, ctx: &Context) {
+        if ctx.input(|i| !i.events.is_empty()) {
+            self.last_activity = Instant::now();
+        }
+        let interval = if self.last_activity.elapsed() < ACTIVE_PERIOD {
+            ACTIVE_REPAINT
+        } else {
+            Duration::from_secs(self.settings.idle_repaint_secs.max(1))
+        };
+        ctx.request_repaint_after(interval);
+    }
+
+    /// Move deleted entries to the front of the trash
+    fn move_to_trash(&mut self, removed: Vec<ClipboardEntry>) {
+        let now = Local::now();
+        for mut entry in removed.into_iter().rev() {
+            self.log_event(capture_log::Event::Deleted, &entry.content);
+            entry.deleted_at = Some(now);
+            self.trash.insert(0, entry);
+        }
+        self.purge_trash();
+    }
+
+    /// Drop trashed entries beyond the trash size or older than the retention age
+    fn purge_trash(&mut self) {
+        let cutoff =
+            Local::now() - chrono::Duration::days(self.settings.trash_retention_days as i64);
+        self.trash
+            .retain(|e| e.deleted_at.is_none_or(|deleted| deleted >= cutoff));
+        self.trash.truncate(self.settings.max_trash);
+    }
+
+    /// Restore a trashed entry to the top of the history, with its details
+    ///
+    /// The entry itself goes back rather than being captured again, so
+    /// capture rules like merging don't apply. It stays in the trash if its
+    /// text is already in the history or there's no room for it.
+    fn restore_from_trash(&mut self, id: u64) {
+        let Some(pos) = self.trash.iter().position(|e| e.id == id) else {
+            return;
+        };
+        if self
+            .entries
+            .iter()
+            .any(|e| e.content == self.trash[pos].content)
+        {
+            self.set_status("Entry already in history", 2.0);
+            return;
+        }
+        if self.history_blocked() {
+            self.set_status(
+                "Not restored: every entry is pinned, unpin some or raise the limit",
+                4.0,
+            );
+            return;
+        }
+
+        let mut entry = self.trash.remove(pos);
+        entry.deleted_at = None;
+        entry.timestamp = Local::now();
+        self.total_bytes += entry.content.len();
+        self.flash = Some((entry.id, Instant::now()));
+        self.entries.insert(0, entry);
+        self.evict_excess();
+        self.scroll_to_top = self.settings.scroll_to_new;
+        self.save_data();
+        self.set_status("Entry restored", 2.0);
+    }
+
+    /// Permanently delete a trashed entry, or the whole trash if no id is given
+    fn purge_from_trash(&mut self, id: Option<u64>) {
+        match id {
+            Some(id) => self.trash.retain(|e| e.id != id),
+            None => self.trash.clear(),
+        }
+        self.save_data();
+        self.set_status("Permanently deleted", 2.0);
+    }
+
+    /// Move the unpinned entries in scope to the trash
+    fn clear_unpinned(&mut self, scope: ClearScope) {
+        let (cleared, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|e| scope.includes(e));
+        let count = cleared.len();
+        self.entries = kept;
+        self.move_to_trash(cleared);
+        self.recompute_total_bytes();
+        self.save_data();
+        self.set_status(
+            &format!("Moved {} unpinned entries to the trash", count),
+            2.0,
+        );
+    }
+
+    /// Ask for confirmation before clearing unpinned entries
+    fn request_clear_unpinned(&mut self, scope: ClearScope) {
+        if self.entries.iter().any(|e| scope.includes(e)) {
+            self.pending_clear = Some(scope);
+        } else {
+            let message = match scope {
+                ClearScope::All => "No unpinned entries to clear",
+                ClearScope::Before(_) => "No unpinned entries are that old",
+            };
+            self.set_status(message, 2.0);
+        }
+    }
+
+    /// Move the entry with the specified id to the trash
+    fn remove_entry(&mut self, id: u64) {
+        if let Some(index) = self.entry_index(id) {
+            if self.entries[index].locked {
+                self.set_status("Unlock the entry before deleting it", 2.0);
+                return;
+            }
+            if self.settings.protect_pinned && self.entries[index].pinned {
+                self.set_status("Unpin the entry before deleting it", 2.0);
+                return;
+            }
+            let removed = self.entries.remove(index);
+            self.total_bytes -= removed.content.len();
+            self.move_to_trash(vec![removed]);
+            self.save_data();
+            self.set_status("Moved to trash", 2.0);
+        }
+    }
+
+    /// Open a word-level comparison of two entries, older one first
+    fn open_compare(&mut self, a: u64, b: u64) {
+        self.compare_base = None;
+        let (Some(a), Some(b)) = (self.entry_index(a), self.entry_index(b)) else {
+            return;
+        };
+        let (old, new) = if self.entries[a].timestamp <= self.entries[b].timestamp {
+            (&self.entries[a], &self.entries[b])
+        } else {
+            (&self.entries[b], &self.entries[a])
+        };
+
+        match CompareView::new(&old.content, &new.content) {
+            Ok(view) => self.compare = Some(view),
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Delete an entry, asking first if delete confirmation is enabled
+    fn request_delete(&mut self, id: u64) {
+        if self.settings.confirm_delete {
+            self.pending_delete = Some(id);
+        } else {
+            self.remove_entry(id);
+        }
+    }
+
+    /// Handle the in-app shortcuts from the keymap
+    ///
+    /// Ignored while a text field has focus, so typing in the search box or
+    /// scratchpad works as usual; only the command palette opens from
+    /// anywhere. Escape also closes the settings window. Nothing fires while a
+    /// new binding is being recorded.
+    fn handle_keyboard(&mut self, ctx: &Context) {
+        if self.recording_key.is_some() {
+            return;
+        }
+        let typing =
+            self.palette.is_some() || self.quick_open.is_some() || ctx.wants_keyboard_input();
+
+        let mut triggered = Vec::new();
+        for (action, shortcut) in keymap::check_order(&self.settings.keybindings) {
+            if (!typing || action.works_while_typing())
+                && ctx.input_mut(|i| i.consume_shortcut(&shortcut))
+            {
+                triggered.push(action);
+            }
+        }
+        if !typing
+            && self.show_settings_window
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape))
+        {
+            self.show_settings_window = false;
+        }
+
+        for action in triggered {
+            self.run_key_action(ctx, action);
+        }
+    }
+
+    /// Run an action triggered by its shortcut
+    fn run_key_action(&mut self, ctx: &Context, action: KeyAction) {
+        match action {
+            KeyAction::CycleAppearance => self.cycle_appearance(ctx),
+            KeyAction::OpenPalette => self.palette = Some(CommandPalette::default()),
+            KeyAction::QuickOpen => self.quick_open = Some(QuickOpen::default()),
+            KeyAction::ToggleSettings => self.show_settings_window = !self.show_settings_window,
+            KeyAction::ClearUnpinned => self.request_clear_unpinned(ClearScope::All),
+            KeyAction::FocusSearch => self.focus_search = true,
+            KeyAction::SelectPrevious | KeyAction::SelectNext => {
+                let ids = self.listed_ids();
+                if ids.is_empty() {
+                    return;
+                }
+                let position = self
+                    .selected
+                    .and_then(|id| ids.iter().position(|&other| other == id));
+                let next = match position {
+                    Some(p) if action == KeyAction::SelectNext => (p + 1).min(ids.len() - 1),
+                    Some(p) => p.saturating_sub(1),
+                    None => 0,
+                };
+                self.selected = Some(ids[next]);
+                self.scroll_to_selected = true;
+            }
+            KeyAction::CopySelected | KeyAction::PinSelected | KeyAction::DeleteSelected => {
+                let Some(id) = self.selected.filter(|id| self.listed_ids().contains(id)) else {
+                    return;
+                };
+                match action {
+                    KeyAction::CopySelected => {
+                        if let Some(content) = self
+                            .entry_index(id)
+                            .map(|i| self.entries[i].content.clone())
+                        {
+                            self.copy_to_clipboard(Some(id), &content);
+                        }
+                    }
+                    KeyAction::PinSelected => self.toggle_pin(id),
+                    _ => self.request_delete(id),
+                }
+            }
+        }
+    }
+
+    /// Show the in-app shortcuts, recording a new one when asked
+    ///
+    /// Unlike the rest of the settings window, changes apply right away.
+    fn keybindings_ui(&mut self, ui: &mut egui::Ui) {
+        if let Some(action) = self.recording_key {
+            if let Some(pressed) = ui.input(|i| keymap::pressed(&i.events)) {
+                ui.input_mut(|i| i.consume_shortcut(&pressed));
+                self.recording_key = None;
+                if pressed == egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::Escape)
+                {
+                    self.set_status("Shortcut unchanged", 2.0);
+                } else {
+                    self.set_binding(action, shortcut::format(&pressed));
+                }
+            }
+        }
+
+        egui::Grid::new("keybindings")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for action in KeyAction::ALL {
+                    ui.label(action.label());
+                    let text = keymap::binding(&self.settings.keybindings, action);
+                    let shown = if self.recording_key == Some(action) {
+                        "Press a shortcut… (Esc cancels)"
+                    } else if text.is_empty() {
+                        "disabled"
+                    } else {
+                        text
+                    };
+                    if ui
+                        .button(shown)
+                        .on_hover_text("Click, then press the new shortcut")
+                        .clicked()
+                    {
+                        self.recording_key = Some(action);
+                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .small_button("⟲")
+                            .on_hover_text(format!("Reset to {}", action.default_shortcut()))
+                            .clicked()
+                        {
+                            self.set_binding(action, action.default_shortcut().to_owned());
+                        }
+                        if ui.small_button("⊘").on_hover_text("Disable").clicked() {
+                            self.set_binding(action, String::new());
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        ui.label(
+            RichText::new("Global hotkeys for showing the window, the paste ring and slots are set separately")
+                .small()
+                .weak(),
+        );
+    }
+
+    /// Bind a shortcut to an action, unless another action already uses it
+    fn set_binding(&mut self, action: KeyAction, text: String) {
+        if let Ok(candidate) = shortcut::parse(&text) {
+            if let Some(other) = keymap::conflict(&self.settings.keybindings, action, &candidate) {
+                self.set_status(
+                    &format!(
+                        "{} is already used to {}",
+                        text,
+                        other.label().to_lowercase()
+                    ),
+                    3.0,
+                );
+                return;
+            }
+        }
+        if text == action.default_shortcut() {
+            self.settings.keybindings.remove(&action);
+        } else {
+            self.settings.keybindings.insert(action, text);
+        }
+        self.save_data();
+    }
+
+    /// Returns the ids of the entries listed right now, in list order
+    fn listed_ids(&self) -> Vec<u64> {
+        self.filtered_entries()
+            .into_iter()
+            .filter(|&idx| !self.in_collapsed_group(&self.entries[idx]))
+            .map(|idx| self.entries[idx].id)
+            .collect()
+    }
+
+    /// Lock or unlock the entry with the specified id
+    fn toggle_lock(&mut self, id: u64) {
+        if let Some(index) = self.entry_index(id) {
+            let entry = &mut self.entries[index];
+            entry.locked = !entry.locked;
+            let message = if entry.locked {
+                "Entry locked: it can't be deleted or changed until unlocked"
+            } else {
+                "Entry unlocked"
+            };
+            self.save_data();
+            self.set_status(message, 2.0);
+        }
+    }
+
+    /// Pin an entry until `hours` from now, after which it's unpinned again
+    fn pin_temporarily(&mut self, id: u64, hours: i64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        if !self.entries[index].pinned && self.pin_limit_reached() {
+            self.set_status("Pin limit reached, unpin an entry first", 3.0);
+            return;
+        }
+        let entry = &mut self.entries[index];
+        entry.pinned = true;
+        entry.pin_until = Some(Local::now() + chrono::Duration::hours(hours));
+        let remaining = entry.pin_remaining().unwrap_or_default();
+        self.save_data();
+        self.set_status(&format!("Entry pinned for {}", remaining), 2.0);
+    }
+
+    /// Unpin entries whose temporary pin has run out
+    ///
+    /// They become ordinary entries again, so the history limits apply to
+    /// them straight away.
+    fn expire_pins(&mut self) {
+        let now = Local::now();
+        let mut expired = 0;
+        for entry in &mut self.entries {
+            if entry.pin_until.is_some_and(|until| until <= now) {
+                entry.pinned = false;
+                entry.pin_until = None;
+                expired += 1;
+            }
+        }
+        if expired == 0 {
+            return;
+        }
+        self.evict_excess();
+        self.save_data();
+        let message = if expired == 1 {
+            "A temporary pin ran out; the entry is unpinned".to_owned()
+        } else {
+            format!(
+                "{} temporary pins ran out; the entries are unpinned",
+                expired
+            )
+        };
+        self.set_status(&message, 3.0);
+    }
+
+    /// Toggle pinned status of the entry with the specified id
+    fn toggle_pin(&mut self, id: u64) {
+        if let Some(index) = self.entry_index(id) {
+            if !self.entries[index].pinned && self.pin_limit_reached() {
+                self.set_status("Pin limit reached, unpin an entry first", 3.0);
+                return;
+            }
+            self.entries[index].pinned = !self.entries[index].pinned;
+            self.entries[index].pin_until = None;
+            self.save_data();
+
+            if !self.entries[index].pinned {
+                self.set_status("Entry unpinned", 2.0);
+            } else if self.pinned_count() >= self.settings.max_entries {
+                let message = match self.settings.full_history {
+                    FullHistory::Block => "Entry pinned; every entry is pinned, so capturing stops",
+                    FullHistory::Grow => {
+                        "Entry pinned; every entry is pinned, so the history grows past the limit"
+                    }
+                };
+                self.set_status(message, 4.0);
+            } else {
+                self.set_status("Entry pinned", 2.0);
+            }
+        }
+    }
+
+    /// Returns the number of pinned entries
+    fn pinned_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.pinned).count()
+    }
+
+    /// Returns true if no more entries can be pinned
+    fn pin_limit_reached(&self) -> bool {
+        self.settings
+            .max_pinned
+            .is_some_and(|max| self.pinned_count() >= max)
+    }
+
+    /// Get filtered entries based on the search query, ordered by the sort mode
+    fn filtered_entries(&self) -> Vec<usize> {
+        let query = Query::parse(&self.applied_search);
+        let mut indices: Vec<usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !self.session_only || entry.timestamp >= self.session_start)
+            .filter(|(_, entry)| query.matches(entry))
+            .map(|(idx, _)| idx)
+            .collect();
+
+        // Capture order breaks ties, so entries captured or used within the
+        // same instant keep a deterministic order
+        let entries = &self.entries;
+        match self.settings.sort_mode {
+            SortMode::Newest => {
+                indices.sort_by_key(|&i| std::cmp::Reverse(entries[i].capture_order()));
+            }
+            SortMode::Oldest => indices.sort_by_key(|&i| entries[i].capture_order()),
+            SortMode::RecentlyUsed => indices.sort_by_key(|&i| {
+                std::cmp::Reverse((entries[i].last_used, entries[i].capture_order()))
+            }),
+        }
+        let searching = !self.applied_search.trim().is_empty();
+        if self.settings.pinned_first || (self.settings.pinned_first_in_search && searching) {
+            // Stable, so each group keeps the order above
+            indices.sort_by_key(|&i| !entries[i].pinned);
+        }
+        if self.settings.group_by_kind {
+            indices.sort_by_key(|&i| {
+                let kind = entries[i].kind();
+                ContentKind::ALL.iter().position(|&k| k == kind)
+            });
+        }
+
+        indices
+    }
+
+    /// Returns true if the entry is hidden in a folded content kind group
+    fn in_collapsed_group(&self, entry: &ClipboardEntry) -> bool {
+        self.settings.group_by_kind && self.collapsed_groups.contains(&entry.kind())
+    }
+
+    /// Show the heading of a content kind group, folding it when clicked
+    fn group_header(&mut self, ui: &mut egui::Ui, kind: ContentKind, count: usize) {
+        let collapsed = self.collapsed_groups.contains(&kind);
+        let arrow = if collapsed { "⏵" } else { "⏷" };
+        let heading = RichText::new(format!("{} {} ({})", arrow, kind.group_label(), count))
+            .strong()
+            .color(self.accent());
+        if ui
+            .add(egui::Label::new(heading).sense(Sense::click()))
+            .on_hover_text(if collapsed {
+                "Show this group"
+            } else {
+                "Hide this group"
+            })
+            .clicked()
+            && !self.collapsed_groups.remove(&kind)
+        {
+            self.collapsed_groups.insert(kind);
+        }
+    }
+
+    /// Copy the normalized form of a phone number or date entry
+    fn copy_normalized(&mut self, id: u64) {
+        if let Some(normalized) = self
+            .entry_index(id)
+            .and_then(|i| self.entries.get(i))
+            .and_then(|e| detect::normalize(&e.content))
+        {
+            self.copy_to_clipboard(Some(id), &normalized);
+        }
+    }
+
+    /// Add the normalized form of a phone number or date entry as a new clip
+    fn add_normalized(&mut self, id: u64) {
+        if let Some(normalized) = self
+            .entry_index(id)
+            .and_then(|i| self.entries.get(i))
+            .and_then(|e| detect::normalize(&e.content))
+        {
+            self.add_entry(normalized);
+            self.set_status("Added normalized entry", 2.0);
+        }
+    }
+
+    /// Add a copy of an entry with smart quotes, dashes and Unicode normalized
+    fn add_cleaned(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        let cleaned = detect::clean_text(&self.entries[index].content);
+        if cleaned == self.entries[index].content {
+            self.set_status("Nothing to clean up", 2.0);
+        } else {
+            self.add_entry(cleaned);
+            self.set_status("Added cleaned entry", 2.0);
+        }
+    }
+
+    /// Returns the folder user-defined transforms are loaded from
+    fn transforms_dir(&self) -> Option<PathBuf> {
+        self.data_dir().map(|dir| dir.join(transforms::FOLDER))
+    }
+
+    /// Load the transforms found in the transforms folder
+    fn reload_transforms(&mut self) {
+        self.transforms = self
+            .transforms_dir()
+            .map(|dir| transforms::load(&dir))
+            .unwrap_or_default();
+    }
+
+    /// Start a user-defined transform of an entry
+    ///
+    /// The transform runs in the background; `finish_transform` adds its
+    /// output as a new entry once it's done.
+    fn add_transformed(&mut self, id: u64, transform: usize, ctx: &Context) {
+        let (Some(index), Some(transform)) = (self.entry_index(id), self.transforms.get(transform))
+        else {
+            return;
+        };
+        if self.entries[index].locked {
+            self.set_status("Unlock the entry before transforming it", 2.0);
+            return;
+        }
+        if let Some(running) = &self.running_transform {
+            let message = format!("Wait for {} to finish first", running.name);
+            self.set_status(&message, 2.0);
+            return;
+        }
+        let running =
+            transforms::Running::start(transform, self.entries[index].content.clone(), ctx);
+        let message = format!("Running {}…", running.name);
+        self.running_transform = Some(running);
+        self.set_status(&message, transforms::TIMEOUT.as_secs_f32());
+    }
+
+    /// Add the output of the running transform once it has finished
+    fn finish_transform(&mut self) {
+        let Some(result) = self.running_transform.as_ref().and_then(|r| r.poll()) else {
+            return;
+        };
+        let Some(running) = self.running_transform.take() else {
+            return;
+        };
+        match result {
+            Ok(output) if output.trim().is_empty() => {
+                self.set_status(&format!("{} returned nothing", running.name), 2.0);
+            }
+            Ok(output) => {
+                self.add_entry(output);
+                self.set_status(&format!("Added result of {}", running.name), 2.0);
+            }
+            Err(message) => self.set_status(&message, 4.0),
+        }
+    }
+
+    /// Add a copy of an entry to the top of the history and select it
+    ///
+    /// The copy is a new unpinned entry: it keeps the content and what's known
+    /// about it, but starts with a fresh timestamp and no usage. Duplicates
+    /// are allowed here even though captures would merge them, and the copy
+    /// is marked so compacting leaves it in place too.
+    fn duplicate_entry(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        if self.history_blocked() {
+            self.set_status(
+                "Not duplicated: every entry is pinned, unpin some or raise the limit",
+                4.0,
+            );
+            return;
+        }
+
+        let original = &self.entries[index];
+        let copy = ClipboardEntry {
+            local_only: original.local_only,
+            formats: original.formats.clone(),
+            raw: original.raw.clone(),
+            lossy: original.lossy,
+            duplicate: true,
+            ..ClipboardEntry::new(self.next_id, original.content.clone())
+        };
+        self.next_id += 1;
+        self.total_bytes += copy.content.len();
+        self.flash = Some((copy.id, Instant::now()));
+        self.selected = Some(copy.id);
+        self.entries.insert(0, copy);
+        self.evict_excess();
+        self.scroll_to_top = true;
+        self.save_data();
+        self.set_status("Duplicated entry", 2.0);
+    }
+
+    /// Record an event in the capture log, if it's kept
+    ///
+    /// Only a preview is written, and likely secrets are replaced whether or
+    /// not they're masked in the list. Failures are logged rather than shown,
+    /// since they'd repeat with every capture.
+    fn log_event(&self, event: capture_log::Event, content: &str) {
+        if !self.settings.capture_log {
+            return;
+        }
+        let Some(dir) = self.data_dir() else {
+            return;
+        };
+        let preview = if detect::looks_like_secret(content, &self.settings.secret_rules) {
+            "•••••• (looks like a secret)".to_owned()
+        } else {
+            ClipboardEntry::new(0, content.to_owned()).preview(capture_log::PREVIEW_CHARS)
+        };
+        if let Err(message) = capture_log::append(&dir, event, &preview) {
+            log::warn!("{}", message);
+        }
+    }
+
+    /// Open the capture log in the default app for text files
+    fn open_capture_log(&mut self) {
+        let Some(path) = self.data_dir().map(|dir| dir.join(capture_log::FILE)) else {
+            self.set_status("Storage path not available", 2.0);
+            return;
+        };
+        if !path.exists() {
+            self.set_status("Nothing logged yet", 2.0);
+            return;
+        }
+        if let Err(e) = open::that(&path) {
+            self.set_status(&format!("Failed to open {}: {}", path.display(), e), 3.0);
+        }
+    }
+
+    /// Open the transforms folder, creating it if needed
+    fn open_transforms_dir(&mut self) {
+        let Some(dir) = self.transforms_dir() else {
+            self.set_status("Storage path not available", 2.0);
+            return;
+        };
+
+        let result = fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|()| open::that(&dir).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            self.set_status(&format!("Failed to open {}: {}", dir.display(), e), 3.0);
+        }
+    }
+
+    /// Copy an entry with its line endings converted, leaving the entry as is
+    fn copy_with_line_ending(&mut self, id: u64, ending: LineEnding) {
+        if let Some(index) = self.entry_index(id) {
+            let converted = detect::with_line_ending(&self.entries[index].content, ending);
+            if self.copy_to_clipboard(Some(id), &converted) {
+                self.set_status(&format!("Copied with {} line endings", ending.label()), 2.0);
+            }
+        }
+    }
+
+    /// Share an entry by email, copying it instead when that isn't possible
+    fn share_entry(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        let content = self.entries[index].content.clone();
+        match share::by_email(&content) {
+            Ok(()) => self.set_status("Opened a new email with the entry", 2.0),
+            Err(message) if self.copy_to_clipboard(Some(id), &content) => {
+                self.set_status(&format!("{}: copied instead", message), 3.0);
+            }
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Open the file a path entry points to in its default app
+    fn open_entry_file(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        if let Err(message) =
+            files::resolve(&self.entries[index].content).and_then(|path| files::open(&path))
+        {
+            self.set_status(&message, 3.0);
+        }
+    }
+
+    /// Show the file a path entry points to in the file manager
+    fn reveal_entry_file(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        if let Err(message) =
+            files::resolve(&self.entries[index].content).and_then(|path| files::reveal(&path))
+        {
+            self.set_status(&message, 3.0);
+        }
+    }
+
+    /// Append an entry to the current system clipboard contents
+    fn copy_append(&mut self, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        let current = self
+            .clipboard_ctx
+            .as_mut()
+            .and_then(|ctx| ctx.get_contents().ok())
+            .unwrap_or_default();
+
+        let content = &self.entries[index].content;
+        let combined = if current.is_empty() {
+            content.clone()
+        } else {
+            let separator = self
+                .settings
+                .append_separator
+                .replace("\\n", "\n")
+                .replace("\\t", "\t");
+            format!("{}{}{}", current, separator, content)
+        };
+
+        if combined.len() > MAX_APPEND_BYTES {
+            self.set_status(
+                &format!(
+                    "Clipboard would exceed {}, not appended",
+                    format_size(MAX_APPEND_BYTES)
+                ),
+                3.0,
+            );
+        } else if self.copy_to_clipboard(None, &combined) {
+            self.set_status("Appended to clipboard", 2.0);
+        }
+    }
+
+    /// Show the context menu for an entry
+    fn entry_context_menu(&mut self, ui: &mut egui::Ui, id: u64) {
+        let Some(index) = self.entry_index(id) else {
+            return;
+        };
+        let kind = self.entries[index].kind();
+        let line_ending = self.entries[index].line_ending();
+
+        if ui.button("📋 Copy").clicked() {
+            let content = self.entries[index].content.clone();
+            self.copy_to_clipboard(Some(id), &content);
+            ui.close_menu();
+        }
+
+        // Offer the opposite of the trailing newline setting
+        if self.entries[index].content.ends_with('\n') {
+            let (label, keep_newline) = if self.settings.strip_trailing_newline {
+                ("📋 Copy with trailing newline", true)
+            } else {
+                ("📋 Copy without trailing newline", false)
+            };
+            if ui.button(label).clicked() {
+                let content = self.entries[index].content.clone();
+                self.copy_with_newline(Some(id), &content, keep_newline);
+                ui.close_menu();
+            }
+        }
+
+        if let Some(raw) = self.entries[index].raw.clone() {
+            if ui
+                .button("📋 Copy raw (with whitespace)")
+                .on_hover_text("Copy the text as captured, before trimming")
+                .clicked()
+            {
+                self.copy_with_newline(Some(id), &raw, true);
+                ui.close_menu();
+            }
+        }
+
+        if ui.button("🔍 View full content").clicked() {
+            self.view_entry(id);
+            ui.close_menu();
+        }
+
+        if ui
+            .button("⧉ Duplicate")
+            .on_hover_text("Add a copy of this entry to the top of the history")
+            .clicked()
+        {
+            self.duplicate_entry(id);
+            ui.close_menu();
+        }
+
+        if ui
+            .button("✉ Share by email")
+            .on_hover_text("Open a new email with this entry as the message")
+            .clicked()
+        {
+            self.share_entry(id);
+            ui.close_menu();
+        }
+
+        if ui.button("➕ Copy (append)").clicked() {
+            self.copy_append(id);
+            ui.close_menu();
+        }
+
+        if line_ending.is_some() {
+            ui.menu_button("Copy as", |ui| {
+                for ending in [LineEnding::Lf, LineEnding::Crlf] {
+                    if ui
+                        .button(format!("{} line endings", ending.label()))
+                        .clicked()
+                    {
+                        self.copy_with_line_ending(id, ending);
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+
+        if kind == ContentKind::Url && ui.button("🔗 Copy as link…").clicked() {
+            self.link_prompt = Some(LinkPrompt {
+                id,
+                label: String::new(),
+                format: LinkFormat::Markdown,
+            });
+            ui.close_menu();
+        }
+
+        if kind == ContentKind::Path {
+            if ui.button("📂 Open file").clicked() {
+                self.open_entry_file(id);
+                ui.close_menu();
+            }
+            if ui
+                .button("🗁 Reveal in folder")
+                .on_hover_text("Show the file in the file manager")
+                .clicked()
+            {
+                self.reveal_entry_file(id);
+                ui.close_menu();
+            }
+        }
+
+        if matches!(kind, ContentKind::Phone | ContentKind::Date) {
+            let label = if kind == ContentKind::Date {
+                "Copy as ISO 8601"
+            } else {
+                "Copy normalized number"
+            };
+            if ui.button(label).clicked() {
+                self.copy_normalized(id);
+                ui.close_menu();
+            }
+            if ui.button("Add normalized as new entry").clicked() {
+                self.add_normalized(id);
+                ui.close_menu();
+            }
+        }
+
+        if self.entries[index].needs_cleanup()
+            && ui
+                .button("🧹 Add cleaned copy")
+                .on_hover_text("Straighten quotes and dashes and normalize Unicode (NFC)")
+                .clicked()
+        {
+            self.add_cleaned(id);
+            ui.close_menu();
+        }
+
+        if self.save_path.is_some() && !self.entries[index].locked {
+            ui.menu_button("🧩 Transform", |ui| {
+                let mut chosen = None;
+                for (position, transform) in self.transforms.iter().enumerate() {
+                    if ui.button(&transform.name).clicked() {
+                        chosen = Some(position);
+                    }
+                }
+                if self.transforms.is_empty() {
+                    ui.label(RichText::new("No transforms installed").weak());
+                }
+                if let Some(position) = chosen {
+                    self.add_transformed(id, position, ui.ctx());
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui
+                    .button("📂 Open transforms folder")
+                    .on_hover_text(
+                        "Rhai scripts here get the entry as `text` and end with the result",
+                    )
+                    .clicked()
+                {
+                    self.open_transforms_dir();
+                    ui.close_menu();
+                }
+                if ui.button("🔄 Reload transforms").clicked() {
+                    self.reload_transforms();
+                    self.set_status(&format!("Loaded {} transforms", self.transforms.len()), 2.0);
+                    ui.close_menu();
+                }
+            });
+        }
+
+        // Comparing takes two steps: mark one entry, then pick the other
+        match self.compare_base.filter(|&base| base != id) {
+            Some(base) => {
+                if ui.button("⚖ Compare with marked entry").clicked() {
+                    self.open_compare(base, id);
+                    ui.close_menu();
+                }
+            }
+            None => {
+                if ui.button("⚖ Compare…").clicked() {
+                    self.compare_base = Some(id);
+                    self.set_status("Right-click another entry to compare with it", 3.0);
+                    ui.close_menu();
+                }
+            }
+        }
+
+        // Numbered hotkey slots for pinned entries
+        let bound_slot = self.slot_of(id);
+        let pinned = self.entries[index].pinned;
+        ui.add_enabled_ui(pinned, |ui| {
+            ui.menu_button("⌨ Hotkey", |ui| {
+                for slot in 1..=self.settings.slot_count {
+                    let taken = self
+                        .slot_bindings
+                        .get(&slot)
+                        .is_some_and(|&other| other != id);
+                    let label = format!(
+                        "{}{}",
+                        self.settings.slot_keys.hotkey(slot),
+                        if taken { " (replace)" } else { "" }
+                    );
+                    if ui.radio(bound_slot == Some(slot), label).clicked() {
+                        self.bind_slot(slot, Some(id));
+                        ui.close_menu();
+                    }
+                }
+                if let Some(slot) = bound_slot {
+                    ui.separator();
+                    if ui.button("Clear").clicked() {
+                        self.bind_slot(slot, None);
+                        ui.close_menu();
+                    }
+                }
+            });
+        })
+        .response
+        .on_disabled_hover_text("Pin the entry to bind it to a hotkey");
+
+        let mut local_only = self.entries[index].local_only;
+        if ui
+            .checkbox(&mut local_only, "🏠 Keep local")
+            .on_hover_text("Leave this entry out of exports and sync")
+            .changed()
+        {
+            self.entries[index].local_only = local_only;
+            self.save_data();
+            ui.close_menu();
+        }
+
+        let mut one_shot = self.entries[index].one_shot;
+        if ui
+            .checkbox(&mut one_shot, "🔥 One-shot")
+            .on_hover_text("Remove this entry after it's copied once")
+            .changed()
+        {
+            self.entries[index].one_shot = one_shot;
+            self.save_data();
+            ui.close_menu();
+        }
+
+        let mut clear_after_copy = self.entries[index].clear_after_copy;
+        if ui
+            .checkbox(&mut clear_after_copy, "⏲ Clear clipboard after copying")
+            .on_hover_text(format!(
+                "Empty the system clipboard {}s after this entry is copied",
+                self.settings.wipe_after_secs
+            ))
+            .changed()
+        {
+            self.entries[index].clear_after_copy = clear_after_copy;
+            self.save_data();
+            ui.close_menu();
+        }
+
+        ui.separator();
+        let pin_label = if self.entries[index].pinned {
+            "📌 Unpin"
+        } else {
+            "📍 Pin"
+        };
+        if ui.button(pin_label).clicked() {
+            self.toggle_pin(id);
+            ui.close_menu();
+        }
+        ui.menu_button("⏳ Pin for…", |ui| {
+            for (label, hours) in TEMPORARY_PINS {
+                if ui.button(label).clicked() {
+                    self.pin_temporarily(id, hours);
+                    ui.close_menu();
+                }
+            }
+        })
+        .response
+        .on_hover_text("Pin until the task is done; the entry is unpinned afterwards");
+        let locked = self.entries[index].locked;
+        let lock_label = if locked { "🔓 Unlock" } else { "🔒 Lock" };
+        if ui
+            .button(lock_label)
+            .on_hover_text("A locked entry can't be deleted, replaced or transformed")
+            .clicked()
+        {
+            self.toggle_lock(id);
+            ui.close_menu();
+        }
+        let protected = locked || (self.settings.protect_pinned && self.entries[index].pinned);
+        if ui
+            .add_enabled(!protected, egui::Button::new("🗑 Delete"))
+            .on_disabled_hover_text(if locked {
+                "Unlock to delete"
+            } else {
+                "Unpin to delete"
+            })
+            .clicked()
+        {
+            self.request_delete(id);
+            ui.close_menu();
+        }
+    }
+
+    /// Show the details of the selected entry
+    fn details_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(8.0);
+        ui.label(RichText::new("ℹ Details").strong());
+        ui.separator();
+
+        let Some(index) = self.selected.and_then(|id| self.entry_index(id)) else {
+            ui.label(RichText::new("Select an entry to see its details").weak());
+            return;
+        };
+        let entry = &self.entries[index];
+        if !self
+            .detail_counts
+            .as_ref()
+            .is_some_and(|counts| counts.is_for(entry))
+        {
+            self.detail_counts = Some(details::Counts::of(entry));
+        }
+        if let Some(counts) = &self.detail_counts {
+            egui::ScrollArea::vertical().show(ui, |ui| details::show(ui, entry, counts));
+        }
+    }
+
+    /// Show the selected entry's full content with its details beside it,
+    /// for the split layout
+    ///
+    /// The content is laid out again only when the selection or the entry
+    /// changes.
+    fn split_ui(&mut self, ui: &mut egui::Ui) {
+        egui::SidePanel::right("split_details")
+            .default_width(240.0)
+            .show_inside(ui, |ui| self.details_ui(ui));
+
+        let Some(index) = self.selected.and_then(|id| self.entry_index(id)) else {
+            self.split_viewer = None;
+            return;
+        };
+        let entry = &self.entries[index];
+        if self.is_masked(entry) {
+            self.split_viewer = None;
+            ui.add_space(8.0);
+            ui.label(RichText::new("Hidden: looks like a secret").weak());
+            return;
+        }
+        if !self
+            .split_viewer
+            .as_ref()
+            .is_some_and(|viewer| viewer.is_for(entry))
+        {
+            self.split_viewer = Some(ContentViewer::new(entry));
+        }
+
+        let Some(viewer) = &mut self.split_viewer else {
+            return;
+        };
+        ui.add_space(8.0);
+        let response = viewer.show_inline(ui);
+        if response.copy {
+            let id = self.entries[index].id;
+            let content = self.entries[index].content.clone();
+            self.copy_to_clipboard(Some(id), &content);
+        }
+        if let Some(message) = response.error {
+            self.set_status(&message, 3.0);
+        }
+    }
+
+    /// Show the editable scratchpad
+    ///
+    /// The scratchpad isn't part of the history, so it's never captured,
+    /// deduplicated or evicted.
+    fn scratchpad_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("📝 Scratchpad")
+            .id_salt("scratchpad")
+            .show(ui, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::multiline(&mut self.scratchpad)
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Notes to assemble and copy…"),
+                );
+                if response.lost_focus() {
+                    self.save_data();
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("📋 Copy").clicked() {
+                        let content = self.scratchpad.clone();
+                        self.copy_to_clipboard(None, &content);
+                    }
+                    if ui.button("❌ Clear").clicked() {
+                        self.scratchpad.clear();
+                        self.save_data();
+                    }
+                });
+            });
+        ui.separator();
+    }
+
+    /// Start importing entries from the OS clipboard history
+    ///
+    /// The history is read in the background; `finish_os_import` adds the
+    /// entries once it's been read.
+    fn import_os_history(&mut self, ctx: &Context) {
+        if self.os_import.is_some() {
+            self.set_status("Already importing the clipboard history", 2.0);
+            return;
+        }
+        self.os_import = Some(os_history::Import::start(ctx));
+        self.set_status("Reading the clipboard history…", 10.0);
+    }
+
+    /// Add the entries of the OS clipboard history once it has been read
+    ///
+    /// Entries keep the time they were copied when the source records it,
+    /// and are otherwise stamped with the import time in their original order.
+    fn finish_os_import(&mut self) {
+        let Some(result) = self.os_import.as_ref().and_then(os_history::Import::poll) else {
+            return;
+        };
+        self.os_import = None;
+        match result {
+            Ok((source, items)) => {
+                let imported = self.merge_entries(history_entries(items, Local::now()));
+                self.set_status(
+                    &format!("Imported {} entries from {}", imported, source),
+                    3.0,
+                );
+            }
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Export entries to a file chosen by the user
+    ///
+    /// Exports every entry, or only those matching the current search and
+    /// filters when `filtered_only` is set.
+    fn export_entries(&mut self, format: ExportFormat, filtered_only: bool) {
+        let candidates: Vec<usize> = if filtered_only {
+            self.filtered_entries()
+        } else {
+            (0..self.entries.len()).collect()
+        };
+        // Local-only entries never leave the machine
+        let entries: Vec<ClipboardEntry> = candidates
+            .iter()
+            .map(|&idx| &self.entries[idx])
+            .filter(|e| !e.local_only)
+            .cloned()
+            .collect();
+        let skipped = candidates.len() - entries.len();
+
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(format!("Export {} entries", entries.len()))
+            .add_filter(format.label(), &[format.extension()])
+            .set_file_name(format!("klippy-export.{}", format.extension()))
+            .save_file()
+        else {
+            return;
+        };
+
+        match transfer::export(&entries, &path, format) {
+            Ok(()) if skipped > 0 => self.set_status(
+                &format!(
+                    "Exported {} entries, skipped {} local-only",
+                    entries.len(),
+                    skipped
+                ),
+                3.0,
+            ),
+            Ok(()) => self.set_status(&format!("Exported {} entries", entries.len()), 2.0),
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Import entries from a JSON file chosen by the user
+    ///
+    /// Merging adds entries that aren't already present, after listing them
+    /// for review if previews are on. Replacing discards the current history,
+    /// so it waits for confirmation first.
+    fn import_entries(&mut self, replace: bool) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        match transfer::import_json(&path) {
+            Ok(incoming) if replace => self.pending_import = Some(incoming),
+            Ok(incoming) if self.settings.preview_imports => {
+                let preview = ImportPreview::new(incoming, |content| self.has_content(content));
+                self.import_preview = Some(preview);
+            }
+            Ok(incoming) => {
+                self.merge_entries(incoming);
+            }
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Write the settings, without any history, to a file chosen by the user
+    fn export_settings(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export settings")
+            .add_filter("JSON", &["json"])
+            .set_file_name("klippy-settings.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = serde_json::to_string_pretty(&self.settings)
+            .map_err(|e| format!("Failed to export settings: {}", e))
+            .and_then(|json| {
+                fs::write(&path, json)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+            });
+        match result {
+            Ok(()) => self.set_status("Settings exported", 2.0),
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Replace the settings with ones read from a file chosen by the user
+    ///
+    /// A data file works too; its history is ignored. Settings missing from
+    /// the file get their defaults, and out-of-range values are clamped.
+    fn import_settings(&mut self, ctx: &Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let result = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|data| {
+                serde_json::from_str::<Settings>(&data)
+                    .map_err(|e| format!("Not a Klippy settings file: {}", e))
+            });
+        match result {
+            Ok(mut settings) => {
+                settings.sanitize();
+                self.apply_settings(ctx, settings);
+                self.set_status("Settings imported", 2.0);
+            }
+            Err(message) => self.set_status(&message, 4.0),
+        }
+    }
+
+    /// Switch to new settings, applying those that take effect live
+    fn apply_settings(&mut self, ctx: &Context, settings: Settings) {
+        if settings.status_style == StatusStyle::Line {
+            let start = self.status_messages.len().saturating_sub(1);
+            self.status_messages.drain(..start);
+        }
+        self.settings = settings;
+        logging::set_verbose(self.settings.verbose_logging);
+        self.evict_excess();
+        self.purge_trash();
+        self.apply_style(ctx);
+        self.apply_hotkeys();
+        self.save_data();
+    }
+
+    /// Returns true if an entry with exactly this content is in the history
+    fn has_content(&self, content: &str) -> bool {
+        self.entries.iter().any(|e| e.content == content)
+    }
+
+    /// Add imported entries whose content isn't already in the history,
+    /// returning how many were added
+    fn merge_entries(&mut self, incoming: Vec<ClipboardEntry>) -> usize {
+        let mut imported = 0;
+        for mut entry in incoming {
+            if entry.content.trim().is_empty() || self.has_content(&entry.content) {
+                continue;
+            }
+            entry.id = self.next_id;
+            self.next_id += 1;
+            self.entries.push(entry);
+            imported += 1;
+        }
+
+        // Keep the newest entries on top
+        self.entries
+            .sort_by_key(|e| std::cmp::Reverse(e.capture_order()));
+        self.recompute_total_bytes();
+        self.evict_excess();
+        self.save_data();
+        self.set_status(&format!("Imported {} entries", imported), 2.0);
+        imported
+    }
+
+    /// Replace the whole history with imported entries
+    fn replace_entries(&mut self, incoming: Vec<ClipboardEntry>) {
+        self.entries = incoming;
+        self.assign_missing_ids();
+        self.entries
+            .sort_by_key(|e| std::cmp::Reverse(e.capture_order()));
+        self.recompute_total_bytes();
+        self.evict_excess();
+        self.save_data();
+        self.set_status(
+            &format!("Replaced history with {} entries", self.entries.len()),
+            2.0,
+        );
+    }
+
+    /// Back up the data file, returning the path of the backup
+    fn backup_now(&mut self) -> Result<PathBuf, String> {
+        let path = self
+            .save_path
+            .clone()
+            .ok_or_else(|| "Storage path not available".to_owned())?;
+        // Backups copy the data file, so it has to hold everything
+        self.journal = None;
+        self.save_data();
+        self.last_backup = Instant::now();
+        self.backups = None;
+        backup::create_backup(&path, self.settings.max_backups)
+    }
+
+    /// Returns true if a backup on exit is due: the newest backup is older
+    /// than the backup interval, or there is none
+    ///
+    /// This keeps frequent restarts from filling the backups with copies of
+    /// nearly the same data.
+    fn exit_backup_due(&self) -> bool {
+        let interval = Duration::from_secs(self.settings.backup_interval_mins * 60);
+        self.save_path
+            .as_deref()
+            .and_then(backup::newest_backup_age)
+            .is_none_or(|age| age >= interval)
+    }
+
+    /// Back up the data file now, reporting the outcome in the status bar
+    fn report_backup(&mut self) {
+        match self.backup_now() {
+            Ok(path) => self.set_status(&format!("Backed up to {}", path.display()), 3.0),
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Make an automatic backup once the backup interval has passed
+    fn run_scheduled_backup(&mut self) {
+        let interval = Duration::from_secs(self.settings.backup_interval_mins * 60);
+        if self.settings.auto_backup && self.last_backup.elapsed() >= interval {
+            if let Err(message) = self.backup_now() {
+                self.set_status(&message, 3.0);
+            }
+        }
+    }
+
+    /// Drop redundant data from the history and rewrite the data file,
+    /// returning a summary of what was reclaimed
+    ///
+    /// Entries with the same content are merged into the newest copy, under
+    /// the same rules as captures: pinned entries are only merged if they
+    /// count as duplicates, and copies made with Duplicate never are. The
+    /// newest copy keeps any pin or slot binding, the higher use and revision
+    /// counts, and the combined capture count when duplicates are counted.
+    /// Trashed entries past retention are dropped.
+    fn compact(&mut self) -> Result<String, String> {
+        let size_before = self.data_file_size();
+
+        let include_pinned = self.settings.dedup_includes_pinned;
+        let count_duplicates = self.settings.count_duplicates;
+        let mut kept: Vec<ClipboardEntry> = Vec::with_capacity(self.entries.len());
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let mut merged = 0;
+        for entry in std::mem::take(&mut self.entries) {
+            if entry.cleared || entry.duplicate || (entry.pinned && !include_pinned) {
+                kept.push(entry);
+                continue;
+            }
+            match positions.get(&entry.content) {
+                Some(&pos) => {
+                    let newest = &mut kept[pos];
+                    newest.merge_pin(&entry);
+                    newest.local_only |= entry.local_only;
+                    newest.locked |= entry.locked;
+                    newest.use_count = newest.use_count.max(entry.use_count);
+                    newest.revisions = newest.revisions.max(entry.revisions);
+                    newest.occurrences = if count_duplicates {
+                        newest.occurrences + entry.occurrences
+                    } else {
+                        newest.occurrences.max(entry.occurrences)
+                    };
+                    newest.last_used = newest.last_used.max(entry.last_used);
+                    for bound in self.slot_bindings.values_mut() {
+                        if *bound == entry.id {
+                            *bound = newest.id;
+                        }
+                    }
+                    merged += 1;
+                }
+                None => {
+                    positions.insert(entry.content.clone(), kept.len());
+                    kept.push(entry);
+                }
+            }
+        }
+        self.entries = kept;
+        self.recompute_total_bytes();
+
+        let trash_before = self.trash.len();
+        self.purge_trash();
+        let dropped = trash_before - self.trash.len();
+
+        self.last_compact = Some(Local::now());
+        self.journal = None;
+        self.try_save()?;
+        let reclaimed = size_before.saturating_sub(self.data_file_size());
+        Ok(format!(
+            "Compacted: merged {} duplicates, dropped {} trash entries, reclaimed {}",
+            merged,
+            dropped,
+            format_size(reclaimed as usize)
+        ))
+    }
+
+    /// Compact the history now, reporting the outcome in the status bar
+    fn report_compact(&mut self) {
+        match self.compact() {
+            Ok(summary) => self.set_status(&summary, 4.0),
+            Err(message) => self.set_status(&message, 4.0),
+        }
+    }
+
+    /// Compact the history once `COMPACT_INTERVAL_DAYS` have passed
+    fn run_scheduled_compact(&mut self) {
+        let interval = chrono::Duration::days(COMPACT_INTERVAL_DAYS);
+        let due = self
+            .last_compact
+            .is_none_or(|last| Local::now() - last >= interval);
+        if self.settings.auto_compact && due {
+            self.report_compact();
+        }
+    }
+
+    /// Returns the size of the data file in bytes, or 0 if there's none
+    fn data_file_size(&self) -> u64 {
+        let Some(path) = &self.save_path else {
+            return 0;
+        };
+        [path.clone(), journal::path_for(path)]
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|meta| meta.len())
+            .sum()
+    }
+
+    /// Load a backup, replacing the history once confirmed
+    fn restore_backup(&mut self, path: &Path) {
+        match transfer::import_json(path) {
+            Ok(incoming) => self.pending_import = Some(incoming),
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Returns the directory holding the data file
+    fn data_dir(&self) -> Option<PathBuf> {
+        self.save_path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+    }
+
+    /// Open the storage directory in the file manager, creating it if needed
+    fn open_clips(&mut self) {
+        let Some(dir) = self.data_dir() else {
+            self.set_status("Storage path not available", 2.0);
+            return;
+        };
+
+        let result = fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|()| open::that(&dir).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => self.set_status("Opened storage directory", 2.0),
+            Err(e) => self.set_status(&format!("Failed to open {}: {}", dir.display(), e), 3.0),
+        }
+    }
+
+    /// Switch to the next appearance and remember it
+    fn cycle_appearance(&mut self, ctx: &Context) {
+        self.settings.appearance = self.settings.appearance.next();
+        self.apply_style(ctx);
+        self.save_data();
+        self.set_status(
+            &format!("Appearance: {}", self.settings.appearance.label()),
+            2.0,
+        );
+    }
+
+    /// Apply the choices made in the first-run setup
+    fn apply_setup(&mut self, ctx: &Context, setup: Setup) {
+        self.settings.appearance = setup.appearance;
+        self.apply_style(ctx);
+        self.settings.max_entries = setup.max_entries;
+        self.evict_excess();
+        if setup.background {
+            self.settings.close_action = CloseAction::Hide;
+            self.apply_hotkeys();
+        }
+        self.save_data();
+        if setup.autostart != self.autostart {
+            self.set_autostart(setup.autostart);
+        } else {
+            self.set_status("Setup saved", 2.0);
+        }
+    }
+
+    /// Register or unregister launching on login
+    fn set_autostart(&mut self, enabled: bool) {
+        match autostart::set_enabled(enabled) {
+            Ok(()) => {
+                self.autostart = enabled;
+                let state = if enabled { "enabled" } else { "disabled" };
+                self.set_status(&format!("Launch on login {}", state), 2.0);
+            }
+            Err(message) => self.set_status(&message, 3.0),
+        }
+    }
+
+    /// Show the saved searches menu: apply, save, rename and delete searches
+    fn saved_searches_menu(&mut self, ui: &mut egui::Ui) {
+        if self.settings.saved_searches.is_empty() {
+            ui.label(RichText::new("No saved searches yet").weak());
+        }
+
+        let mut apply = None;
+        let mut delete = None;
+        let mut rename = None;
+        for (index, saved) in self.settings.saved_searches.iter().enumerate() {
+            ui.horizontal(|ui| match &mut self.renaming_search {
+                Some((renaming, name)) if *renaming == index => {
+                    let response = ui.add(egui::TextEdit::singleline(name).desired_width(140.0));
+                    let enter =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.small_button("✔").clicked() || enter {
+                        rename = Some((index, name.trim().to_owned()));
+                    }
+                }
+                _ => {
+                    if ui.button(&saved.name).on_hover_text(&saved.query).clicked() {
+                        apply = Some(saved.query.clone());
+                    }
+                    if ui.small_button("✏").on_hover_text("Rename").clicked() {
+                        self.renaming_search = Some((index, saved.name.clone()));
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                        delete = Some(index);
+                    }
+                }
+            });
+        }
+
+        ui.separator();
+        let query = self.search_term.trim().to_owned();
+        ui.add_enabled_ui(!query.is_empty(), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_search_name)
+                        .hint_text("Name")
+                        .desired_width(140.0),
+                );
+                let name = self.new_search_name.trim().to_owned();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Save search"))
+                    .clicked()
+                {
+                    // Saving under an existing name updates that search
+                    match self
+                        .settings
+                        .saved_searches
+                        .iter_mut()
+                        .find(|s| s.name == name)
+                    {
+                        Some(saved) => saved.query = query.clone(),
+                        None => self.settings.saved_searches.push(SavedSearch {
+                            name: name.clone(),
+                            query: query.clone(),
+                        }),
+                    }
+                    self.new_search_name.clear();
+                    self.save_data();
+                    self.set_status(&format!("Saved search \"{}\"", name), 2.0);
+                }
+            });
+        })
+        .response
+        .on_disabled_hover_text("Type a search to save it");
+
+        if let Some(query) = apply {
+            self.search_term = query;
+            self.search_edited = None;
+            ui.close_menu();
+        }
+        if let Some((index, name)) = rename {
+            if !name.is_empty() {
+                self.settings.saved_searches[index].name = name;
+                self.save_data();
+            }
+            self.renaming_search = None;
+        }
+        if let Some(index) = delete {
+            let removed = self.settings.saved_searches.remove(index);
+            self.renaming_search = None;
+            self.save_data();
+            self.set_status(&format!("Deleted saved search \"{}\"", removed.name), 2.0);
+        }
+    }
+
+    /// Returns the commands offered in the palette, labelled for the current state
+    fn palette_commands(&self) -> Vec<(PaletteCommand, String)> {
+        let mut commands = vec![(PaletteCommand::OpenSettings, "Open settings".to_owned())];
+        if !self.search_term.is_empty() {
+            commands.push((PaletteCommand::ClearSearch, "Clear search".to_owned()));
+        }
+        for (index, saved) in self.settings.saved_searches.iter().enumerate() {
+            commands.push((
+                PaletteCommand::SavedSearch(index),
+                format!("Search: {}", saved.name),
+            ));
+        }
+        let session = if self.session_only {
+            "Show all entries"
+        } else {
+            "Show only this session's entries"
+        };
+        commands.push((PaletteCommand::ToggleSessionOnly, session.to_owned()));
+        let monitoring = if self.monitoring_enabled {
+            "Pause capturing"
+        } else {
+            "Resume capturing"
+        };
+        commands.push((PaletteCommand::ToggleMonitoring, monitoring.to_owned()));
+        let details = if self.settings.show_details {
+            "Hide details panel"
+        } else {
+            "Show details panel"
+        };
+        commands.push((PaletteCommand::ToggleDetails, details.to_owned()));
+        commands.push((PaletteCommand::MiniMode, "Switch to mini mode".to_owned()));
+        commands.push((PaletteCommand::QuickOpen, "Jump to an entry…".to_owned()));
+        commands.push((
+            PaletteCommand::CycleAppearance,
+            format!(
+                "Switch appearance to {}",
+                self.settings.appearance.next().label()
+            ),
+        ));
+        for mode in SortMode::ALL {
+            if mode != self.settings.sort_mode {
+                commands.push((
+                    PaletteCommand::Sort(mode),
+                    format!("Sort by {}", mode.label()),
+                ));
+            }
+        }
+        commands.push((
+            PaletteCommand::ClearUnpinned,
+            "Clear unpinned entries…".to_owned(),
+        ));
+        for format in ExportFormat::ALL {
+            commands.push((
+                PaletteCommand::Export(format),
+                format!("Export all as {}…", format.label()),
+            ));
+        }
+        commands.push((PaletteCommand::Import(false), "Import (merge)…".to_owned()));
+        commands.push((PaletteCommand::Import(true), "Import (replace)…".to_owned()));
+        commands.push((
+            PaletteCommand::ImportOsHistory,
+            "Import system clipboard history".to_owned(),
+        ));
+        commands.push((PaletteCommand::BackUpNow, "Back up now".to_owned()));
+        commands.push((PaletteCommand::Compact, "Compact history".to_owned()));
+        commands.push((
+            PaletteCommand::OpenTrash,
+            format!("Open trash ({})", self.trash.len()),
+        ));
+        commands.push((
+            PaletteCommand::OpenStorage,
+            "Open storage location".to_owned(),
+        ));
+        commands.push((
+            PaletteCommand::CopyStoragePath,
+            "Copy storage path".to_owned(),
+        ));
+        let mask = if self.settings.mask_secrets {
+            "Stop masking secrets"
+        } else {
+            "Mask secrets"
+        };
+        commands.push((PaletteCommand::ToggleMaskSecrets, mask.to_owned()));
+        if autostart::is_supported() {
+            let launch = if self.autostart {
+                "Disable launch on login"
+            } else {
+                "Enable launch on login"
+            };
+            commands.push((PaletteCommand::ToggleAutostart, launch.to_owned()));
+        }
+        commands.push((PaletteCommand::Quit, "Quit Klippy".to_owned()));
+        commands
+    }
+
+    /// Run a command chosen in the palette
+    fn run_command(&mut self, ctx: &Context, command: PaletteCommand) {
+        match command {
+            PaletteCommand::OpenSettings => self.show_settings_window = true,
+            PaletteCommand::ClearSearch => {
+                self.search_term.clear();
+                self.search_edited = None;
+            }
+            PaletteCommand::SavedSearch(index) => {
+                if let Some(saved) = self.settings.saved_searches.get(index) {
+                    self.search_term = saved.query.clone();
+                    self.search_edited = None;
+                }
+            }
+            PaletteCommand::ToggleSessionOnly => self.session_only = !self.session_only,
+            PaletteCommand::ToggleMonitoring => self.set_monitoring(!self.monitoring_enabled),
+            PaletteCommand::ToggleDetails => {
+                self.settings.show_details = !self.settings.show_details;
+                self.save_data();
+            }
+            PaletteCommand::MiniMode => self.set_mini_mode(ctx, true),
+            PaletteCommand::QuickOpen => self.quick_open = Some(QuickOpen::default()),
+            PaletteCommand::CycleAppearance => self.cycle_appearance(ctx),
+            PaletteCommand::Sort(mode) => {
+                self.settings.sort_mode = mode;
+                self.save_data();
+            }
+            PaletteCommand::ClearUnpinned => self.request_clear_unpinned(ClearScope::All),
+            PaletteCommand::Export(format) => self.export_entries(format, false),
+            PaletteCommand::Import(replace) => self.import_entries(replace),
+            PaletteCommand::ImportOsHistory => self.import_os_history(ctx),
+            PaletteCommand::BackUpNow => self.report_backup(),
+            PaletteCommand::Compact => self.report_compact(),
+            PaletteCommand::OpenTrash => self.show_trash_window = true,
+            PaletteCommand::OpenStorage => self.open_clips(),
+            PaletteCommand::CopyStoragePath => self.copy_data_dir(),
+            PaletteCommand::ToggleMaskSecrets => {
+                self.settings.mask_secrets = !self.settings.mask_secrets;
+                self.save_data();
+            }
+            PaletteCommand::ToggleAutostart => self.set_autostart(!self.autostart),
+            PaletteCommand::Quit => self.quit(ctx),
+        }
+    }
+
+    /// Show the storage backend, data file and outcome of the last save
+    fn storage_diagnostics(&self, ui: &mut egui::Ui) {
+        let path = self
+            .save_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "not available".to_owned());
+        let size = self
+            .save_path
+            .as_ref()
+            .and_then(|path| fs::metadata(path).ok())
+            .map(|meta| format_size(meta.len() as usize))
+            .unwrap_or_else(|| "no file yet".to_owned());
+
+        egui::Grid::new("storage_diagnostics")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Backend");
+                ui.label("JSON file");
+                ui.end_row();
+                ui.label("Path");
+                ui.label(RichText::new(path).small());
+                ui.end_row();
+                ui.label("Location");
+                if self.save_path.is_none() {
+                    ui.colored_label(Color32::LIGHT_RED, "none: history is not saved");
+                } else {
+                    match self.storage_kind {
+                        StorageKind::Config => {
+                            ui.label("config directory");
+                        }
+                        StorageKind::Portable => {
+                            ui.label("portable").on_hover_text(format!(
+                                "Next to the executable ({} or {})",
+                                PORTABLE_MARKER, PORTABLE_FLAG
+                            ));
+                        }
+                        StorageKind::Fallback => {
+                            ui.colored_label(Color32::YELLOW, "fallback")
+                                .on_hover_text("The config directory is unavailable");
+                        }
+                        StorageKind::Demo => {
+                            ui.colored_label(Color32::YELLOW, "demo")
+                                .on_hover_text("Sample data in a temporary folder");
+                        }
+                    }
+                }
+                ui.end_row();
+                ui.label("File size");
+                ui.label(size);
+                ui.end_row();
+                ui.label("Last compacted");
+                match self.last_compact {
+                    Some(time) => ui.label(time.format("%Y-%m-%d %H:%M").to_string()),
+                    None => ui.label(RichText::new("never").weak()),
+                };
+                ui.end_row();
+                ui.label("Last save");
+                match &self.last_save {
+                    None => {
+                        ui.label(RichText::new("not saved this session").weak());
+                    }
+                    Some((time, Ok(()))) => {
+                        ui.label(format!("✔ {}", time.format("%H:%M:%S")));
+                    }
+                    Some((time, Err(message))) => {
+                        ui.colored_label(
+                            Color32::LIGHT_RED,
+                            format!("✖ {}", time.format("%H:%M:%S")),
+                        )
+                        .on_hover_text(message);
+                    }
+                }
+                ui.end_row();
+            });
+    }
+
+    /// Copy the storage directory path to the clipboard
+    fn copy_data_dir(&mut self) {
+        let Some(dir) = self.data_dir() else {
+            self.set_status("Storage path not available", 2.0);
+            return;
+        };
+
+        if self.copy_with_newline(None, &dir.to_string_lossy(), true) {
+            self.set_status("Copied storage path", 2.0);
+        }
+    }
+}
+
+impl App for ClipboardManager {
+    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
+        // Check for new clipboard content
+        self.check_clipboard(ctx);
+
+        // Empty the clipboard if a copy asked for it
+        self.run_pending_wipe(ctx);
+
+        // Pick up changes made to the data file by other programs
+        self.reload_if_changed();
+
+        // Add the result of a transform once it's done
+        self.finish_transform();
+
+        // Add the OS clipboard history once it's been read
+        self.finish_os_import();
+
+        // Record the formats of new captures once they're listed
+        self.finish_format_listings();
+
+        // Unpin entries whose temporary pin has run out
+        self.expire_pins();
+
+        // Back up and compact the data file periodically
+        self.run_scheduled_backup();
+        self.run_scheduled_compact();
+
+        // Run actions for global hotkeys
+        self.handle_hotkeys(ctx);
+
+        // Keyboard navigation of the list
+        self.handle_keyboard(ctx);
+
+        // Minimize or hide instead of quitting, if configured
+        self.handle_close_request(ctx);
+
+        // Update status message timer
+        self.update_status(ctx); // Use delta_time instead of dt // Use delta time from ctx
+
+        // Keep the title's activity stats current
+        self.update_title(ctx);
+
+        // Check again soon while active, less often when idle
+        self.schedule_repaint(ctx);
+
+        // Remember the window geometry so it can be restored next launch
+        let settling = self.geometry_settles.is_some_and(|at| Instant::now() < at);
+        if let Some((outer, inner)) = ctx
+            .input(|i| i.viewport().outer_rect.zip(i.viewport().inner_rect))
+            .filter(|_| !settling)
+        {
+            let geometry = Some(WindowGeometry::from_rects(outer, inner));
+            if self.mini_mode {
+                self.mini_geometry = geometry;
+            } else {
+                self.window_geometry = geometry;
+            }
+        }
+
+        if self.mini_mode {
+            self.mini_ui(ctx);
+            return;
+        }
+
+        // Top panel with search and status
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            ui.add_space(8.0);
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new("🔍 Search").strong());
+                let search = ui.add_sized(
+                    [200.0, 28.0],
+                    egui::TextEdit::singleline(&mut self.search_term)
+                        .hint_text("text is:pinned type:url after:2024-01-01"),
+                );
+                if search.changed() {
+                    self.search_edited = Some(Instant::now());
+                }
+                if std::mem::take(&mut self.focus_search) {
+                    search.request_focus();
+                }
+
+                if ui
+                    .button("❌ Clear")
+                    .on_hover_text("Clear search")
+                    .clicked()
+                {
+                    self.search_term.clear();
+                    self.search_edited = None;
+                }
+
+                let typed = self.search_term.trim().chars().count();
+                if typed > 0 && typed < self.settings.search_min_chars {
+                    ui.label(
+                        RichText::new(format!(
+                            "Type {} or more characters",
+                            self.settings.search_min_chars
+                        ))
+                        .small()
+                        .weak(),
+                    );
+                }
+
+                ui.menu_button("⭐", |ui| self.saved_searches_menu(ui))
+                    .response
+                    .on_hover_text("Saved searches");
+
+                ui.toggle_value(&mut self.session_only, "🕘 This session")
+                    .on_hover_text("Only show entries captured since Klippy started");
+
+                let appearance = self.settings.appearance;
+                let shortcut =
+                    keymap::binding(&self.settings.keybindings, KeyAction::CycleAppearance);
+                let hint = if shortcut.is_empty() {
+                    format!("Switch to {}", appearance.next().label())
+                } else {
+                    format!("Switch to {} ({})", appearance.next().label(), shortcut)
+                };
+                if ui
+                    .button(format!("🎨 {}", appearance.label()))
+                    .on_hover_text(hint)
+                    .clicked()
+                {
+                    self.cycle_appearance(ctx);
+                }
+
+                if ui
+                    .button("🗕 Mini")
+                    .on_hover_text("Shrink to a small always-on-top strip of the newest entries")
+                    .clicked()
+                {
+                    self.set_mini_mode(ctx, true);
+                }
+
+                if ui
+                    .toggle_value(&mut self.settings.show_details, "ℹ Details")
+                    .on_hover_text("Show everything about the selected entry in a panel")
+                    .changed()
+                {
+                    self.save_data();
+                }
+
+                if self.monitoring_enabled {
+                    if ui
+                        .button("⏸ Pause")
+                        .on_hover_text("Stop capturing until resumed, even after a restart")
+                        .clicked()
+                    {
+                        self.set_monitoring(false);
+                    }
+                } else {
+                    if ui
+                        .button("▶ Resume")
+                        .on_hover_text("Start capturing again")
+                        .clicked()
+                    {
+                        self.set_monitoring(true);
+                    }
+                    ui.colored_label(Color32::YELLOW, "⏸ Capturing paused");
+                }
+
+                let previous_sort = (
+                    self.settings.sort_mode,
+                    self.settings.pinned_first,
+                    self.settings.pinned_first_in_search,
+                    self.settings.group_by_kind,
+                );
+                egui::ComboBox::from_id_salt("sort_mode")
+                    .selected_text(format!("↕ {}", self.settings.sort_mode.label()))
+                    .show_ui(ui, |ui| {
+                        for mode in SortMode::ALL {
+                            ui.selectable_value(&mut self.settings.sort_mode, mode, mode.label());
+                        }
+                        ui.separator();
+                        ui.checkbox(&mut self.settings.pinned_first, "📌 Pinned first");
+                        ui.add_enabled(
+                            !self.settings.pinned_first,
+                            egui::Checkbox::new(
+                                &mut self.settings.pinned_first_in_search,
+                                "📌 Pinned first in search results",
+                            ),
+                        )
+                        .on_disabled_hover_text("Pinned entries already come first everywhere");
+                        ui.checkbox(&mut self.settings.group_by_kind, "🗂 Group by type")
+                            .on_hover_text(
+                            "Group links, phone numbers, dates and text under their own headings",
+                        );
+                    });
+                let sort = (
+                    self.settings.sort_mode,
+                    self.settings.pinned_first,
+                    self.settings.pinned_first_in_search,
+                    self.settings.group_by_kind,
+                );
+                if sort != previous_sort {
+                    self.save_data();
+                }
+
+                // While searching, show how many entries match
+                let count = if self.applied_search.trim().is_empty() && !self.session_only {
+                    format!("{} items", self.entries.len())
+                } else {
+                    format!(
+                        "{} of {} shown",
+                        self.filtered_entries().len(),
+                        self.entries.len()
+                    )
+                };
+
+                // Pinned entries are never evicted, so warn as they fill the history
+                let pinned = self.pinned_count();
+                let cap = self
+                    .settings
+                    .max_pinned
+                    .unwrap_or(self.settings.max_entries)
+                    .min(self.settings.max_entries);
+                let pinned_text = match self.settings.max_pinned {
+                    Some(max) => format!("📌 {}/{}", pinned, max),
+                    None => format!("📌 {}", pinned),
+                };
+                let pinned_hint = if self.history_blocked() {
+                    "Every entry is pinned: new copies aren't captured"
+                } else if pinned >= self.settings.max_entries {
+                    "Every entry is pinned: the history is growing past the limit"
+                } else if self.pin_limit_reached() {
+                    "Pin limit reached"
+                } else {
+                    "Pinned entries are never evicted"
+                };
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(
+                        RichText::new(format!("📋 {} · {}", count, format_size(self.total_bytes)))
+                            .weak(),
+                    )
+                    .on_hover_text("Total size of stored clipboard content");
+                    let pinned_text = if pinned * 10 >= cap * 9 && pinned > 0 {
+                        RichText::new(pinned_text).color(Color32::YELLOW)
+                    } else {
+                        RichText::new(pinned_text).weak()
+                    };
+                    ui.label(pinned_text).on_hover_text(pinned_hint);
+                    if self.entries.len() > self.settings.max_entries {
+                        ui.colored_label(
+                            Color32::YELLOW,
+                            format!(
+                                "⚠ {} over limit",
+                                self.entries.len() - self.settings.max_entries
+                            ),
+                        )
+                        .on_hover_text("Every entry is pinned, so nothing can be evicted");
+                    } else if let Some((percent, usage)) = self.limit_usage() {
+                        let warning = ui
+                            .add(
+                                egui::Label::new(
+                                    RichText::new(format!("⚠ {}% full", percent))
+                                        .color(Color32::YELLOW),
+                                )
+                                .sense(Sense::click()),
+                            )
+                            .on_hover_text(format!(
+                                "{}: the oldest unpinned entries are dropped as new ones arrive.\nRaise the limits, or export and clear what you no longer need. Click to open Settings.",
+                                usage
+                            ));
+                        if warning.clicked() {
+                            self.show_settings_window = true;
+                        }
+                    }
+                });
+            });
+            ui.add_space(4.0);
+            ui.separator();
+        });
+
+        // Bottom panel with status messages
+        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal_wrapped(|ui| {
+                if self.settings.status_style == StatusStyle::Line {
+                    let status = self
+                        .status_messages
+                        .last()
+                        .map(|(msg, _)| msg.as_str())
+                        .unwrap_or("Ready");
+                    ui.label(RichText::new(status).color(self.accent()));
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("⏻ Quit").clicked() {
+                        self.quit(ctx);
+                    }
+
+                    if ui.button("⚙ Settings").clicked() {
+                        self.show_settings_window = true;
+                    }
+
+                    let clear = ui.button("🧹 Clear Unpinned");
+                    let shortcut =
+                        keymap::binding(&self.settings.keybindings, KeyAction::ClearUnpinned);
+                    let clear = if shortcut.is_empty() {
+                        clear
+                    } else {
+                        clear.on_hover_text(shortcut)
+                    };
+                    if clear.clicked() {
+                        self.request_clear_unpinned(ClearScope::All);
+                    }
+
+                    if ui.button("📂 Open Location").clicked() {
+                        self.open_clips();
+                    }
+                });
+            });
+            ui.add_space(4.0);
+            ui.separator();
+        });
+
+        // Apply the search typed this frame, once it's due
+        self.update_search(ctx);
+
+        // Toasts float over the list, clear of the top and bottom bars
+        self.show_toasts(ctx, ctx.available_rect());
+
+        if self.settings.show_details {
+            match self.settings.details_layout {
+                DetailsLayout::Side => {
+                    egui::SidePanel::right("details_panel")
+                        .default_width(240.0)
+                        .show(ctx, |ui| self.details_ui(ui));
+                }
+                DetailsLayout::Split => {
+                    let pane = egui::TopBottomPanel::bottom("details_split")
+                        .resizable(true)
+                        .default_height(self.split_height)
+                        .min_height(MIN_SPLIT_HEIGHT)
+                        .show(ctx, |ui| self.split_ui(ui));
+                    self.split_height = pane.response.rect.height();
+                }
+            }
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.scratchpad_ui(ui);
+
+            // egui keeps the scroll position within a session; the saved
+            // offset only matters on the first frame after launch
+            let mut list = egui::ScrollArea::vertical();
+            if std::mem::take(&mut self.restore_scroll) {
+                list = list.vertical_scroll_offset(self.scroll_offset);
+            }
+            if std::mem::take(&mut self.scroll_to_top) {
+                list = list.vertical_scroll_offset(0.0);
+            }
+            let list = list.show(ui, |ui| {
+                // Store the ids of filtered entries in a local variable to avoid
+                // keeping the immutable borrow of self active. Ids stay valid when
+                // an action below removes or reorders entries mid-frame.
+                // Resolve the timestamp format once, and size the time column to fit it
+                let time_format = if is_valid_time_format(&self.settings.time_format) {
+                    self.settings.time_format.clone()
+                } else {
+                    DEFAULT_TIME_FORMAT.to_owned()
+                };
+                let time_column_width = ui.fonts(|fonts| {
+                    fonts
+                        .layout_no_wrap(
+                            Local::now().format(&time_format).to_string(),
+                            egui::TextStyle::Body.resolve(ui.style()),
+                            Color32::LIGHT_GRAY,
+                        )
+                        .size()
+                        .x
+                }) + TIME_COLUMN_GAP;
+
+                let filtered: Vec<u64> = self
+                    .filtered_entries()
+                    .into_iter()
+                    .map(|idx| self.entries[idx].id)
+                    .collect();
+
+                if filtered.is_empty() {
+                    ui.centered_and_justified(|ui| {
+                        if self.entries.is_empty() {
+                            ui.label("No clipboard entries yet. Copy something to add it here.");
+                        } else {
+                            // Name the filters, since they're easy to overlook
+                            let mut filters = Query::parse(&self.applied_search).filter_labels();
+                            if self.session_only {
+                                filters.push("\"This session\"".to_owned());
+                            }
+                            if filters.is_empty() {
+                                ui.label("No matching entries found. Try different search text.");
+                            } else {
+                                ui.label(format!(
+                                    "No entries match the filters {}. Remove them or clear the search to see all entries.",
+                                    filters.join(", ")
+                                ));
+                            }
+                        }
+                    });
+                } else {
+                    // Entries per content kind, for the group headings
+                    let mut group_counts: HashMap<ContentKind, usize> = HashMap::new();
+                    if self.settings.group_by_kind {
+                        for &id in &filtered {
+                            if let Some(idx) = self.entry_index(id) {
+                                *group_counts.entry(self.entries[idx].kind()).or_default() += 1;
+                            }
+                        }
+                    }
+                    let mut current_group = None;
+
+                    // Clone any data needed in closures to avoid borrowing self
+                    for &id in &filtered {
+                        ui.push_id(id, |ui| {
+                            // Skip entries removed earlier in this frame
+                            let Some(idx) = self.entry_index(id) else {
+                                return;
+                            };
+
+                            // Heading at the start of each content kind group
+                            if self.settings.group_by_kind {
+                                let kind = self.entries[idx].kind();
+                                if current_group != Some(kind) {
+                                    current_group = Some(kind);
+                                    let count =
+                                        group_counts.get(&kind).copied().unwrap_or_default();
+                                    ui.add_space(4.0);
+                                    self.group_header(ui, kind, count);
+                                }
+                                if self.collapsed_groups.contains(&kind) {
+                                    return;
+                                }
+                            }
+
+                            let preview = self.entries[idx].preview(self.settings.preview_chars);
+                            let extra_lines = self.entries[idx].extra_lines();
+                            let line_ending = self.entries[idx].line_ending();
+                            let formatted_time = self.entries[idx].formatted_time(&time_format);
+                            let is_pinned = self.entries[idx].pinned;
+                            let pin_remaining = self.entries[idx].pin_remaining();
+                            let kind = self.entries[idx].kind();
+                            let last_used = self.entries[idx].last_used_text();
+                            let masked = self.is_masked(&self.entries[idx]);
+                            let maskable = masked || self.revealed.contains(&id);
+                            let revisions = self.entries[idx].revisions;
+                            let occurrences = self.entries[idx].occurrences;
+                            let local_only = self.entries[idx].local_only;
+                            let one_shot = self.entries[idx].one_shot;
+                            let rich_formats = self.entries[idx].rich_formats().join(", ");
+                            let trimmed = self.entries[idx].raw.is_some();
+                            let lossy = self.entries[idx].lossy;
+                            let is_locked = self.entries[idx].locked;
+                            let needs_cleanup = self.entries[idx].needs_cleanup();
+                            let slot = self.slot_of(id);
+                            let opacity = if self.settings.fade_old_entries
+                                && !is_pinned
+                                && !self.high_contrast()
+                            {
+                                self.entries[idx].freshness()
+                            } else {
+                                1.0
+                            };
+                            let content = &self.entries[idx].content;
+                            let ends_with_newline = content.ends_with('\n');
+                            let is_current = *content == self.current_clipboard
+                                || (ends_with_newline
+                                    && detect::strip_trailing_newline(content)
+                                        == self.current_clipboard);
+
+                            // Expanded multi-line rows grow to show up to
+                            // `max_expanded_lines` extra lines
+                            let is_expanded =
+                                extra_lines > 0 && !masked && self.expanded.contains(&id);
+                            let expanded_lines = if is_expanded {
+                                self.entries[idx].expanded_lines(self.settings.max_expanded_lines)
+                            } else {
+                                Vec::new()
+                            };
+                            let hidden_lines = extra_lines - expanded_lines.len().min(extra_lines);
+                            let line_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                            let expanded_height = if is_expanded {
+                                let link_rows = if hidden_lines > 0 { 1.5 } else { 0.0 };
+                                (expanded_lines.len() as f32 + link_rows) * line_height + 8.0
+                            } else {
+                                0.0
+                            };
+
+                            let (rect, response) = ui.allocate_exact_size(
+                                Vec2::new(ui.available_width(), ROW_HEIGHT + expanded_height),
+                                Sense::click(),
+                            );
+
+                            // Draw entry background
+                            let bg_color = if is_pinned {
+                                Color32::from_rgb(50, 50, 60)
+                            } else {
+                                Color32::from_rgb(30, 30, 35)
+                            };
+                            ui.painter().rect_filled(rect, 4.0, bg_color);
+                            // Pinned rows also get a left border, so pinning doesn't
+                            // rely on the background shade alone
+                            if is_pinned {
+                                ui.painter().rect_filled(
+                                    egui::Rect::from_min_size(
+                                        rect.min,
+                                        Vec2::new(3.0, rect.height()),
+                                    ),
+                                    egui::CornerRadius {
+                                        nw: 4,
+                                        sw: 4,
+                                        ..Default::default()
+                                    },
+                                    Color32::from_rgb(230, 180, 60),
+                                );
+                            }
+                            if is_current {
+                                ui.painter().rect_stroke(
+                                    rect.shrink(1.0),
+                                    4.0,
+                                    Stroke::new(1.0, self.accent()),
+                                    egui::StrokeKind::Inside,
+                                );
+                            }
+
+                            // A click selects the row and runs the click action. Copying
+                            // moves to a double-click when that's required, and stays
+                            // on a double-click when clicking does something else.
+                            let click_action = self.settings.click_action;
+                            let copy_on_double = self.settings.double_click_to_copy
+                                || click_action != ClickAction::Copy;
+                            if response.clicked() {
+                                self.selected = Some(id);
+                                match click_action {
+                                    ClickAction::Copy if !copy_on_double => {
+                                        self.copy_entry(id);
+                                    }
+                                    ClickAction::Expand if extra_lines > 0 && !masked => {
+                                        self.toggle_expanded(id);
+                                    }
+                                    ClickAction::View => self.view_entry(id),
+                                    _ => {}
+                                }
+                            }
+                            if copy_on_double && response.double_clicked() {
+                                self.copy_entry(id);
+                            }
+
+                            // Fade a highlight out over newly captured rows
+                            if let Some((_, captured)) =
+                                self.flash.filter(|&(flashed, _)| {
+                                    flashed == id && self.settings.flash_captures && !self.reduced_motion()
+                                })
+                            {
+                                let progress =
+                                    captured.elapsed().as_secs_f32() / FLASH_DURATION.as_secs_f32();
+                                if progress < 1.0 {
+                                    ui.painter().rect_filled(
+                                        rect,
+                                        4.0,
+                                        self.accent().gamma_multiply(0.35 * (1.0 - progress)),
+                                    );
+                                    ui.ctx().request_repaint();
+                                } else {
+                                    self.flash = None;
+                                }
+                            }
+
+                            // Keyboard selection
+                            if self.selected == Some(id) {
+                                ui.painter().rect_filled(
+                                    rect,
+                                    4.0,
+                                    self.accent().gamma_multiply(0.12),
+                                );
+                                if self.scroll_to_selected {
+                                    ui.scroll_to_rect(rect, None);
+                                    self.scroll_to_selected = false;
+                                }
+                            }
+
+                            // Context menu with per-entry actions
+                            response.context_menu(|ui| self.entry_context_menu(ui, id));
+
+                            // Peek at the content without opening the viewer
+                            response.on_hover_ui(|ui| {
+                                if let Some(index) = self.entry_index(id).filter(|_| !masked) {
+                                    ui.set_max_width(480.0);
+                                    let tooltip = tooltip_text(&self.entries[index].content);
+                                    ui.label(RichText::new(tooltip).monospace());
+                                    ui.separator();
+                                }
+                                ui.label(RichText::new(last_used).small().weak());
+                            });
+
+                            // Lay the row out as time, preview and action columns so
+                            // they line up across rows
+                            let header_rect = egui::Rect::from_min_size(
+                                rect.min,
+                                Vec2::new(rect.width(), ROW_HEIGHT),
+                            );
+                            let row_rect = header_rect.shrink(8.0);
+                            let show_time = !(self.settings.hide_time_when_narrow
+                                && row_rect.width() < NARROW_ROW_WIDTH);
+                            let time_width = if show_time { time_column_width } else { 0.0 };
+                            let preview_width =
+                                (row_rect.width() - time_width) * self.settings.preview_share;
+                            let time_rect = egui::Rect::from_min_size(
+                                row_rect.min,
+                                Vec2::new(time_width, row_rect.height()),
+                            );
+                            let preview_rect = egui::Rect::from_min_size(
+                                time_rect.right_top(),
+                                Vec2::new(preview_width, row_rect.height()),
+                            );
+                            let actions_rect =
+                                egui::Rect::from_min_max(preview_rect.right_top(), row_rect.max);
+                            let column = |ui: &mut egui::Ui, rect, layout| {
+                                let mut child = ui.new_child(
+                                    egui::UiBuilder::new().max_rect(rect).layout(layout),
+                                );
+                                child.set_clip_rect(rect);
+                                child
+                            };
+                            // The text columns fade with age; actions stay
+                            // fully visible
+                            let faded = |ui: &mut egui::Ui, rect, layout| {
+                                let mut child = column(ui, rect, layout);
+                                child.multiply_opacity(opacity);
+                                child
+                            };
+                            let left = egui::Layout::left_to_right(egui::Align::Center);
+
+                            // Time
+                            if show_time {
+                                faded(ui, time_rect, left).add(
+                                    egui::Label::new(
+                                        RichText::new(formatted_time).color(Color32::LIGHT_GRAY),
+                                    )
+                                    .truncate(),
+                                );
+                            }
+
+                            faded(ui, preview_rect, left).horizontal(|ui| {
+                                // Pinned badge
+                                if is_pinned {
+                                    ui.label("📌").on_hover_text("Pinned");
+                                }
+                                if let Some(remaining) = &pin_remaining {
+                                    ui.label(RichText::new(format!("⏳{}", remaining)).small().weak())
+                                        .on_hover_text(format!("Unpinned again in {}", remaining));
+                                }
+
+                                // Numbered hotkey slot
+                                if let Some(slot) = slot {
+                                    ui.label(RichText::new(format!("⌨{}", slot)).small().weak())
+                                        .on_hover_text(format!(
+                                            "Copied with {}",
+                                            self.settings.slot_keys.hotkey(slot)
+                                        ));
+                                }
+
+                                // Kept out of exports and sync
+                                if local_only {
+                                    ui.label(RichText::new("🏠").small())
+                                        .on_hover_text("Local only: not exported or synced");
+                                }
+
+                                // Removed once copied
+                                if one_shot {
+                                    ui.label(RichText::new("🔥").small())
+                                        .on_hover_text("One-shot: removed after it's copied once");
+                                }
+
+                                // Marked for comparison
+                                if self.compare_base == Some(id) {
+                                    ui.label("⚖").on_hover_text(
+                                        "Marked for comparison; right-click another entry to compare",
+                                    );
+                                }
+
+                                // Marker for what's on the clipboard right now
+                                if is_current {
+                                    ui.label(
+                                        RichText::new("● current")
+                                            .small()
+                                            .color(self.accent()),
+                                    )
+                                    .on_hover_text("This is what's on the clipboard now");
+                                }
+
+                                // Content kind badge
+                                if kind != ContentKind::Text {
+                                    ui.label(RichText::new(kind.label()).small().weak());
+                                }
+
+                                // Content preview, obscured for likely secrets
+                                if masked {
+                                    ui.label(RichText::new("••••••••••••").weak());
+                                } else {
+                                    ui.add(egui::Label::new(preview).truncate());
+                                }
+                                if is_locked {
+                                    ui.label(RichText::new("🔒").small().weak()).on_hover_text(
+                                        "Locked: can't be deleted, replaced or transformed until unlocked",
+                                    );
+                                }
+                                // Times the same text was captured
+                                if occurrences > 1 {
+                                    ui.label(RichText::new(format!("×{}", occurrences)).small().weak())
+                                        .on_hover_text(format!("Captured {} times", occurrences));
+                                }
+                                // Similar versions merged into this entry
+                                if revisions > 0 {
+                                    ui.label(RichText::new(format!("✎ {}", revisions)).small().weak())
+                                        .on_hover_text(format!(
+                                            "Replaced {} similar earlier version(s); they're in the trash",
+                                            revisions
+                                        ));
+                                }
+                                // Richer flavors were on the clipboard than the text kept
+                                if !rich_formats.is_empty() {
+                                    ui.label(RichText::new("✨").small().weak()).on_hover_text(
+                                        format!(
+                                            "Rich content was available: {}\nOnly the plain text is kept and copied back",
+                                            rich_formats
+                                        ),
+                                    );
+                                }
+                                if lossy {
+                                    ui.label(RichText::new("�").small().weak()).on_hover_text(
+                                        "The copied text wasn't valid UTF-8; invalid bytes were replaced, so it may differ from the original",
+                                    );
+                                }
+                                if trimmed {
+                                    ui.label(RichText::new("✂").small().weak()).on_hover_text(
+                                        "Surrounding whitespace was trimmed on capture; right-click to copy the raw text",
+                                    );
+                                }
+                                if needs_cleanup && !masked {
+                                    ui.label(RichText::new("❝").small().weak()).on_hover_text(
+                                        "Contains smart quotes, typographic dashes or unnormalized Unicode; right-click to clean up",
+                                    );
+                                }
+                                if ends_with_newline && !masked {
+                                    ui.label(RichText::new("↵").small().weak())
+                                        .on_hover_text("Ends with a newline");
+                                }
+                                if extra_lines > 0 && !masked {
+                                    let plural = if extra_lines == 1 { "" } else { "s" };
+                                    let ending = line_ending.map(|e| e.label()).unwrap_or_default();
+                                    let arrow = if is_expanded { "⏶" } else { "⏎" };
+                                    let badge =
+                                        ui.add(
+                                            egui::Label::new(
+                                                RichText::new(format!(
+                                                    "{} {} more line{} · {}",
+                                                    arrow, extra_lines, plural, ending
+                                                ))
+                                                .small()
+                                                .weak(),
+                                            )
+                                            .sense(Sense::click()),
+                                        )
+                                        .on_hover_text(
+                                            if is_expanded { "Collapse" } else { "Expand" },
+                                        );
+                                    if badge.clicked() {
+                                        self.toggle_expanded(id);
+                                    }
+                                }
+                            });
+
+                            // Extra lines of an expanded row, below the preview
+                            if is_expanded {
+                                let body_rect = egui::Rect::from_min_max(
+                                    egui::pos2(preview_rect.left(), header_rect.bottom()),
+                                    rect.max - Vec2::new(8.0, 4.0),
+                                );
+                                let mut body =
+                                    faded(ui, body_rect, egui::Layout::top_down(egui::Align::Min));
+                                body.spacing_mut().item_spacing.y = 0.0;
+                                for line in expanded_lines {
+                                    body.add(
+                                        egui::Label::new(RichText::new(line).monospace())
+                                            .truncate(),
+                                    );
+                                }
+                                if hidden_lines > 0 {
+                                    body.add_space(line_height * 0.25);
+                                    if body
+                                        .link(format!("View full ({} more lines)", hidden_lines))
+                                        .clicked()
+                                    {
+                                        self.view_entry(id);
+                                    }
+                                }
+                            }
+
+                            let right = egui::Layout::right_to_left(egui::Align::Center);
+                            column(ui, actions_rect, right).scope(|ui| {
+                                let actions = self.settings.inline_actions;
+
+                                // copy button
+                                if actions.copy && ui.button("📋").clicked() {
+                                    self.copy_entry(id);
+                                }
+
+                                // Delete button, targeting the entry by id
+                                let protected =
+                                    is_locked || (self.settings.protect_pinned && is_pinned);
+                                if actions.delete
+                                    && ui
+                                        .add_enabled(!protected, egui::Button::new("🗑"))
+                                        .on_disabled_hover_text(if is_locked {
+                                            "Unlock to delete"
+                                        } else {
+                                            "Unpin to delete"
+                                        })
+                                        .clicked()
+                                {
+                                    self.request_delete(id);
+                                }
+
+                                // Pin button, targeting the entry by id
+                                let pin_text = if is_pinned { "📌" } else { "📍" };
+                                if actions.pin && ui.button(pin_text).clicked() {
+                                    self.toggle_pin(id);
+                                }
+
+                                // Reveal/hide button for likely secrets
+                                if maskable {
+                                    let (icon, tip) = if masked {
+                                        ("👁", "Reveal")
+                                    } else {
+                                        ("🔒", "Hide")
+                                    };
+                                    if ui.button(icon).on_hover_text(tip).clicked() {
+                                        if masked {
+                                            self.revealed.insert(id);
+                                        } else {
+                                            self.revealed.remove(&id);
+                                        }
+                                    }
+                                }
+                            });
+
+                            // Draw separator
                             ui.painter().line_segment(
                                 [rect.left_bottom(), rect.right_bottom()],
                                 Stroke::new(1.0, Color32::from_rgb(50, 50, 55)),
@@ -430,49 +6167,1343 @@ impl App for ClipboardManager {
                         });
                     }
                 }
-            });
-        });
+            });
+            self.scroll_offset = list.state.offset.y;
+        });
+
+        // Backups are listed when the settings open rather than every frame
+        if !self.show_settings_window {
+            self.backups = None;
+        }
+        if self.show_settings_window {
+            let mut show = self.show_settings_window;
+            let mut max_entries = self.settings.max_entries;
+            let mut limit_size = self.settings.max_total_mb.is_some();
+            let mut max_total_mb = self.settings.max_total_mb.unwrap_or(10);
+            let mut warn_limit = self.settings.limit_warning.is_some();
+            let mut limit_warning = self.settings.limit_warning.unwrap_or(90);
+            let mut min_capture_chars = self.settings.min_capture_chars;
+            let mut idle_repaint_secs = self.settings.idle_repaint_secs;
+            let mut limit_pinned = self.settings.max_pinned.is_some();
+            let mut full_history = self.settings.full_history;
+            let mut max_pinned = self.settings.max_pinned.unwrap_or(20);
+            let mut use_duplicate_window = self.settings.duplicate_window_secs.is_some();
+            let mut count_duplicates = self.settings.count_duplicates;
+            let mut duplicate_window_secs = self.settings.duplicate_window_secs.unwrap_or(60);
+            let mut merge_whitespace_changes = self.settings.merge_whitespace_changes;
+            let mut trim_on_capture = self.settings.trim_on_capture;
+            let mut invalid_text = self.settings.invalid_text;
+            let mut merge_similar = self.settings.merge_similar;
+            let mut record_clears = self.settings.record_clears;
+            let mut similarity_threshold = self.settings.similarity_threshold;
+            let mut dedup_includes_pinned = self.settings.dedup_includes_pinned;
+            let mut append_separator = self.settings.append_separator.clone();
+            let mut inline_actions = self.settings.inline_actions;
+            let mut click_action = self.settings.click_action;
+            let mut double_click_to_copy = self.settings.double_click_to_copy;
+            let mut confirm_delete = self.settings.confirm_delete;
+            let mut scroll_to_new = self.settings.scroll_to_new;
+            let mut remember_scroll = self.settings.remember_scroll;
+            let mut search_min_chars = self.settings.search_min_chars;
+            let mut search_debounce_ms = self.settings.search_debounce_ms;
+            let mut flash_captures = self.settings.flash_captures;
+            let mut fade_old_entries = self.settings.fade_old_entries;
+            let mut reduced_motion = self.settings.reduced_motion;
+            let mut high_contrast = self.settings.high_contrast;
+            let mut status_style = self.settings.status_style;
+            let mut toast_corner = self.settings.toast_corner;
+            let mut copy_to_primary = self.settings.copy_to_primary;
+            let mut protect_pinned = self.settings.protect_pinned;
+            let mut auto_pin = self.settings.auto_pin;
+            let mut auto_pin_threshold = self.settings.auto_pin_threshold;
+            let mut strip_trailing_newline = self.settings.strip_trailing_newline;
+            let mut preview_share = self.settings.preview_share;
+            let mut details_layout = self.settings.details_layout;
+            let mut max_expanded_lines = self.settings.max_expanded_lines;
+            let mut time_format = self.settings.time_format.clone();
+            let mut accent_color = self.settings.accent_color;
+            let mut appearance = self.settings.appearance;
+            let mut hide_time_when_narrow = self.settings.hide_time_when_narrow;
+            let mut max_trash = self.settings.max_trash;
+            let mut paste_ring_enabled = self.settings.paste_ring_enabled;
+            let mut paste_ring_size = self.settings.paste_ring_size;
+            let mut slot_count = self.settings.slot_count;
+            let mut slot_keys = self.settings.slot_keys;
+            let mut paste_ring_hotkey = self.settings.paste_ring_hotkey.clone();
+            let mut close_action = self.settings.close_action;
+            let mut show_hotkey = self.settings.show_hotkey.clone();
+            let mut panic_enabled = self.settings.panic_enabled;
+            let mut panic_hotkey = self.settings.panic_hotkey.clone();
+            let mut auto_paste = self.settings.auto_paste;
+            let mut mask_secrets = self.settings.mask_secrets;
+            let mut capture_allowlist = self.settings.capture_allowlist.join(", ");
+            let mut capture_denylist = self.settings.capture_denylist.join(", ");
+            let mut secret_rules = self.settings.secret_rules;
+            let mut clipboard_wipe = self.settings.clipboard_wipe;
+            let mut wipe_after_secs = self.settings.wipe_after_secs;
+            let mut trash_retention_days = self.settings.trash_retention_days;
+            let mut save = false;
+            let mut import_history = false;
+            let mut export = None;
+            let mut autostart = self.autostart;
+            let mut toggle_autostart = false;
+            let mut import_file = None;
+            let mut export_settings = false;
+            let mut import_settings = false;
+            let mut auto_backup = self.settings.auto_backup;
+            let mut backup_interval_mins = self.settings.backup_interval_mins;
+            let mut max_backups = self.settings.max_backups;
+            let mut auto_compact = self.settings.auto_compact;
+            let mut verbose_logging = self.settings.verbose_logging;
+            let mut storage_format = self.settings.storage_format;
+            let mut verify_copies = self.settings.verify_copies;
+            let mut capture_log = self.settings.capture_log;
+            let mut compact = false;
+            let mut open_log = false;
+            let mut preview_imports = self.settings.preview_imports;
+            let mut clear_older = false;
+            let mut backup = false;
+            let mut restore = None;
+            let mut open_dir = false;
+            let mut save_now = false;
+            let mut copy_dir = false;
+            let save_path = self.save_path.clone();
+            let backups = self
+                .backups
+                .get_or_insert_with(|| {
+                    save_path
+                        .as_deref()
+                        .map(backup::list_backups)
+                        .unwrap_or_default()
+                })
+                .clone();
+
+            egui::Window::new("⚙ Settings")
+                .open(&mut show)
+                .resizable(false)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    // Only the sections scroll, so the buttons below stay in view
+                    egui::ScrollArea::vertical()
+                        .max_height(ctx.screen_rect().height() * 0.7)
+                        .show(ui, |ui| {
+                            egui::CollapsingHeader::new("📚 History")
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    ui.label("Set max number of entries to retain:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut max_entries)
+                                            .range(10..=500)
+                                            .speed(1),
+                                    );
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut limit_size, "Also limit total size to");
+                                        ui.add_enabled(
+                                            limit_size,
+                                            egui::DragValue::new(&mut max_total_mb)
+                                                .range(1..=1024)
+                                                .suffix(" MB"),
+                                        );
+                                    })
+                                    .response
+                                    .on_hover_text("Oldest unpinned entries are removed until the history fits");
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut warn_limit, "Warn when the history is");
+                                        ui.add_enabled(
+                                            warn_limit,
+                                            egui::DragValue::new(&mut limit_warning)
+                                                .range(50..=100)
+                                                .suffix("% full"),
+                                        );
+                                    })
+                                    .response
+                                    .on_hover_text("Shows a note in the top bar as the entry count or total size nears its limit");
+                                    ui.horizontal(|ui| {
+                                        ui.label("When every entry is pinned:");
+                                        for option in [FullHistory::Block, FullHistory::Grow] {
+                                            ui.radio_value(&mut full_history, option, option.label());
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut limit_pinned, "Allow at most");
+                                        ui.add_enabled(
+                                            limit_pinned,
+                                            egui::DragValue::new(&mut max_pinned).range(1..=500),
+                                        );
+                                        ui.label("pinned entries");
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Ignore copies shorter than");
+                                        ui.add(egui::DragValue::new(&mut min_capture_chars).range(1..=100));
+                                        ui.label("characters");
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("When idle, check the clipboard every");
+                                        ui.add(
+                                            egui::DragValue::new(&mut idle_repaint_secs)
+                                                .range(1..=60)
+                                                .suffix(" s"),
+                                        );
+                                    })
+                                    .response
+                                    .on_hover_text(
+                                        "Longer intervals save battery, but copies made in quick succession while idle may be missed",
+                                    );
+                                });
+                            ui.collapsing("📋 Capturing", |ui| {
+                                ui.checkbox(&mut count_duplicates, "Count duplicates")
+                                    .on_hover_text(
+                                        "Copying an entry's text again moves it to the top and counts how often it was copied",
+                                    );
+                                ui.add_enabled_ui(!count_duplicates, |ui| {
+                                    ui.checkbox(
+                                        &mut use_duplicate_window,
+                                        "Re-add duplicates copied again after a while",
+                                    );
+                                    ui.add_enabled_ui(use_duplicate_window, |ui| {
+                                        ui.horizontal(|ui| {
+                                            ui.label("Ignore duplicates within");
+                                            ui.add(
+                                                egui::DragValue::new(&mut duplicate_window_secs)
+                                                    .range(1..=86400)
+                                                    .suffix(" s"),
+                                            );
+                                        });
+                                    });
+                                });
+                                ui.checkbox(
+                                    &mut dedup_includes_pinned,
+                                    "Pinned entries count as duplicates",
+                                )
+                                .on_hover_text(
+                                    "When off, copying a pinned entry's text again adds a new unpinned entry",
+                                );
+                                ui.checkbox(
+                                    &mut merge_whitespace_changes,
+                                    "Update the latest entry when only surrounding whitespace changes",
+                                )
+                                .on_hover_text(
+                                    "Follows the duplicate settings above: pinned entries are only updated if they count as duplicates, and updates are counted when counting duplicates",
+                                );
+                                ui.checkbox(
+                                    &mut trim_on_capture,
+                                    "Trim surrounding whitespace from captures",
+                                )
+                                .on_hover_text(
+                                    "The untrimmed text is kept; right-click a trimmed entry to copy it",
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.label("Copied text that isn't valid UTF-8:");
+                                    for option in [InvalidText::Convert, InvalidText::Skip] {
+                                        ui.radio_value(&mut invalid_text, option, option.label());
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut merge_similar, "Replace recent entries at least");
+                                    ui.add_enabled(
+                                        merge_similar,
+                                        egui::Slider::new(&mut similarity_threshold, 0.5..=0.99)
+                                            .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+                                    );
+                                    ui.label("similar");
+                                })
+                                .response
+                                .on_hover_text(format!(
+                                    "Compares new captures with the last {} entries; replaced versions go to the trash",
+                                    SIMILARITY_WINDOW
+                                ));
+                                ui.checkbox(&mut record_clears, "Record when the clipboard is cleared")
+                                    .on_hover_text("Adds a \"Clipboard cleared\" marker instead of ignoring empty reads");
+                            });
+                            ui.collapsing("🎨 Appearance", |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label("Row buttons:");
+                                    ui.checkbox(&mut inline_actions.copy, "Copy");
+                                    ui.checkbox(&mut inline_actions.delete, "Delete");
+                                    ui.checkbox(&mut inline_actions.pin, "Pin");
+                                })
+                                .response
+                                .on_hover_text("Hidden actions remain available by right-clicking a row");
+                                ui.horizontal(|ui| {
+                                    ui.label("Clicking a row:");
+                                    for option in ClickAction::ALL {
+                                        ui.radio_value(&mut click_action, option, option.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Expand only applies to multi-line entries; others are selected");
+                                ui.add_enabled(
+                                    click_action == ClickAction::Copy,
+                                    egui::Checkbox::new(&mut double_click_to_copy, "Double-click a row to copy it"),
+                                )
+                                .on_hover_text("The copy button always copies with a single click")
+                                .on_disabled_hover_text("Rows are copied with a double-click when clicking does something else");
+                                ui.horizontal(|ui| {
+                                    ui.label("Appearance:");
+                                    for option in Appearance::ALL {
+                                        ui.radio_value(&mut appearance, option, option.label());
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Accent color:");
+                                    ui.color_edit_button_srgb(&mut accent_color);
+                                    if ui.small_button("Reset").clicked() {
+                                        accent_color = default_accent_color();
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Timestamp format:");
+                                    ui.add(egui::TextEdit::singleline(&mut time_format).desired_width(120.0))
+                                        .on_hover_text("A chrono format string, e.g. %H:%M or %d/%m %H:%M");
+                                    for (label, preset) in TIME_FORMAT_PRESETS {
+                                        if ui.small_button(*label).clicked() {
+                                            time_format = (*preset).to_owned();
+                                        }
+                                    }
+                                });
+                                if is_valid_time_format(&time_format) {
+                                    ui.label(
+                                        RichText::new(format!("e.g. {}", Local::now().format(&time_format)))
+                                            .small()
+                                            .weak(),
+                                    );
+                                } else {
+                                    ui.label(
+                                        RichText::new("Invalid format, the default will be used")
+                                            .small()
+                                            .color(Color32::LIGHT_RED),
+                                    );
+                                }
+                                // Applied while dragging so the list can be tuned by eye
+                                ui.horizontal(|ui| {
+                                    ui.label("Preview length:");
+                                    let slider = ui.add(
+                                        egui::Slider::new(&mut self.settings.preview_chars, MIN_PREVIEW_CHARS..=200)
+                                            .suffix(" chars"),
+                                    );
+                                    if slider.drag_stopped() || (slider.changed() && !slider.dragged()) {
+                                        self.save_data();
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Preview column width:");
+                                    ui.add(
+                                        egui::Slider::new(&mut preview_share, 0.4..=0.9)
+                                            .custom_formatter(|share, _| format!("{:.0}%", share * 100.0)),
+                                    );
+                                });
+                                ui.checkbox(
+                                    &mut hide_time_when_narrow,
+                                    "Hide timestamps in narrow windows",
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.label("Details panel:");
+                                    for layout in DetailsLayout::ALL {
+                                        ui.radio_value(&mut details_layout, layout, layout.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Below the list, the details share a resizable pane with the selected entry's full content");
+                                ui.horizontal(|ui| {
+                                    ui.label("Expanded rows show up to");
+                                    ui.add(egui::DragValue::new(&mut max_expanded_lines).range(1..=100));
+                                    ui.label("extra lines");
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Search after");
+                                    ui.add(egui::DragValue::new(&mut search_min_chars).range(1..=10));
+                                    ui.label("characters and a pause of");
+                                    ui.add(
+                                        egui::DragValue::new(&mut search_debounce_ms)
+                                            .range(0..=2000)
+                                            .speed(10)
+                                            .suffix(" ms"),
+                                    );
+                                })
+                                .response
+                                .on_hover_text("Raise these for very large histories");
+                                ui.checkbox(&mut flash_captures, "Flash new entries when they're captured");
+                                ui.checkbox(&mut fade_old_entries, "Fade older entries")
+                                    .on_hover_text("Unpinned entries get fainter over their first week, so stale ones stand out. Off with high contrast.");
+                                ui.horizontal(|ui| {
+                                    ui.label("Show messages as");
+                                    for style in [StatusStyle::Line, StatusStyle::Toasts] {
+                                        ui.radio_value(&mut status_style, style, style.label());
+                                    }
+                                    ui.add_enabled_ui(status_style == StatusStyle::Toasts, |ui| {
+                                        egui::ComboBox::from_id_salt("toast_corner")
+                                            .selected_text(toast_corner.label())
+                                            .show_ui(ui, |ui| {
+                                                for corner in ToastCorner::ALL {
+                                                    ui.selectable_value(&mut toast_corner, corner, corner.label());
+                                                }
+                                            });
+                                    });
+                                })
+                                .response
+                                .on_hover_text("Toasts stack up, so quick successive messages aren't missed");
+                                let system = |value: Option<bool>| match value {
+                                    Some(true) => "system: on",
+                                    Some(false) => "system: off",
+                                    None => "system: unknown",
+                                };
+                                for (label, preference, reported) in [
+                                    (
+                                        "Reduce motion",
+                                        &mut reduced_motion,
+                                        self.system_preferences.reduced_motion,
+                                    ),
+                                    (
+                                        "High contrast",
+                                        &mut high_contrast,
+                                        self.system_preferences.high_contrast,
+                                    ),
+                                ] {
+                                    ui.horizontal(|ui| {
+                                        ui.label(label);
+                                        for option in [Preference::System, Preference::On, Preference::Off] {
+                                            ui.radio_value(preference, option, option.label());
+                                        }
+                                        ui.label(RichText::new(system(reported)).small().weak());
+                                    });
+                                }
+                            });
+                            ui.collapsing("🖱 Copying", |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label("Separator for Copy (append):");
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut append_separator).desired_width(60.0),
+                                    )
+                                    .on_hover_text("Use \\n for a newline and \\t for a tab");
+                                });
+                                ui.checkbox(&mut scroll_to_new, "Scroll to the top when something is copied")
+                                    .on_hover_text("Off keeps your place while reviewing older entries");
+                                ui.checkbox(&mut remember_scroll, "Restore the scroll position on launch");
+                                ui.checkbox(&mut confirm_delete, "Ask before deleting an entry");
+                                ui.add_enabled(
+                                    PrimarySelection::is_supported(),
+                                    egui::Checkbox::new(
+                                        &mut copy_to_primary,
+                                        "Also copy to the primary selection (middle-click paste)",
+                                    ),
+                                )
+                                .on_disabled_hover_text("There's no primary selection on this platform");
+                                ui.checkbox(&mut verify_copies, "Check that copies reach the clipboard")
+                                    .on_hover_text("Reads the clipboard back after copying and retries once if it didn't change. Turn on if pasting sometimes gives the previous content.");
+                                ui.checkbox(
+                                    &mut protect_pinned,
+                                    "Pinned entries can't be deleted until unpinned",
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut auto_pin, "Pin entries automatically after");
+                                    ui.add_enabled(
+                                        auto_pin,
+                                        egui::DragValue::new(&mut auto_pin_threshold)
+                                            .range(2..=1000)
+                                            .suffix(" copies"),
+                                    );
+                                })
+                                .response
+                                .on_hover_text("Pinned entries are never evicted");
+                                ui.checkbox(
+                                    &mut strip_trailing_newline,
+                                    "Copy without a trailing newline",
+                                )
+                                .on_hover_text(
+                                    "Stored entries keep their newline; right-click a row to copy it",
+                                );
+                            });
+                            ui.collapsing("🔒 Privacy", |ui| {
+                                ui.checkbox(&mut mask_secrets, "Mask likely secrets")
+                                    .on_hover_text(
+                                        "Hide previews of API keys, tokens and similar until revealed",
+                                    );
+                                ui.add_enabled_ui(mask_secrets, |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut secret_rules.jwt, "JWTs");
+                                        ui.checkbox(&mut secret_rules.api_keys, "API keys");
+                                        ui.checkbox(&mut secret_rules.hex, "Hex strings");
+                                        ui.checkbox(&mut secret_rules.base64, "Base64 strings");
+                                    });
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Clear the clipboard");
+                                    ui.add(
+                                        egui::DragValue::new(&mut wipe_after_secs)
+                                            .range(5..=3600)
+                                            .suffix(" s"),
+                                    );
+                                    ui.label("after copying:");
+                                });
+                                ui.horizontal(|ui| {
+                                    for option in ClipboardWipe::ALL {
+                                        ui.radio_value(&mut clipboard_wipe, option, option.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Mark an entry from its right-click menu. Nothing is cleared if you've copied something else since.");
+
+                                ui.add_space(10.0);
+                                let supported = foreground::is_supported();
+                                ui.add_enabled_ui(supported, |ui| {
+                                    egui::Grid::new("app_filters").num_columns(2).show(ui, |ui| {
+                                        ui.label("Only capture from");
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut capture_allowlist)
+                                                .hint_text("any app"),
+                                        );
+                                        ui.end_row();
+                                        ui.label("Never capture from");
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut capture_denylist)
+                                                .hint_text("e.g. keepassxc, 1password"),
+                                        );
+                                        ui.end_row();
+                                    });
+                                })
+                                .response
+                                .on_hover_text("Comma-separated app names, matched against the focused window")
+                                .on_disabled_hover_text(
+                                    "The focused app can't be detected on this platform, so nothing is filtered",
+                                );
+                                if !supported {
+                                    ui.label(
+                                        RichText::new("App filters need X11 and xprop")
+                                            .small()
+                                            .weak(),
+                                    );
+                                }
+                            });
+                            ui.collapsing("⌨ Hotkeys", |ui| {
+                                ui.checkbox(&mut paste_ring_enabled, "Paste ring")
+                                    .on_hover_text(
+                                        "A global hotkey pastes recent entries one after another, oldest first",
+                                    );
+                                ui.add_enabled_ui(paste_ring_enabled, |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Hotkey");
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut paste_ring_hotkey)
+                                                .desired_width(100.0),
+                                        );
+                                        ui.label("cycling through");
+                                        ui.add(egui::DragValue::new(&mut paste_ring_size).range(2..=20));
+                                        ui.label("entries");
+                                    });
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Slot hotkeys");
+                                    ui.add(egui::DragValue::new(&mut slot_count).range(1..=MAX_SLOTS));
+                                    ui.label("using");
+                                    egui::ComboBox::from_id_salt("slot_keys")
+                                        .selected_text(slot_keys.label())
+                                        .show_ui(ui, |ui| {
+                                            for keys in SlotKeys::ALL {
+                                                ui.selectable_value(&mut slot_keys, keys, keys.label());
+                                            }
+                                        });
+                                })
+                                .response
+                                .on_hover_text("Pinned entries can be bound to a numbered hotkey from their menu. Fewer slots or the numpad avoid clashing with other shortcuts.");
+                                ui.checkbox(&mut auto_paste, "Paste automatically after a hotkey copies");
+                                ui.collapsing("⌨ Keyboard shortcuts", |ui| self.keybindings_ui(ui));
+                            });
+                            ui.collapsing("🪟 Window", |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label("Closing the window:");
+                                    for action in [CloseAction::Quit, CloseAction::Minimize, CloseAction::Hide]
+                                    {
+                                        ui.radio_value(&mut close_action, action, action.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Minimize and Hide keep capturing; use Quit to exit");
+                                ui.add_enabled_ui(close_action == CloseAction::Hide, |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Show window hotkey");
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut show_hotkey).desired_width(100.0),
+                                        );
+                                    });
+                                });
+                                ui.checkbox(&mut panic_enabled, "Panic hotkey").on_hover_text(
+                                    "Hides the window and clears the system clipboard in one keystroke",
+                                );
+                                ui.add_enabled_ui(panic_enabled, |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Hotkey");
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut panic_hotkey).desired_width(100.0),
+                                        );
+                                    });
+                                });
+
+                                ui.add_space(10.0);
+                                let supported = autostart::is_supported();
+                                let response = ui
+                                    .add_enabled(
+                                        supported,
+                                        egui::Checkbox::new(&mut autostart, "Launch on login"),
+                                    )
+                                    .on_disabled_hover_text("Not supported on this platform");
+                                toggle_autostart = response.changed();
+                            });
+                            ui.collapsing("📤 Import and export", |ui| {
+                                if ui
+                                    .button("📥 Import OS History")
+                                    .on_hover_text("Import entries from the system clipboard history")
+                                    .clicked()
+                                {
+                                    import_history = true;
+                                }
+
+                                ui.horizontal(|ui| {
+                                    ui.menu_button("📤 Export…", |ui| {
+                                        ui.label(
+                                            RichText::new(format!("All {} entries", self.entries.len())).weak(),
+                                        );
+                                        for format in ExportFormat::ALL {
+                                            if ui.button(format.label()).clicked() {
+                                                export = Some((format, false));
+                                                ui.close_menu();
+                                            }
+                                        }
+
+                                        // Only the entries currently listed, while searching
+                                        if !self.applied_search.trim().is_empty() || self.session_only {
+                                            let shown = self.filtered_entries().len();
+                                            ui.separator();
+                                            ui.label(RichText::new(format!("{} shown entries", shown)).weak());
+                                            for format in ExportFormat::ALL {
+                                                if ui
+                                                    .add_enabled(shown > 0, egui::Button::new(format.label()))
+                                                    .clicked()
+                                                {
+                                                    export = Some((format, true));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                        }
+                                    });
+                                    if ui
+                                        .button("📥 Import (merge)…")
+                                        .on_hover_text("Add entries from a file to the current history")
+                                        .clicked()
+                                    {
+                                        import_file = Some(false);
+                                    }
+                                    if ui
+                                        .button("📥 Import (replace)…")
+                                        .on_hover_text("Replace the current history with a file")
+                                        .clicked()
+                                    {
+                                        import_file = Some(true);
+                                    }
+                                });
+                                ui.checkbox(&mut preview_imports, "Review entries before merging an import")
+                                    .on_hover_text("List the incoming entries, flagging duplicates, and choose which to add");
+                            });
+                            ui.collapsing("🗑 Trash", |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label("Trash keeps");
+                                    ui.add(egui::DragValue::new(&mut max_trash).range(0..=500));
+                                    ui.label("entries for");
+                                    ui.add(
+                                        egui::DragValue::new(&mut trash_retention_days)
+                                            .range(1..=365)
+                                            .suffix(" days"),
+                                    );
+                                });
+                                if ui
+                                    .button(format!("🗑 Open Trash ({})", self.trash.len()))
+                                    .clicked()
+                                {
+                                    self.show_trash_window = true;
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.label("Delete unpinned entries older than");
+                                    ui.add(
+                                        egui::DragValue::new(&mut self.clear_older_days)
+                                            .range(1..=3650)
+                                            .suffix(" days"),
+                                    );
+                                    if ui.button("🧹 Delete…").clicked() {
+                                        clear_older = true;
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Moves them to the trash after asking; pinned entries are kept");
+                            });
+                            ui.collapsing("💾 Backups", |ui| {
+                                ui.checkbox(&mut auto_backup, "Automatic backups");
+                                ui.add_enabled_ui(auto_backup, |ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Every");
+                                        ui.add(
+                                            egui::DragValue::new(&mut backup_interval_mins)
+                                                .range(5..=1440)
+                                                .suffix(" min"),
+                                        );
+                                        ui.label("keeping");
+                                        ui.add(egui::DragValue::new(&mut max_backups).range(1..=100));
+                                    });
+                                });
+                                if ui.button("💾 Back Up Now").clicked() {
+                                    backup = true;
+                                }
+                                ui.collapsing(format!("Restore from backup ({})", backups.len()), |ui| {
+                                    if backups.is_empty() {
+                                        ui.label(RichText::new("No backups yet").weak());
+                                    }
+                                    egui::ScrollArea::vertical()
+                                        .max_height(150.0)
+                                        .show(ui, |ui| {
+                                            for path in &backups {
+                                                ui.horizontal(|ui| {
+                                                    let name = path
+                                                        .file_name()
+                                                        .map(|n| n.to_string_lossy().into_owned())
+                                                        .unwrap_or_default();
+                                                    ui.label(name);
+                                                    if ui.small_button("Restore").clicked() {
+                                                        restore = Some(path.clone());
+                                                    }
+                                                });
+                                            }
+                                        });
+                                });
+                            });
+                            ui.collapsing("🩺 Storage diagnostics", |ui| {
+                                self.storage_diagnostics(ui);
+                                ui.horizontal(|ui| {
+                                    if ui.button("📂 Open Location").clicked() {
+                                        open_dir = true;
+                                    }
+                                    if ui.button("📋 Copy Path").clicked() {
+                                        copy_dir = true;
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    if ui.button("💾 Save Now").clicked() {
+                                        save_now = true;
+                                    }
+                                    if ui.button("🗄 Back Up Now").clicked() {
+                                        backup = true;
+                                    }
+                                    if ui
+                                        .button("🗜 Compact Now")
+                                        .on_hover_text("Merge duplicate entries, empty expired trash and rewrite the data file")
+                                        .clicked()
+                                    {
+                                        compact = true;
+                                    }
+                                });
+                                ui.checkbox(
+                                    &mut auto_compact,
+                                    format!("Compact automatically every {} days", COMPACT_INTERVAL_DAYS),
+                                );
+                                ui.horizontal(|ui| {
+                                    ui.label("Storage format:");
+                                    for format in StorageFormat::ALL {
+                                        ui.radio_value(&mut storage_format, format, format.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("JSON Lines appends only what changed to data.jsonl and rewrites data.json now and then, which is quicker with a large history. Backups and sync use data.json.");
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut capture_log, "Keep a capture log")
+                                        .on_hover_text("Record what was captured, copied and removed, with a short preview, even after entries are gone. Likely secrets are never written.");
+                                    if ui.button("📄 Open Log").clicked() {
+                                        open_log = true;
+                                    }
+                                });
+                                ui.checkbox(&mut verbose_logging, "Verbose logging")
+                                    .on_hover_text("Log routine details to the terminal, such as failed clipboard reads, to help track down problems");
+                            });
+                        });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("✅ Save").clicked() {
+                            save = true;
+                        }
+                        ui.separator();
+                        if ui
+                            .button("📤 Export settings…")
+                            .on_hover_text("Write the current settings to a file, without any history")
+                            .clicked()
+                        {
+                            export_settings = true;
+                        }
+                        if ui
+                            .button("📥 Import settings…")
+                            .on_hover_text("Replace the settings with ones from a file; the history is kept")
+                            .clicked()
+                        {
+                            import_settings = true;
+                        }
+                    });
+                });
+
+            self.show_settings_window = show;
+            if save {
+                self.settings.max_entries = max_entries;
+                self.settings.full_history = full_history;
+                self.settings.max_total_mb = limit_size.then_some(max_total_mb);
+                self.settings.limit_warning = warn_limit.then_some(limit_warning);
+                self.settings.min_capture_chars = min_capture_chars;
+                self.settings.idle_repaint_secs = idle_repaint_secs;
+                self.settings.max_pinned = limit_pinned.then_some(max_pinned);
+                self.evict_excess();
+                self.settings.duplicate_window_secs =
+                    use_duplicate_window.then_some(duplicate_window_secs);
+                self.settings.count_duplicates = count_duplicates;
+                self.settings.merge_whitespace_changes = merge_whitespace_changes;
+                self.settings.trim_on_capture = trim_on_capture;
+                self.settings.invalid_text = invalid_text;
+                self.settings.merge_similar = merge_similar;
+                self.settings.record_clears = record_clears;
+                self.settings.similarity_threshold = similarity_threshold;
+                self.settings.dedup_includes_pinned = dedup_includes_pinned;
+                self.settings.append_separator = append_separator;
+                self.settings.inline_actions = inline_actions;
+                self.settings.click_action = click_action;
+                self.settings.double_click_to_copy = double_click_to_copy;
+                self.settings.scroll_to_new = scroll_to_new;
+                self.settings.remember_scroll = remember_scroll;
+                self.settings.search_min_chars = search_min_chars;
+                self.settings.search_debounce_ms = search_debounce_ms;
+                self.settings.flash_captures = flash_captures;
+                self.settings.fade_old_entries = fade_old_entries;
+                self.settings.copy_to_primary = copy_to_primary;
+                self.settings.confirm_delete = confirm_delete;
+                self.settings.protect_pinned = protect_pinned;
+                self.settings.auto_pin = auto_pin;
+                self.settings.auto_pin_threshold = auto_pin_threshold;
+                self.settings.strip_trailing_newline = strip_trailing_newline;
+                self.settings.preview_share = preview_share;
+                self.settings.details_layout = details_layout;
+                self.settings.max_expanded_lines = max_expanded_lines;
+                self.settings.accent_color = accent_color;
+                self.settings.appearance = appearance;
+                self.settings.reduced_motion = reduced_motion;
+                self.settings.high_contrast = high_contrast;
+                if status_style == StatusStyle::Line {
+                    // Keep only the newest message for the status line
+                    let start = self.status_messages.len().saturating_sub(1);
+                    self.status_messages.drain(..start);
+                }
+                self.settings.status_style = status_style;
+                self.settings.toast_corner = toast_corner;
+                self.apply_style(ctx);
+                self.settings.time_format = if is_valid_time_format(&time_format) {
+                    time_format
+                } else {
+                    default_time_format()
+                };
+                self.settings.hide_time_when_narrow = hide_time_when_narrow;
+                self.settings.max_trash = max_trash;
+                self.settings.paste_ring_enabled = paste_ring_enabled;
+                self.settings.paste_ring_size = paste_ring_size;
+                self.settings.paste_ring_hotkey = paste_ring_hotkey;
+                self.settings.slot_count = slot_count;
+                self.settings.slot_keys = slot_keys;
+                self.settings.close_action = close_action;
+                self.settings.show_hotkey = show_hotkey;
+                self.settings.panic_enabled = panic_enabled;
+                self.settings.panic_hotkey = panic_hotkey;
+                self.settings.auto_paste = auto_paste;
+                self.settings.mask_secrets = mask_secrets;
+                self.settings.capture_allowlist = split_list(&capture_allowlist);
+                self.settings.capture_denylist = split_list(&capture_denylist);
+                self.settings.secret_rules = secret_rules;
+                self.settings.clipboard_wipe = clipboard_wipe;
+                self.settings.wipe_after_secs = wipe_after_secs;
+                self.apply_hotkeys();
+                self.settings.trash_retention_days = trash_retention_days;
+                self.purge_trash();
+                self.settings.auto_backup = auto_backup;
+                self.settings.backup_interval_mins = backup_interval_mins;
+                self.settings.max_backups = max_backups;
+                self.settings.auto_compact = auto_compact;
+                self.settings.verbose_logging = verbose_logging;
+                self.settings.storage_format = storage_format;
+                self.settings.verify_copies = verify_copies;
+                self.settings.capture_log = capture_log;
+                logging::set_verbose(verbose_logging);
+                self.settings.preview_imports = preview_imports;
+                self.save_data();
+                self.set_status("Settings saved", 2.0);
+            }
+            if toggle_autostart {
+                self.set_autostart(autostart);
+            }
+            if clear_older {
+                let age = chrono::Duration::days(self.clear_older_days as i64);
+                self.request_clear_unpinned(ClearScope::Before(Local::now() - age));
+            }
+            if import_history {
+                self.import_os_history(ctx);
+            }
+            if let Some((format, filtered_only)) = export {
+                self.export_entries(format, filtered_only);
+            }
+            if let Some(replace) = import_file {
+                self.import_entries(replace);
+            }
+            if export_settings {
+                self.export_settings();
+            }
+            if import_settings {
+                self.import_settings(ctx);
+            }
+            if backup {
+                self.report_backup();
+            }
+            if compact {
+                self.report_compact();
+            }
+            if open_log {
+                self.open_capture_log();
+            }
+            if let Some(path) = restore {
+                self.restore_backup(&path);
+            }
+            if save_now {
+                match self.try_save() {
+                    Ok(()) => self.set_status("Data saved", 2.0),
+                    Err(message) => self.set_status(&message, 5.0),
+                }
+            }
+            if open_dir {
+                self.open_clips();
+            }
+            if copy_dir {
+                self.copy_data_dir();
+            }
+        }
+
+        if self.show_trash_window {
+            let mut show = self.show_trash_window;
+            let mut restore = None;
+            let mut purge = None;
+            let mut empty = false;
+
+            egui::Window::new("🗑 Trash")
+                .open(&mut show)
+                .default_size([420.0, 320.0])
+                .show(ctx, |ui| {
+                    if self.trash.is_empty() {
+                        ui.label("The trash is empty.");
+                        return;
+                    }
+
+                    if ui.button("Empty Trash").clicked() {
+                        empty = true;
+                    }
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for entry in &self.trash {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("♻ Restore").clicked() {
+                                    restore = Some(entry.id);
+                                }
+                                if ui
+                                    .small_button("❌")
+                                    .on_hover_text("Delete permanently")
+                                    .clicked()
+                                {
+                                    purge = Some(entry.id);
+                                }
+                                if let Some(deleted) = entry.deleted_at {
+                                    ui.label(
+                                        RichText::new(deleted.format("%Y-%m-%d %H:%M").to_string())
+                                            .weak(),
+                                    );
+                                }
+                                ui.label(entry.preview(self.settings.preview_chars));
+                            });
+                        }
+                    });
+                });
+
+            self.show_trash_window = show;
+            if let Some(id) = restore {
+                self.restore_from_trash(id);
+            }
+            if let Some(id) = purge {
+                self.purge_from_trash(Some(id));
+            }
+            if empty {
+                self.purge_from_trash(None);
+            }
+        }
+
+        if let Some(mut quick_open) = self.quick_open.take() {
+            let mask = self.settings.mask_secrets;
+            let (open, chosen) = quick_open.show(ctx, &self.entries, |entry| {
+                mask && !self.revealed.contains(&entry.id)
+                    && entry.looks_like_secret(&self.settings.secret_rules)
+            });
+            if open {
+                self.quick_open = Some(quick_open);
+            }
+            if let Some(id) = chosen {
+                if let Some(index) = self.entry_index(id) {
+                    let content = self.entries[index].content.clone();
+                    self.copy_to_clipboard(Some(id), &content);
+                }
+            }
+        }
+
+        if let Some(mut palette) = self.palette.take() {
+            let commands = self.palette_commands();
+            let labels: Vec<String> = commands.iter().map(|(_, label)| label.clone()).collect();
+            let (open, chosen) = palette.show(ctx, &labels);
+            if open {
+                self.palette = Some(palette);
+            }
+            if let Some(index) = chosen {
+                self.run_command(ctx, commands[index].0);
+            }
+        }
+
+        if let Some(viewer) = &mut self.viewer {
+            let response = viewer.show(ctx);
+            let id = viewer.id;
+            if response.copy {
+                if let Some(index) = self.entry_index(id) {
+                    let content = self.entries[index].content.clone();
+                    self.copy_to_clipboard(Some(id), &content);
+                }
+            }
+            if let Some(message) = response.error {
+                self.set_status(&message, 3.0);
+            }
+            if !response.open {
+                self.viewer = None;
+            }
+        }
+
+        if let Some(compare) = &self.compare {
+            if !compare.show(ctx) {
+                self.compare = None;
+            }
+        }
+
+        // First-run setup
+        if let Some(setup) = &mut self.setup {
+            match setup.show(ctx) {
+                Some(true) => {
+                    if let Some(setup) = self.setup.take() {
+                        self.apply_setup(ctx, setup);
+                    }
+                }
+                Some(false) => {
+                    self.setup = None;
+                    self.save_data();
+                    self.set_status("Using the default settings; change them in Settings", 3.0);
+                }
+                None => {}
+            }
+        }
 
-        if self.show_settings_window {
-            let mut show = self.show_settings_window;
-            let mut max_entries = self.max_entries;
-            let mut save = false;
+        // Review of a merge import
+        if let Some(preview) = &mut self.import_preview {
+            match preview.show(ctx, self.settings.preview_chars) {
+                Some(true) => {
+                    if let Some(preview) = self.import_preview.take() {
+                        self.merge_entries(preview.into_selected());
+                    }
+                }
+                Some(false) => {
+                    self.import_preview = None;
+                    self.set_status("Import cancelled", 2.0);
+                }
+                None => {}
+            }
+        }
 
-            egui::Window::new("⚙ Settings")
-                .open(&mut show)
+        if let Some(incoming) = &self.pending_import {
+            let incoming_count = incoming.len();
+            let pinned = self.entries.iter().filter(|e| e.pinned).count();
+            let mut confirm = false;
+            let mut cancel = false;
+            let mut backup = false;
+
+            egui::Window::new("⚠ Replace history?")
                 .resizable(false)
                 .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ctx, |ui| {
-                    ui.label("Set max number of entries to retain:");
-                    ui.add(
-                        egui::DragValue::new(&mut max_entries)
-                            .range(10..=500)
-                            .speed(1),
-                    );
+                    ui.label(format!(
+                        "Importing will discard all {} current entries ({} pinned) and replace them with {} imported entries.",
+                        self.entries.len(),
+                        pinned,
+                        incoming_count
+                    ));
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("💾 Back Up First")
+                            .on_hover_text("Save a backup of the whole history, including local-only entries")
+                            .clicked()
+                        {
+                            backup = true;
+                        }
+                        if ui.button("⚠ Replace").clicked() {
+                            confirm = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+
+            if backup {
+                match self.backup_now() {
+                    Ok(path) => self.set_status(&format!("Backed up to {}", path.display()), 3.0),
+                    Err(message) => self.set_status(&message, 3.0),
+                }
+            }
+            if confirm {
+                if let Some(incoming) = self.pending_import.take() {
+                    self.replace_entries(incoming);
+                }
+            } else if cancel {
+                self.pending_import = None;
+                self.set_status("Import cancelled", 2.0);
+            }
+        }
+
+        // Confirmation before deleting a single entry, dropped if the entry
+        // disappeared in the meantime
+        let pending = self
+            .pending_delete
+            .and_then(|id| Some((id, self.entry_index(id)?)));
+        if pending.is_none() {
+            self.pending_delete = None;
+        }
+        if let Some((id, index)) = pending {
+            let preview = self.entries[index].preview(self.settings.preview_chars);
+            let mut confirm = false;
+            let mut cancel = false;
 
+            egui::Window::new("🗑 Delete entry?")
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("Move \"{}\" to the trash?", preview));
                     ui.add_space(10.0);
-                    if ui.button("✅ Save").clicked() {
-                        save = true;
-                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("🗑 Delete").clicked() {
+                            confirm = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
                 });
 
-            self.show_settings_window = show;
-            if save {
-                self.max_entries = max_entries;
-                self.save_data();
-                self.set_status("Settings saved", 2.0);
+            if confirm {
+                self.remove_entry(id);
+            }
+            if confirm || cancel {
+                self.pending_delete = None;
+            }
+        }
+
+        // Confirmation before clearing, shared by the button and the shortcut
+        if let Some(scope) = self.pending_clear {
+            let count = self.entries.iter().filter(|e| scope.includes(e)).count();
+            let message = match scope {
+                ClearScope::All => format!(
+                    "Move {} unpinned entries to the trash? Pinned entries are kept.",
+                    count
+                ),
+                ClearScope::Before(cutoff) => format!(
+                    "Move {} unpinned entries captured before {} to the trash? Pinned entries are kept.",
+                    count,
+                    cutoff.format("%Y-%m-%d %H:%M")
+                ),
+            };
+            let mut confirm = false;
+            let mut cancel = false;
+
+            egui::Window::new("🧹 Clear unpinned?")
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(message);
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("🧹 Clear").clicked() {
+                            confirm = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+
+            if confirm {
+                self.clear_unpinned(scope);
+            }
+            if confirm || cancel {
+                self.pending_clear = None;
+            }
+        }
+
+        // Label and format for "Copy as link", dropped if the entry is gone
+        if let Some(prompt) = &mut self.link_prompt {
+            let url = self
+                .entries
+                .iter()
+                .find(|e| e.id == prompt.id)
+                .map(|e| e.content.trim().to_owned());
+            let mut copy = None;
+            let mut cancel = url.is_none();
+
+            if let Some(url) = &url {
+                egui::Window::new("🔗 Copy as link")
+                    .resizable(false)
+                    .collapsible(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(RichText::new(url).small().weak());
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut prompt.label)
+                                .hint_text("Link text (defaults to the URL)"),
+                        );
+                        response.request_focus();
+                        ui.horizontal(|ui| {
+                            for format in [LinkFormat::Markdown, LinkFormat::Html] {
+                                ui.radio_value(&mut prompt.format, format, format.label());
+                            }
+                        });
+                        let label = if prompt.label.trim().is_empty() {
+                            url.as_str()
+                        } else {
+                            prompt.label.trim()
+                        };
+                        let link = prompt.format.format(label, url);
+                        ui.label(RichText::new(&link).monospace().small());
+
+                        ui.add_space(10.0);
+                        let enter =
+                            response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        ui.horizontal(|ui| {
+                            if ui.button("📋 Copy").clicked() || enter {
+                                copy = Some(link);
+                            }
+                            if ui.button("Cancel").clicked() {
+                                cancel = true;
+                            }
+                        });
+                    });
+            }
+
+            if let Some(link) = &copy {
+                if self.copy_to_clipboard(None, link) {
+                    self.set_status("Copied link", 2.0);
+                }
+            }
+            if copy.is_some() || cancel {
+                self.link_prompt = None;
             }
         }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        self.save_data();
+        // The window is closing, so a failed save can't go in the status bar.
+        // Write an emergency copy instead and tell the user where it went.
+        // Leave a full snapshot rather than a journal behind.
+        self.journal = None;
+        if let Err(message) = self.try_save() {
+            log::error!("{}", message);
+            let description = match self.emergency_save() {
+                Ok(path) => format!(
+                    "{}\n\nYour clipboard history was saved to {} instead.",
+                    message,
+                    path.display()
+                ),
+                Err(emergency) => format!(
+                    "{}\n\nAn emergency copy couldn't be written either: {}",
+                    message, emergency
+                ),
+            };
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Klippy couldn't save its data")
+                .set_description(description)
+                .show();
+            return;
+        }
+        if self.settings.auto_backup && self.exit_backup_due() {
+            if let Err(message) = self.backup_now() {
+                log::error!("{}", message);
+            }
+        }
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
+    logging::init();
+
+    // Check the arguments before touching any data
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != PORTABLE_FLAG && arg != demo::DEMO_FLAG)
+        .collect();
+    let command = cli::parse(&args).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    });
+
+    // Start every demo from the same sample data
+    if demo::is_demo() {
+        if let Err(message) = demo::seed() {
+            log::error!("{}", message);
+            std::process::exit(1);
+        }
+    }
+
+    // Command-line actions run without opening the window
+    if let Some(command) = command {
+        if let Err(message) = cli::run(command) {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let app = ClipboardManager::new();
+
+    // Restore the last window geometry if it's still on a connected monitor,
+    // otherwise center on the primary display
+    let (geometry, default_size) = if app.mini_mode {
+        (app.mini_geometry, mini::DEFAULT_SIZE)
+    } else {
+        (app.window_geometry, DEFAULT_WINDOW_SIZE)
+    };
+    let restored = geometry.filter(WindowGeometry::is_on_screen);
+    let viewport = match restored {
+        Some(geometry) => ViewportBuilder::default()
+            .with_inner_size([geometry.width, geometry.height])
+            .with_position([geometry.x, geometry.y]),
+        None => ViewportBuilder::default().with_inner_size(default_size),
+    };
+    let viewport = if app.mini_mode {
+        viewport.with_always_on_top()
+    } else {
+        viewport
+    };
+
     let options = eframe::NativeOptions {
-        viewport: ViewportBuilder::default().with_inner_size([800.0, 600.0]),
+        viewport,
+        centered: restored.is_none(),
         ..Default::default()
     };
 
@@ -480,9 +7511,628 @@ fn main() -> Result<(), eframe::Error> {
         "Klippy",
         options,
         Box::new(|cc: &CreationContext| {
-            let app = Box::new(ClipboardManager::new());
-            cc.egui_ctx.request_repaint_after(Duration::from_secs(1));
+            let mut app = Box::new(app);
+            app.start_hotkeys(&cc.egui_ctx);
+            app.system_preferences = SystemPreferences::detect();
+            app.apply_style(&cc.egui_ctx);
+            app.start_watching(&cc.egui_ctx);
+            app.update_jump_list();
             Ok(app)
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A manager with no data file, holding entries with the given contents,
+    /// newest first
+    fn manager(contents: &[&str]) -> ClipboardManager {
+        let mut app = ClipboardManager::default();
+        for content in contents.iter().rev() {
+            app.add_entry((*content).to_owned());
+        }
+        app
+    }
+
+    fn contents(app: &ClipboardManager) -> Vec<&str> {
+        app.entries.iter().map(|e| e.content.as_str()).collect()
+    }
+
+    /// Load a data file's contents the way startup does
+    fn load(json: &str) -> ClipboardManager {
+        let mut app = ClipboardManager::default();
+        app.apply_loaded(serde_json::from_str(json).unwrap());
+        app
+    }
+
+    #[test]
+    fn loading_orders_mixed_pins_by_capture_time() {
+        // Older versions wrote pinned entries first
+        let app = load(
+            r#"{"entries": [
+                {"id": 2, "content": "pinned old", "timestamp": "2024-01-01T10:00:00Z", "pinned": true},
+                {"id": 5, "content": "pinned new", "timestamp": "2024-01-03T10:00:00Z", "pinned": true},
+                {"id": 4, "content": "newest", "timestamp": "2024-01-04T10:00:00Z", "pinned": false},
+                {"id": 1, "content": "oldest", "timestamp": "2023-12-31T10:00:00Z", "pinned": false},
+                {"id": 3, "content": "middle", "timestamp": "2024-01-02T10:00:00Z", "pinned": false}
+            ], "max_entries": 50, "next_id": 6}"#,
+        );
+        assert_eq!(
+            contents(&app),
+            ["newest", "pinned new", "middle", "pinned old", "oldest"]
+        );
+        assert_eq!(app.total_bytes, 6 + 10 + 6 + 10 + 6);
+    }
+
+    #[test]
+    fn loading_breaks_timestamp_ties_by_id() {
+        let app = load(
+            r#"{"entries": [
+                {"id": 1, "content": "first", "timestamp": "2024-01-01T10:00:00Z", "pinned": false},
+                {"id": 3, "content": "third", "timestamp": "2024-01-01T10:00:00Z", "pinned": true},
+                {"id": 2, "content": "second", "timestamp": "2024-01-01T10:00:00Z", "pinned": false}
+            ], "max_entries": 50, "next_id": 4}"#,
+        );
+        assert_eq!(contents(&app), ["third", "second", "first"]);
+    }
+
+    #[test]
+    fn captures_after_loading_go_first() {
+        // No ids or next id, as in data files from before ids existed
+        let mut app = load(
+            r#"{"entries": [
+                {"content": "pinned", "timestamp": "2024-01-02T10:00:00Z", "pinned": true},
+                {"content": "plain", "timestamp": "2024-01-01T10:00:00Z", "pinned": false}
+            ], "max_entries": 50}"#,
+        );
+        app.add_entry("captured".to_owned());
+        assert_eq!(contents(&app), ["captured", "pinned", "plain"]);
+        let mut ids: Vec<u64> = app.entries.iter().map(|e| e.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+    }
+
+    /// A full history of `max` entries, every one pinned
+    fn all_pinned(max: usize, full_history: FullHistory) -> ClipboardManager {
+        let mut app = ClipboardManager::default();
+        app.settings.max_entries = max;
+        app.settings.full_history = full_history;
+        for i in 0..max {
+            app.add_entry(format!("pinned {}", i));
+            let id = app.entries[0].id;
+            app.toggle_pin(id);
+        }
+        app
+    }
+
+    #[test]
+    fn all_pinned_history_blocks_new_captures() {
+        let mut app = all_pinned(3, FullHistory::Block);
+        assert!(app.history_blocked());
+        app.add_entry("new".to_owned());
+        assert_eq!(app.entries.len(), 3);
+        assert!(app.entries.iter().all(|e| e.pinned));
+        assert!(app
+            .status_messages
+            .last()
+            .unwrap()
+            .0
+            .starts_with("Not captured"));
+        app.add_clear_marker();
+        assert_eq!(app.entries.len(), 3);
+
+        // Unpinning one makes room again, and the limit holds
+        let id = app.entries[0].id;
+        app.toggle_pin(id);
+        assert!(!app.history_blocked());
+        app.add_entry("new".to_owned());
+        assert_eq!(contents(&app), ["new", "pinned 1", "pinned 0"]);
+    }
+
+    #[test]
+    fn all_pinned_history_grows_past_the_limit() {
+        let mut app = all_pinned(3, FullHistory::Grow);
+        assert!(!app.history_blocked());
+        app.add_entry("new 1".to_owned());
+        app.add_entry("new 2".to_owned());
+        assert_eq!(app.entries.len(), 5);
+        assert_eq!(contents(&app)[..2], ["new 2", "new 1"]);
+
+        // Once a pin is dropped the limit applies again, oldest unpinned first
+        let id = app.entries[2].id;
+        app.toggle_pin(id);
+        app.add_entry("new 3".to_owned());
+        assert_eq!(contents(&app), ["new 3", "pinned 1", "pinned 0"]);
+    }
+
+    fn messages(timers: &[f32]) -> Vec<(String, f32)> {
+        timers
+            .iter()
+            .enumerate()
+            .map(|(i, &timer)| (i.to_string(), timer))
+            .collect()
+    }
+
+    #[test]
+    fn first_status_frame_doesnt_age_messages() {
+        // egui's clock can already be well past zero on the first frame
+        let mut set_at_startup = messages(&[3.0, 8.0]);
+        age_status_messages(&mut set_at_startup, None, 250.0);
+        assert_eq!(set_at_startup, messages(&[3.0, 8.0]));
+    }
+
+    #[test]
+    fn status_messages_age_and_expire() {
+        let mut timers = messages(&[1.0, 2.0]);
+        age_status_messages(&mut timers, Some(10.0), 10.5);
+        assert_eq!(timers, messages(&[0.5, 1.5]));
+        age_status_messages(&mut timers, Some(10.5), 11.0);
+        assert_eq!(timers, vec![("1".to_owned(), 1.0)]);
+    }
+
+    #[test]
+    fn status_clock_going_backwards_doesnt_age() {
+        let mut timers = messages(&[2.0]);
+        age_status_messages(&mut timers, Some(10.0), 4.0);
+        assert_eq!(timers, messages(&[2.0]));
+    }
+
+    #[test]
+    fn formats_are_listed_after_capture() {
+        let mut app = ClipboardManager::default();
+        app.capture_new("copied".to_owned(), false, &egui::Context::default());
+        let id = app.entries[0].id;
+        if !formats::is_supported() {
+            assert!(app.format_listings.is_empty());
+            return;
+        }
+        assert_eq!(app.format_listings.len(), 1);
+        assert_eq!(app.format_listings[0].id, id);
+
+        let started = Instant::now();
+        while !app.format_listings.is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(5), "not listed");
+            std::thread::sleep(Duration::from_millis(5));
+            app.finish_format_listings();
+        }
+        assert_eq!(contents(&app), ["copied"]);
+    }
+
+    #[test]
+    fn lossy_captures_are_marked() {
+        let mut app = ClipboardManager::default();
+        let (text, lossy) = formats::decode(b"bad \xff bytes".to_vec());
+        let ctx = egui::Context::default();
+        app.capture_new(text, lossy, &ctx);
+        app.capture_new("good".to_owned(), false, &ctx);
+        assert_eq!(contents(&app), ["good", "bad � bytes"]);
+        assert!(!app.entries[0].lossy);
+        assert!(app.entries[1].lossy);
+    }
+
+    /// A manager that merges whitespace-only changes
+    fn merging() -> ClipboardManager {
+        let mut app = ClipboardManager::default();
+        app.settings.merge_whitespace_changes = true;
+        app
+    }
+
+    fn capture(app: &mut ClipboardManager, contents: &[&str]) {
+        for content in contents {
+            app.add_entry((*content).to_owned());
+        }
+    }
+
+    #[test]
+    fn surrounding_whitespace_changes_update_the_latest_entry() {
+        let mut app = merging();
+        capture(&mut app, &["foo", "foo  ", "\tfoo\n", " foo "]);
+        assert_eq!(contents(&app), [" foo "]);
+        assert_eq!(app.total_bytes, 5);
+    }
+
+    #[test]
+    fn inner_whitespace_changes_are_new_entries() {
+        let mut app = merging();
+        capture(&mut app, &["foo bar", "foo  bar", "foo\nbar"]);
+        assert_eq!(contents(&app), ["foo\nbar", "foo  bar", "foo bar"]);
+    }
+
+    #[test]
+    fn only_the_latest_entry_is_updated() {
+        let mut app = merging();
+        capture(&mut app, &["foo", "bar", "foo "]);
+        assert_eq!(contents(&app), ["foo ", "bar", "foo"]);
+    }
+
+    #[test]
+    fn whitespace_changes_are_kept_apart_when_not_merging() {
+        let mut app = ClipboardManager::default();
+        capture(&mut app, &["foo", "foo "]);
+        assert_eq!(contents(&app), ["foo ", "foo"]);
+    }
+
+    #[test]
+    fn merging_with_trimming_updates_the_untrimmed_text() {
+        let mut app = merging();
+        app.settings.trim_on_capture = true;
+        capture(&mut app, &["foo ", "  foo\n"]);
+        assert_eq!(contents(&app), ["foo"]);
+        assert_eq!(app.entries[0].raw.as_deref(), Some("  foo\n"));
+
+        capture(&mut app, &["foo"]);
+        assert_eq!(contents(&app), ["foo"]);
+        assert_eq!(app.entries[0].raw, None);
+    }
+
+    #[test]
+    fn merging_follows_the_pinned_duplicate_setting() {
+        let mut app = merging();
+        capture(&mut app, &["foo"]);
+        let id = app.entries[0].id;
+        app.toggle_pin(id);
+
+        app.settings.dedup_includes_pinned = false;
+        capture(&mut app, &["foo "]);
+        assert_eq!(contents(&app), ["foo ", "foo"]);
+        assert!(app.entries[1].pinned);
+
+        let mut app = merging();
+        capture(&mut app, &["foo"]);
+        let id = app.entries[0].id;
+        app.toggle_pin(id);
+        capture(&mut app, &["foo "]);
+        assert_eq!(contents(&app), ["foo "]);
+        assert!(app.entries[0].pinned);
+    }
+
+    #[test]
+    fn merging_counts_recaptures_when_counting_duplicates() {
+        let mut app = merging();
+        app.settings.count_duplicates = true;
+        capture(&mut app, &["foo", "foo ", " foo"]);
+        assert_eq!(contents(&app), [" foo"]);
+        assert_eq!(app.entries[0].occurrences, 3);
+    }
+
+    #[test]
+    fn locked_entries_are_not_merged_into() {
+        let mut app = merging();
+        capture(&mut app, &["foo"]);
+        let id = app.entries[0].id;
+        app.toggle_lock(id);
+        capture(&mut app, &["foo "]);
+        assert_eq!(contents(&app), ["foo ", "foo"]);
+    }
+
+    #[test]
+    fn merged_content_is_scanned_again() {
+        let mut app = merging();
+        capture(&mut app, &["https://example.com\n"]);
+        assert_eq!(app.entries[0].line_ending(), Some(LineEnding::Lf));
+        assert!(app.entries[0].kind() == ContentKind::Url);
+
+        capture(&mut app, &["https://example.com\r\n"]);
+        assert_eq!(contents(&app), ["https://example.com\r\n"]);
+        assert_eq!(app.entries[0].line_ending(), Some(LineEnding::Crlf));
+    }
+
+    #[test]
+    fn cleaned_copy_needs_no_cleanup() {
+        let mut app = manager(&["\u{201c}quoted\u{201d} \u{2014} text"]);
+        assert!(app.entries[0].needs_cleanup());
+
+        app.add_cleaned(app.entries[0].id);
+        assert_eq!(contents(&app)[0], "\"quoted\" - text");
+        assert!(!app.entries[0].needs_cleanup());
+        assert!(app.entries[1].needs_cleanup());
+    }
+
+    #[test]
+    fn split_view_is_rebuilt_only_when_the_content_changes() {
+        let mut app = merging();
+        capture(&mut app, &["foo", "bar"]);
+        let viewer = ContentViewer::new(&app.entries[0]);
+        assert!(viewer.is_for(&app.entries[0]));
+        assert!(!viewer.is_for(&app.entries[1]));
+
+        app.toggle_pin(app.entries[0].id);
+        assert!(viewer.is_for(&app.entries[0]));
+
+        capture(&mut app, &[" bar "]);
+        assert!(!viewer.is_for(&app.entries[0]));
+    }
+
+    #[test]
+    fn loaded_entries_get_distinct_versions() {
+        let app = load(
+            r#"{"max_entries": 50, "entries": [
+                {"id": 1, "content": "a", "timestamp": "2024-01-01T00:00:00+00:00", "pinned": false},
+                {"id": 2, "content": "b", "timestamp": "2024-01-01T00:00:00+00:00", "pinned": false}
+            ]}"#,
+        );
+        assert_ne!(app.entries[0].version, app.entries[1].version);
+    }
+
+    #[test]
+    fn exit_backup_waits_for_the_interval() {
+        let dir = std::env::temp_dir().join(format!("klippy-exit-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut app = manager(&["entry"]);
+        app.save_path = Some(dir.join("data.json"));
+        app.settings.backup_interval_mins = 60;
+        assert!(app.exit_backup_due());
+
+        app.backup_now().unwrap();
+        assert!(!app.exit_backup_due());
+        app.settings.backup_interval_mins = 0;
+        assert!(app.exit_backup_due());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn secret_check_follows_the_rules() {
+        let entry = ClipboardEntry::new(1, "0123456789abcdef0123456789abcdef".to_owned());
+        let mut rules = SecretRules {
+            hex: true,
+            ..SecretRules::default()
+        };
+        assert!(entry.looks_like_secret(&rules));
+
+        rules.hex = false;
+        assert!(!entry.looks_like_secret(&rules));
+    }
+
+    #[test]
+    fn copies_only_count_for_the_copied_entry() {
+        let mut app = ClipboardManager::default();
+        let mut one_shot = ClipboardEntry::new(1, "same".to_owned());
+        one_shot.one_shot = true;
+        app.entries = vec![one_shot, ClipboardEntry::new(2, "same".to_owned())];
+        app.total_bytes = 8;
+
+        // Text that merely matches an entry, e.g. the scratchpad, isn't a use
+        app.record_copy(None);
+        assert_eq!(app.entries.len(), 2);
+        assert_eq!(app.entries[1].use_count, 0);
+
+        app.record_copy(app.entry_index(2));
+        assert_eq!(app.entries.len(), 2);
+        assert_eq!(app.entries[0].use_count, 0);
+        assert_eq!(app.entries[1].use_count, 1);
+        assert!(app.entries[1].last_used.is_some());
+
+        app.record_copy(app.entry_index(1));
+        assert_eq!(app.entries.len(), 1);
+        assert_eq!(app.entries[0].id, 2);
+        assert_eq!(app.total_bytes, 4);
+    }
+
+    #[test]
+    fn panic_closes_everything_showing_content() {
+        let mut app = manager(&["0123456789abcdef0123456789abcdef", "notes"]);
+        let id = app.entries[0].id;
+        app.revealed.insert(id);
+        app.expanded.insert(id);
+        app.selected = Some(id);
+        app.show_trash_window = true;
+        app.viewer = Some(ContentViewer::new(&app.entries[0]));
+        app.split_viewer = Some(ContentViewer::new(&app.entries[1]));
+
+        app.panic(&egui::Context::default());
+        assert!(app.viewer.is_none() && app.split_viewer.is_none());
+        assert!(app.selected.is_none() && !app.show_trash_window);
+        assert!(app.revealed.is_empty() && app.expanded.is_empty());
+    }
+
+    fn duplicated(contents: &[(&str, bool)]) -> ClipboardManager {
+        let mut app = ClipboardManager::default();
+        for (id, &(content, pinned)) in contents.iter().enumerate() {
+            let mut entry = ClipboardEntry::new(id as u64 + 1, content.to_owned());
+            entry.pinned = pinned;
+            app.entries.push(entry);
+        }
+        app.next_id = contents.len() as u64 + 1;
+        app.recompute_total_bytes();
+        app
+    }
+
+    #[test]
+    fn compact_follows_the_duplicate_rules() {
+        let mut app = duplicated(&[("text", false), ("text", true), ("text", false)]);
+        app.settings.dedup_includes_pinned = false;
+        app.compact().unwrap();
+        let pinned: Vec<_> = app.entries.iter().map(|e| e.pinned).collect();
+        assert_eq!(pinned, [false, true]);
+
+        let mut app = duplicated(&[("text", false), ("text", true), ("text", false)]);
+        app.settings.dedup_includes_pinned = true;
+        app.compact().unwrap();
+        assert_eq!(app.entries.len(), 1);
+        assert!(app.entries[0].pinned);
+    }
+
+    #[test]
+    fn compact_keeps_duplicated_entries() {
+        let mut app = duplicated(&[("text", false)]);
+        app.duplicate_entry(1);
+        app.compact().unwrap();
+        assert_eq!(contents(&app), ["text", "text"]);
+        assert!(app.entries[0].duplicate);
+    }
+
+    #[test]
+    fn compact_keeps_the_higher_counts() {
+        let mut app = duplicated(&[("text", false), ("text", false)]);
+        app.entries[0].use_count = 2;
+        app.entries[1].use_count = 5;
+        app.entries[1].revisions = 3;
+        app.entries[0].occurrences = 2;
+        app.entries[1].occurrences = 4;
+        app.settings.count_duplicates = false;
+        app.compact().unwrap();
+        assert_eq!(app.entries.len(), 1);
+        let entry = &app.entries[0];
+        assert_eq!((entry.id, entry.use_count, entry.revisions), (1, 5, 3));
+        assert_eq!(entry.occurrences, 4);
+
+        let mut app = duplicated(&[("text", false), ("text", false)]);
+        app.entries[0].occurrences = 2;
+        app.entries[1].occurrences = 4;
+        app.settings.count_duplicates = true;
+        app.compact().unwrap();
+        assert_eq!(app.entries[0].occurrences, 6);
+    }
+
+    #[test]
+    fn compact_only_drops_expired_trash() {
+        let mut app = duplicated(&[("text", false)]);
+        let mut recent = ClipboardEntry::new(10, "text".to_owned());
+        recent.deleted_at = Some(Local::now());
+        let mut expired = ClipboardEntry::new(11, "old".to_owned());
+        expired.deleted_at = Some(Local::now() - chrono::Duration::days(400));
+        app.trash = vec![recent, expired];
+
+        app.compact().unwrap();
+        let trashed: Vec<u64> = app.trash.iter().map(|e| e.id).collect();
+        assert_eq!(trashed, [10]);
+    }
+
+    #[test]
+    fn masked_entries_open_in_the_viewer_once_revealed() {
+        let mut app = manager(&["0123456789abcdef0123456789abcdef"]);
+        app.settings.mask_secrets = true;
+        let id = app.entries[0].id;
+
+        app.view_entry(id);
+        assert!(app.viewer.is_none());
+
+        app.revealed.insert(id);
+        app.view_entry(id);
+        assert!(app.viewer.is_some());
+    }
+
+    fn history(items: &[(&str, Option<i64>)]) -> Vec<os_history::HistoryItem> {
+        items
+            .iter()
+            .map(|&(content, minutes_ago)| os_history::HistoryItem {
+                content: content.to_owned(),
+                copied_at: minutes_ago.map(|m| Local::now() - chrono::Duration::minutes(m)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn os_history_without_times_keeps_its_order() {
+        let mut app = manager(&["existing"]);
+        let items = history(&[("newest", None), ("middle", None), ("oldest", None)]);
+        app.merge_entries(history_entries(items, Local::now()));
+        assert_eq!(contents(&app), ["newest", "middle", "oldest", "existing"]);
+    }
+
+    #[test]
+    fn os_history_times_place_entries_among_existing_ones() {
+        let mut app = manager(&["existing"]);
+        let items = history(&[("recent", None), ("old", Some(60)), ("existing", Some(90))]);
+        let imported = app.merge_entries(history_entries(items, Local::now()));
+        assert_eq!(imported, 2);
+        assert_eq!(contents(&app), ["recent", "existing", "old"]);
+    }
+
+    #[test]
+    fn restoring_brings_back_the_entry_and_its_details() {
+        let mut app = manager(&["keep", "restore me"]);
+        let id = app.entries[1].id;
+        app.toggle_pin(id);
+        app.entries[1].use_count = 4;
+        app.remove_entry(id);
+        app.add_entry("newer".to_owned());
+
+        app.restore_from_trash(id);
+        assert_eq!(contents(&app), ["restore me", "newer", "keep"]);
+        let restored = &app.entries[0];
+        assert_eq!(restored.id, id);
+        assert!(restored.pinned);
+        assert_eq!(restored.use_count, 4);
+        assert!(restored.deleted_at.is_none());
+        assert!(app.trash.is_empty());
+    }
+
+    #[test]
+    fn restoring_skips_capture_merging() {
+        // A capture would be merged into the latest entry or the one before
+        let mut app = manager(&["text", "text "]);
+        app.settings.merge_whitespace_changes = true;
+        app.settings.merge_similar = true;
+        let id = app.entries[1].id;
+        app.remove_entry(id);
+        app.restore_from_trash(id);
+        assert_eq!(contents(&app), ["text ", "text"]);
+        assert!(app.trash.is_empty());
+    }
+
+    #[test]
+    fn restoring_leaves_the_entry_in_the_trash_when_it_cant() {
+        let mut app = manager(&["dup"]);
+        let id = app.entries[0].id;
+        app.remove_entry(id);
+        app.add_entry("dup".to_owned());
+        app.restore_from_trash(id);
+        assert_eq!(app.trash.len(), 1);
+        assert_eq!(contents(&app), ["dup"]);
+
+        let mut app = all_pinned(2, FullHistory::Block);
+        app.trash
+            .push(ClipboardEntry::new(99, "trashed".to_owned()));
+        app.restore_from_trash(99);
+        assert_eq!(app.trash.len(), 1);
+        assert_eq!(app.entries.len(), 2);
+        assert!(app.entries.iter().all(|e| e.id != 99));
+    }
+
+    #[test]
+    fn deleting_mid_loop_removes_the_intended_ids() {
+        let mut app = manager(&["a", "drop b", "drop c", "d", "drop e"]);
+        let ids: Vec<u64> = app.entries.iter().map(|e| e.id).collect();
+        for id in ids {
+            let index = app.entry_index(id).unwrap();
+            if app.entries[index].content.starts_with("drop") {
+                app.remove_entry(id);
+            }
+        }
+        assert_eq!(contents(&app), ["a", "d"]);
+        assert_eq!(app.trash.len(), 3);
+    }
+
+    #[test]
+    fn actions_follow_the_id_after_a_reorder() {
+        let mut app = manager(&["a", "b", "c"]);
+        let b = app.entries[1].id;
+        let c = app.entries[2].id;
+
+        // A capture mid-loop shifts every index down by one
+        app.add_entry("new".to_owned());
+        app.toggle_pin(b);
+        app.remove_entry(c);
+
+        assert_eq!(contents(&app), ["new", "a", "b"]);
+        assert!(app.entries[2].pinned);
+        assert!(app.entries.iter().filter(|e| e.pinned).all(|e| e.id == b));
+        assert_eq!(app.trash[0].id, c);
+    }
+
+    #[test]
+    fn stale_ids_do_nothing() {
+        let mut app = manager(&["a", "b"]);
+        let a = app.entries[0].id;
+        app.remove_entry(a);
+        app.remove_entry(a);
+        app.toggle_pin(a);
+        assert_eq!(contents(&app), ["b"]);
+        assert!(!app.entries[0].pinned);
+        assert_eq!(app.entry_index(a), None);
+    }
+}
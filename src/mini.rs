@@ -0,0 +1,77 @@
+//! Mini mode
+//!
+//! A small always-on-top strip that shows only the newest entries as chips,
+//! for keeping Klippy beside other windows while working. Clicking a chip
+//! copies it. The strip keeps its own window position and size, separate
+//! from the full window's.
+use crate::ClipboardEntry;
+use egui::{RichText, Ui};
+
+/// Window size used the first time mini mode is opened, in points
+pub const DEFAULT_SIZE: [f32; 2] = [480.0, 96.0];
+/// Most entries shown as chips
+const CHIP_COUNT: usize = 8;
+/// Characters of an entry shown on its chip
+const CHIP_CHARS: usize = 24;
+/// Characters of an entry shown when hovering its chip
+const HOVER_CHARS: usize = 200;
+
+/// What the user did in the strip
+pub enum Action {
+    /// Copy the entry with this id
+    Copy(u64),
+    /// Go back to the full window
+    Exit,
+}
+
+/// Show the newest entries as chips, with a button to leave mini mode
+///
+/// Entries for which `masked` returns true show a placeholder instead of
+/// their content.
+pub fn show(
+    ui: &mut Ui,
+    entries: &[ClipboardEntry],
+    masked: impl Fn(&ClipboardEntry) -> bool,
+) -> Option<Action> {
+    let mut action = None;
+    ui.horizontal_wrapped(|ui| {
+        if ui
+            .button("⛶")
+            .on_hover_text("Back to the full window")
+            .clicked()
+        {
+            action = Some(Action::Exit);
+        }
+        ui.separator();
+
+        let chips = entries.iter().filter(|e| !e.cleared).take(CHIP_COUNT);
+        let mut empty = true;
+        for entry in chips {
+            empty = false;
+            let (label, hover) = if masked(entry) {
+                (
+                    "••••••".to_owned(),
+                    "Hidden: looks like a secret".to_owned(),
+                )
+            } else {
+                (entry.preview(CHIP_CHARS), entry.preview(HOVER_CHARS))
+            };
+            let label = if entry.pinned {
+                format!("📌 {}", label)
+            } else {
+                label
+            };
+            if ui
+                .button(label)
+                .on_hover_text(format!("{}\n\nClick to copy", hover))
+                .clicked()
+            {
+                action = Some(Action::Copy(entry.id));
+            }
+        }
+        if empty {
+            ui.label(RichText::new("Nothing copied yet").weak());
+        }
+    });
+    action
+}
@@ -0,0 +1,113 @@
+//! First-run setup
+//!
+//! Shown once, when there's no data file yet, so new users pick the few
+//! settings that matter most instead of discovering them later. Everything
+//! chosen here can be changed in the settings window; skipping keeps the
+//! defaults.
+use crate::{autostart, Appearance};
+use egui::{Context, RichText};
+
+/// Choices made in the setup window
+pub struct Setup {
+    /// Light or dark appearance
+    pub appearance: Appearance,
+    /// Maximum number of entries to keep
+    pub max_entries: usize,
+    /// Launch on login
+    pub autostart: bool,
+    /// Keep running when the window is closed, brought back by the global hotkey
+    pub background: bool,
+    /// Global hotkey that shows the window, for display
+    hotkey: String,
+}
+
+impl Setup {
+    /// Start from the current (default) settings
+    pub fn new(appearance: Appearance, max_entries: usize, hotkey: &str) -> Self {
+        Self {
+            appearance,
+            max_entries,
+            autostart: false,
+            background: false,
+            hotkey: hotkey.to_owned(),
+        }
+    }
+
+    /// Show the setup window
+    ///
+    /// Returns Some(true) when the choices should be applied, Some(false) if
+    /// setup was skipped, and None while it's still open.
+    pub fn show(&mut self, ctx: &Context) -> Option<bool> {
+        let mut result = None;
+
+        egui::Window::new("👋 Welcome to Klippy")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("A few choices to get started. You can change them later in Settings.");
+                ui.add_space(10.0);
+
+                egui::Grid::new("first_run_setup")
+                    .num_columns(2)
+                    .spacing([16.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label("Appearance");
+                        ui.horizontal(|ui| {
+                            for appearance in Appearance::ALL {
+                                ui.radio_value(
+                                    &mut self.appearance,
+                                    appearance,
+                                    appearance.label(),
+                                );
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Keep up to");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut self.max_entries).range(10..=500));
+                            ui.label("entries");
+                        });
+                        ui.end_row();
+
+                        ui.label("Launch on login");
+                        ui.add_enabled(
+                            autostart::is_supported(),
+                            egui::Checkbox::without_text(&mut self.autostart),
+                        )
+                        .on_disabled_hover_text("Not supported on this platform");
+                        ui.end_row();
+
+                        ui.label("Global hotkey");
+                        ui.checkbox(
+                            &mut self.background,
+                            format!("Keep running when closed, show with {}", self.hotkey),
+                        );
+                        ui.end_row();
+                    });
+
+                ui.add_space(4.0);
+                ui.label(
+                    RichText::new("Klippy captures text as soon as it's copied")
+                        .small()
+                        .weak(),
+                );
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("✅ Start").clicked() {
+                        result = Some(true);
+                    }
+                    if ui
+                        .button("Skip")
+                        .on_hover_text("Use the defaults")
+                        .clicked()
+                    {
+                        result = Some(false);
+                    }
+                });
+            });
+
+        result
+    }
+}
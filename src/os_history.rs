@@ -0,0 +1,242 @@
+//! Import from the OS clipboard history
+//!
+//! Reads entries from clipboard histories that can be read outside of their
+//! own UI: the built-in history on Windows, and Klipper (KDE), CopyQ and
+//! cliphist on Linux. Windows reports when each entry was copied; the Linux
+//! tools only report their order (newest first).
+//!
+//! Reading can take a while, since the Linux tools are queried one entry at
+//! a time, so imports run on a background thread (see `Import`).
+use chrono::{DateTime, Local};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// Upper bound on the number of entries read from any one source
+const MAX_IMPORT: usize = 1000;
+
+/// An entry read from the OS clipboard history
+pub struct HistoryItem {
+    pub content: String,
+    /// When it was copied, if the source records it
+    pub copied_at: Option<DateTime<Local>>,
+}
+
+/// The name of the source read and its entries, newest first
+pub type History = (&'static str, Vec<HistoryItem>);
+
+/// An import reading the OS clipboard history on a background thread
+pub struct Import {
+    result: Receiver<Result<History, String>>,
+}
+
+impl Import {
+    /// Start reading the history, repainting `ctx` once it's read
+    pub fn start(ctx: &egui::Context) -> Self {
+        let (sender, result) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let _ = sender.send(read_history());
+            ctx.request_repaint();
+        });
+        Import { result }
+    }
+
+    /// Returns the history once it has been read
+    pub fn poll(&self) -> Option<Result<History, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(
+                "Reading the clipboard history stopped unexpectedly".to_owned(),
+            )),
+        }
+    }
+}
+
+/// Read the OS clipboard history, newest entry first
+///
+/// Returns a user-facing message when no supported history source exists.
+#[cfg(target_os = "linux")]
+fn read_history() -> Result<History, String> {
+    linux::read()
+}
+
+/// Read the OS clipboard history, newest entry first
+#[cfg(windows)]
+fn read_history() -> Result<History, String> {
+    windows_history::read().map(|items| ("Windows clipboard history", items))
+}
+
+/// Read the OS clipboard history, newest entry first
+#[cfg(not(any(target_os = "linux", windows)))]
+fn read_history() -> Result<History, String> {
+    Err("Importing clipboard history is not supported on this platform".to_owned())
+}
+
+/// Clipboard history tools on Linux
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{History, HistoryItem, MAX_IMPORT};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// Reads a source's history, or None if the source isn't available
+    type HistoryReader = fn() -> Option<Vec<String>>;
+
+    /// Read the first history tool found, newest entry first
+    pub fn read() -> Result<History, String> {
+        let sources: [(&'static str, HistoryReader); 3] = [
+            ("Klipper", read_klipper),
+            ("CopyQ", read_copyq),
+            ("cliphist", read_cliphist),
+        ];
+
+        sources
+            .iter()
+            .find_map(|(name, read)| read().map(|entries| (*name, entries)))
+            .map(|(name, entries)| {
+                let items = entries
+                    .into_iter()
+                    .map(|content| HistoryItem {
+                        content,
+                        copied_at: None,
+                    })
+                    .collect();
+                (name, items)
+            })
+            .ok_or_else(|| {
+                "No supported clipboard history (Klipper, CopyQ, cliphist) found".to_owned()
+            })
+    }
+
+    /// Run a command and return its stdout if it succeeded
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            None
+        }
+    }
+
+    /// Read history from KDE's Klipper over D-Bus
+    fn read_klipper() -> Option<Vec<String>> {
+        let qdbus = ["qdbus6", "qdbus"]
+            .into_iter()
+            .find(|program| run(program, &["org.kde.klipper"]).is_some())?;
+
+        let mut entries = Vec::new();
+        for i in 0..MAX_IMPORT {
+            let index = i.to_string();
+            match run(
+                qdbus,
+                &[
+                    "org.kde.klipper",
+                    "/klipper",
+                    "org.kde.klipper.klipper.getClipboardHistoryItem",
+                    &index,
+                ],
+            ) {
+                // qdbus terminates replies with a newline
+                Some(item) if !item.is_empty() => {
+                    entries.push(item.strip_suffix('\n').unwrap_or(&item).to_owned())
+                }
+                _ => break,
+            }
+        }
+        Some(entries)
+    }
+
+    /// Read history from CopyQ's command line interface
+    fn read_copyq() -> Option<Vec<String>> {
+        let count: usize = run("copyq", &["count"])?.trim().parse().ok()?;
+        let entries = (0..count.min(MAX_IMPORT))
+            .filter_map(|i| run("copyq", &["read", &i.to_string()]))
+            .collect();
+        Some(entries)
+    }
+
+    /// Read history from cliphist (wlroots compositors)
+    fn read_cliphist() -> Option<Vec<String>> {
+        let list = run("cliphist", &["list"])?;
+        let entries = list
+            .lines()
+            .take(MAX_IMPORT)
+            .filter_map(|line| {
+                let mut child = Command::new("cliphist")
+                    .arg("decode")
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .ok()?;
+                child.stdin.take()?.write_all(line.as_bytes()).ok()?;
+                let output = child.wait_with_output().ok()?;
+                String::from_utf8(output.stdout).ok()
+            })
+            .collect();
+        Some(entries)
+    }
+}
+
+/// The built-in clipboard history on Windows (Win+V)
+#[cfg(windows)]
+mod windows_history {
+    use super::{HistoryItem, MAX_IMPORT};
+    use chrono::{DateTime, Local};
+    use windows::ApplicationModel::DataTransfer::{
+        Clipboard, ClipboardHistoryItemsResultStatus, StandardDataFormats,
+    };
+
+    /// 100ns intervals between 1601-01-01, where Windows times start, and 1970-01-01
+    const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+    const TICKS_PER_SECOND: i64 = 10_000_000;
+
+    /// Read the text entries of the history, newest first
+    pub fn read() -> Result<Vec<HistoryItem>, String> {
+        let failed =
+            |e: windows::core::Error| format!("Failed to read the clipboard history: {}", e);
+        if !Clipboard::IsHistoryEnabled().map_err(failed)? {
+            return Err("Clipboard history is turned off in the Windows settings".to_owned());
+        }
+        let result = Clipboard::GetHistoryItemsAsync()
+            .and_then(|operation| operation.get())
+            .map_err(failed)?;
+        if result.Status().map_err(failed)? != ClipboardHistoryItemsResultStatus::Success {
+            return Err("Windows didn't allow reading the clipboard history".to_owned());
+        }
+        let items = result.Items().map_err(failed)?;
+        let text = StandardDataFormats::Text().map_err(failed)?;
+
+        let mut entries = Vec::new();
+        for index in 0..items.Size().map_err(failed)? {
+            if entries.len() == MAX_IMPORT {
+                break;
+            }
+            let Ok(item) = items.GetAt(index) else {
+                continue;
+            };
+            // Images and files in the history have no text to import
+            let Ok(view) = item.Content() else {
+                continue;
+            };
+            if !view.Contains(&text).unwrap_or(false) {
+                continue;
+            }
+            let Ok(content) = view.GetTextAsync().and_then(|operation| operation.get()) else {
+                continue;
+            };
+            let copied_at = item.Timestamp().ok().and_then(|time| {
+                let ticks = time.UniversalTime - UNIX_EPOCH_TICKS;
+                DateTime::from_timestamp(
+                    ticks.div_euclid(TICKS_PER_SECOND),
+                    (ticks.rem_euclid(TICKS_PER_SECOND) * 100) as u32,
+                )
+            });
+            entries.push(HistoryItem {
+                content: content.to_string_lossy(),
+                copied_at: copied_at.map(|time| time.with_timezone(&Local)),
+            });
+        }
+        Ok(entries)
+    }
+}
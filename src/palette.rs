@@ -0,0 +1,103 @@
+//! Command palette
+//!
+//! A keyboard-first list of every action, opened with Ctrl+Shift+P. Typing
+//! filters the commands (all words must appear in the label), the arrow keys
+//! move the selection and Enter runs it. The palette only handles display and
+//! selection; the commands themselves are supplied and run by the app.
+use egui::{Context, Key, Modifiers, RichText};
+
+/// Maximum number of matching commands listed at once
+const MAX_SHOWN: usize = 12;
+
+/// State of the open command palette
+#[derive(Default)]
+pub struct CommandPalette {
+    /// Filter typed by the user
+    query: String,
+    /// Position of the highlighted command among the matches
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// Returns the indices of the labels matching the typed filter
+    fn matches(&self, labels: &[String]) -> Vec<usize> {
+        let query = self.query.to_lowercase();
+        let words: Vec<&str> = query.split_whitespace().collect();
+        labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| {
+                let label = label.to_lowercase();
+                words.iter().all(|word| label.contains(word))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Show the palette over the window
+    ///
+    /// Returns whether it's still open, and the index of the label chosen
+    /// this frame, if any.
+    pub fn show(&mut self, ctx: &Context, labels: &[String]) -> (bool, Option<usize>) {
+        let matches = self.matches(labels);
+
+        // Take the navigation keys before the text field sees them
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+                i.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self
+            .selected
+            .min(matches.len().min(MAX_SHOWN).saturating_sub(1));
+
+        let mut chosen = enter.then(|| matches.get(self.selected).copied()).flatten();
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .resizable(false)
+            .fixed_size([420.0, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command…")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+
+                if matches.is_empty() {
+                    ui.label(RichText::new("No matching commands").weak());
+                }
+                for (position, &index) in matches.iter().take(MAX_SHOWN).enumerate() {
+                    if ui
+                        .selectable_label(position == self.selected, &labels[index])
+                        .clicked()
+                    {
+                        chosen = Some(index);
+                    }
+                }
+                if matches.len() > MAX_SHOWN {
+                    ui.label(
+                        RichText::new(format!("{} more…", matches.len() - MAX_SHOWN))
+                            .small()
+                            .weak(),
+                    );
+                }
+            });
+
+        (!escape && chosen.is_none(), chosen)
+    }
+}
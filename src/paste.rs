@@ -0,0 +1,25 @@
+//! Simulated paste into the focused application
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+/// Send the platform paste shortcut to whichever application has focus
+pub fn send_paste() -> Result<(), String> {
+    let mut enigo =
+        Enigo::new(&Settings::default()).map_err(|e| format!("Auto-paste unavailable: {}", e))?;
+
+    let modifier = if cfg!(target_os = "macos") {
+        Key::Meta
+    } else {
+        Key::Control
+    };
+
+    // Make sure no modifiers from the triggering hotkey are still held
+    for key in [Key::Alt, Key::Shift, Key::Control, Key::Meta] {
+        let _ = enigo.key(key, Direction::Release);
+    }
+
+    enigo
+        .key(modifier, Direction::Press)
+        .and_then(|_| enigo.key(Key::Unicode('v'), Direction::Click))
+        .and_then(|_| enigo.key(modifier, Direction::Release))
+        .map_err(|e| format!("Auto-paste failed: {}", e))
+}
@@ -0,0 +1,46 @@
+//! The PRIMARY selection
+//!
+//! X11 desktops have a second clipboard, the PRIMARY selection, which holds
+//! the last selected text and is pasted with a middle click. Other platforms
+//! have no equivalent, so everything here is a no-op there.
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+use clipboard::{
+    x11_clipboard::{Primary, X11ClipboardContext},
+    ClipboardProvider,
+};
+
+/// Connection to the PRIMARY selection
+pub struct PrimarySelection {
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+    ctx: X11ClipboardContext<Primary>,
+}
+
+impl PrimarySelection {
+    /// Returns true if this platform has a PRIMARY selection
+    pub fn is_supported() -> bool {
+        cfg!(all(
+            unix,
+            not(any(target_os = "macos", target_os = "android"))
+        ))
+    }
+
+    /// Connect to the PRIMARY selection, or None where there isn't one
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+    pub fn new() -> Option<Self> {
+        X11ClipboardContext::new().ok().map(|ctx| Self { ctx })
+    }
+
+    /// Connect to the PRIMARY selection, or None where there isn't one
+    #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android")))))]
+    pub fn new() -> Option<Self> {
+        None
+    }
+
+    /// Put text in the PRIMARY selection
+    pub fn set(&mut self, text: &str) {
+        #[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+        let _ = self.ctx.set_contents(text.to_owned());
+        #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android")))))]
+        let _ = text;
+    }
+}
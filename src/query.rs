@@ -0,0 +1,222 @@
+//! Search query parsing
+//!
+//! The search box accepts a small query language. A query is a list of
+//! whitespace-separated terms, all of which must match (implicit AND):
+//!
+//! ```text
+//! is:pinned           pinned entries
+//! type:<kind>         entries of a detected kind: text, url, path, phone, date
+//! before:YYYY-MM-DD   entries captured before that day
+//! after:YYYY-MM-DD    entries captured after that day
+//! "some words"        quoted text, matched as a single phrase
+//! anything else       case-insensitive text found in the content
+//! ```
+//!
+//! Terms with an unknown key or an invalid value (e.g. `type:banana` or
+//! `before:soon`) are treated as literal text rather than errors.
+use crate::detect::ContentKind;
+use crate::ClipboardEntry;
+use chrono::NaiveDate;
+
+/// A single search term
+#[derive(Clone, PartialEq, Debug)]
+enum Term {
+    /// Lowercased text that must appear in the content
+    Text(String),
+    /// Entry must be pinned
+    Pinned,
+    /// Entry must be of the given kind
+    Kind(ContentKind),
+    /// Entry must be captured before this day
+    Before(NaiveDate),
+    /// Entry must be captured after this day
+    After(NaiveDate),
+}
+
+/// A parsed search query
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    /// Parse a query from the search box text
+    pub fn parse(input: &str) -> Self {
+        let terms = split_words(input)
+            .into_iter()
+            .map(|(word, quoted)| {
+                if quoted {
+                    Term::Text(word.to_lowercase())
+                } else {
+                    parse_term(&word).unwrap_or_else(|| Term::Text(word.to_lowercase()))
+                }
+            })
+            .collect();
+        Self { terms }
+    }
+
+    /// Returns true if the entry matches every term
+    pub fn matches(&self, entry: &ClipboardEntry) -> bool {
+        let mut content = None;
+        self.terms.iter().all(|term| match term {
+            Term::Text(text) => content
+                .get_or_insert_with(|| entry.content.to_lowercase())
+                .contains(text.as_str()),
+            Term::Pinned => entry.pinned,
+            Term::Kind(kind) => entry.kind() == *kind,
+            Term::Before(date) => entry.timestamp.date_naive() < *date,
+            Term::After(date) => entry.timestamp.date_naive() > *date,
+        })
+    }
+
+    /// Returns the non-text terms in query syntax, e.g. `type:url`
+    pub fn filter_labels(&self) -> Vec<String> {
+        self.terms
+            .iter()
+            .filter_map(|term| match term {
+                Term::Text(_) => None,
+                Term::Pinned => Some("is:pinned".to_owned()),
+                Term::Kind(kind) => Some(format!("type:{}", kind.label())),
+                Term::Before(date) => Some(format!("before:{}", date)),
+                Term::After(date) => Some(format!("after:{}", date)),
+            })
+            .collect()
+    }
+}
+
+/// Parse a `key:value` term, or None if it isn't a valid one
+fn parse_term(word: &str) -> Option<Term> {
+    let (key, value) = word.split_once(':')?;
+    let date = || NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+
+    match key.to_lowercase().as_str() {
+        "is" if value.eq_ignore_ascii_case("pinned") => Some(Term::Pinned),
+        "type" => ContentKind::from_label(&value.to_lowercase()).map(Term::Kind),
+        "before" => date().map(Term::Before),
+        "after" => date().map(Term::After),
+        _ => None,
+    }
+}
+
+/// Split input into words, keeping double-quoted phrases together
+///
+/// Returns each word with whether it was quoted. An unterminated quote runs
+/// to the end of the input.
+fn split_words(input: &str) -> Vec<(String, bool)> {
+    let mut words = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            if !phrase.is_empty() {
+                words.push((phrase, true));
+            }
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            words.push((word, false));
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_is_pinned() {
+        assert_eq!(Query::parse("is:pinned").terms, vec![Term::Pinned]);
+        assert_eq!(Query::parse("IS:Pinned").terms, vec![Term::Pinned]);
+    }
+
+    #[test]
+    fn parses_type() {
+        assert_eq!(
+            Query::parse("type:url").terms,
+            vec![Term::Kind(ContentKind::Url)]
+        );
+        assert_eq!(
+            Query::parse("type:Phone").terms,
+            vec![Term::Kind(ContentKind::Phone)]
+        );
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(
+            Query::parse("before:2024-03-01 after:2023-12-31").terms,
+            vec![
+                Term::Before(date("2024-03-01")),
+                Term::After(date("2023-12-31"))
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_phrases_together() {
+        assert_eq!(
+            Query::parse(r#"foo "Hello World" bar"#).terms,
+            vec![
+                Term::Text("foo".to_owned()),
+                Term::Text("hello world".to_owned()),
+                Term::Text("bar".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_filters_are_text() {
+        assert_eq!(
+            Query::parse(r#""is:pinned""#).terms,
+            vec![Term::Text("is:pinned".to_owned())]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_runs_to_the_end() {
+        assert_eq!(
+            Query::parse(r#"a "b c"#).terms,
+            vec![Term::Text("a".to_owned()), Term::Text("b c".to_owned())]
+        );
+        assert_eq!(Query::parse("\"").terms, vec![]);
+    }
+
+    #[test]
+    fn unknown_and_invalid_terms_are_text() {
+        assert_eq!(
+            Query::parse("type:banana before:soon is:odd foo:bar").terms,
+            vec![
+                Term::Text("type:banana".to_owned()),
+                Term::Text("before:soon".to_owned()),
+                Term::Text("is:odd".to_owned()),
+                Term::Text("foo:bar".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_every_term() {
+        let mut entry = ClipboardEntry::new(1, "Hello https world".to_owned());
+        assert!(Query::parse("hello WORLD").matches(&entry));
+        assert!(!Query::parse("hello is:pinned").matches(&entry));
+        entry.pinned = true;
+        assert!(Query::parse("hello is:pinned").matches(&entry));
+        assert!(!Query::parse("type:url").matches(&entry));
+    }
+}
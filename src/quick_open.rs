@@ -0,0 +1,154 @@
+//! Quick open
+//!
+//! An overlay for jumping straight to an entry, opened with Ctrl+K. Typing
+//! fuzzy-matches across the whole history: the typed characters must appear
+//! in order, not necessarily together, and runs and word starts rank higher.
+//! The arrow keys move the selection and Enter copies it. Like the command
+//! palette, this only handles display and selection; copying is up to the app.
+use crate::ClipboardEntry;
+use egui::{Context, Key, Modifiers, RichText};
+
+/// Maximum number of matching entries listed at once
+const MAX_SHOWN: usize = 8;
+/// Characters of each entry searched, so huge entries stay cheap to match
+const SCAN_CHARS: usize = 2000;
+/// Characters of each entry shown in the list
+const PREVIEW_CHARS: usize = 60;
+
+/// State of the open quick open overlay
+#[derive(Default)]
+pub struct QuickOpen {
+    /// Text typed by the user
+    query: String,
+    /// Position of the highlighted entry among the matches
+    selected: usize,
+}
+
+impl QuickOpen {
+    /// Returns the ids of the entries matching the typed text, best first
+    ///
+    /// Equal scores keep the history's order, so newer entries win ties and
+    /// an empty query lists the newest entries.
+    fn matches(&self, entries: &[ClipboardEntry]) -> Vec<u64> {
+        let query: Vec<char> = self.query.trim().to_lowercase().chars().collect();
+        let mut scored: Vec<(u32, u64)> = entries
+            .iter()
+            .filter(|e| !e.cleared)
+            .filter_map(|e| score(&query, &e.content).map(|score| (score, e.id)))
+            .collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Show the overlay over the window
+    ///
+    /// Returns whether it's still open, and the id of the entry chosen this
+    /// frame, if any. Entries for which `masked` returns true are listed
+    /// without their content.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        entries: &[ClipboardEntry],
+        masked: impl Fn(&ClipboardEntry) -> bool,
+    ) -> (bool, Option<u64>) {
+        let matches = self.matches(entries);
+
+        // Take the navigation keys before the text field sees them
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+                i.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self
+            .selected
+            .min(matches.len().min(MAX_SHOWN).saturating_sub(1));
+
+        let mut chosen = enter.then(|| matches.get(self.selected).copied()).flatten();
+        egui::Window::new("Quick Open")
+            .title_bar(false)
+            .resizable(false)
+            .fixed_size([460.0, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Jump to an entry…")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+
+                if matches.is_empty() {
+                    ui.label(RichText::new("No matching entries").weak());
+                }
+                for (position, &id) in matches.iter().take(MAX_SHOWN).enumerate() {
+                    let Some(entry) = entries.iter().find(|e| e.id == id) else {
+                        continue;
+                    };
+                    let label = if masked(entry) {
+                        "••••••••••••".to_owned()
+                    } else {
+                        entry.preview(PREVIEW_CHARS)
+                    };
+                    if ui
+                        .selectable_label(position == self.selected, label)
+                        .clicked()
+                    {
+                        chosen = Some(id);
+                    }
+                }
+                if matches.len() > MAX_SHOWN {
+                    ui.label(
+                        RichText::new(format!("{} more…", matches.len() - MAX_SHOWN))
+                            .small()
+                            .weak(),
+                    );
+                }
+            });
+
+        (!escape && chosen.is_none(), chosen)
+    }
+}
+
+/// Score how well the lowercased `query` fuzzy-matches `text`, or None if
+/// its characters don't all appear in order
+///
+/// Each matched character scores a point, with a bonus when it follows the
+/// previous match directly or starts a word.
+fn score(query: &[char], text: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in text.chars().take(SCAN_CHARS) {
+        if next == query.len() {
+            break;
+        }
+        let matched = c.to_lowercase().eq(std::iter::once(query[next]));
+        if matched {
+            score += 1;
+            if previous_matched {
+                score += 4;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 6;
+            }
+            next += 1;
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    (next == query.len()).then_some(score)
+}
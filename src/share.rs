@@ -0,0 +1,83 @@
+//! Sharing entries by email
+//!
+//! There's no cross-platform share sheet to hand content to, so sharing opens
+//! a `mailto:` link in the default mail app, with the entry as the message
+//! body. Mail apps and browsers cap link lengths, so content whose link would
+//! be too long isn't shared this way; callers copy it instead.
+use std::fmt::Write;
+
+/// Longest `mailto:` link opened, in bytes, after encoding
+///
+/// Percent-encoding can triple the content's length, or more for non-ASCII
+/// text, so the limit applies to the finished link.
+const MAX_LINK_BYTES: usize = 2000;
+/// Most characters of the content used for the subject line
+const SUBJECT_CHARS: usize = 60;
+
+/// Open a new email with `content` as its body
+///
+/// Fails if the content is too long for a link or no mail app handles it.
+pub fn by_email(content: &str) -> Result<(), String> {
+    let link = mailto(content)?;
+    open::that(&link).map_err(|e| format!("No mail app to share with: {}", e))
+}
+
+/// Returns the `mailto:` link for a new email with `content` as its body, or
+/// an error if it would be too long
+fn mailto(content: &str) -> Result<String, String> {
+    let body = content.trim();
+    let first_line = body.lines().next().unwrap_or_default();
+    let mut subject: String = first_line.chars().take(SUBJECT_CHARS).collect();
+    if subject.len() < first_line.len() {
+        subject.push('…');
+    }
+    let link = format!("mailto:?subject={}&body={}", encode(&subject), encode(body));
+    if link.len() > MAX_LINK_BYTES {
+        return Err("Too long to share by email".to_owned());
+    }
+    Ok(link)
+}
+
+/// Percent-encode text for a `mailto:` query value
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_is_encoded_into_the_link() {
+        assert_eq!(
+            mailto("  Hi there\nSee you at 5 & bring snacks\n").unwrap(),
+            "mailto:?subject=Hi%20there&body=Hi%20there%0ASee%20you%20at%205%20%26%20bring%20snacks"
+        );
+    }
+
+    #[test]
+    fn long_subjects_are_shortened() {
+        let link = mailto(&"a".repeat(100)).unwrap();
+        let subject = format!("subject={}%E2%80%A6&", "a".repeat(SUBJECT_CHARS));
+        assert!(link.contains(&subject), "{}", link);
+    }
+
+    #[test]
+    fn limit_applies_to_the_encoded_link() {
+        // Short enough in bytes, but each byte encodes to three
+        let text = "é€".repeat(250);
+        assert!(text.len() < MAX_LINK_BYTES);
+        assert!(mailto(&text).is_err());
+
+        assert!(mailto(&"a".repeat(MAX_LINK_BYTES - 200)).is_ok());
+        assert!(mailto(&"a".repeat(MAX_LINK_BYTES)).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+//! In-app keyboard shortcuts
+//!
+//! Unlike global hotkeys these only fire while Klippy has focus. They're
+//! written like `Ctrl+Shift+Delete`: any of `Ctrl` (Cmd on macOS), `Shift`
+//! and `Alt`, followed by an egui key name.
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+/// Parse a shortcut such as `Ctrl+Shift+Delete`
+pub fn parse(text: &str) -> Result<KeyboardShortcut, String> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+
+    for part in text.split('+').map(str::trim) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => modifiers |= Modifiers::COMMAND,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            "alt" | "option" => modifiers |= Modifiers::ALT,
+            _ if key.is_none() => {
+                key = Some(
+                    Key::from_name(part)
+                        .ok_or_else(|| format!("Unknown key \"{}\" in shortcut {}", part, text))?,
+                );
+            }
+            _ => return Err(format!("Shortcut {} has more than one key", text)),
+        }
+    }
+
+    key.map(|key| KeyboardShortcut::new(modifiers, key))
+        .ok_or_else(|| format!("Shortcut {} has no key", text))
+}
+
+/// Write a shortcut the way `parse` reads it, e.g. `Ctrl+Shift+Delete`
+pub fn format(shortcut: &KeyboardShortcut) -> String {
+    let modifiers = shortcut.modifiers;
+    let mut parts = Vec::new();
+    if modifiers.command || modifiers.ctrl || modifiers.mac_cmd {
+        parts.push("Ctrl");
+    }
+    if modifiers.shift {
+        parts.push("Shift");
+    }
+    if modifiers.alt {
+        parts.push("Alt");
+    }
+    parts.push(shortcut.logical_key.name());
+    parts.join("+")
+}
@@ -0,0 +1,153 @@
+//! Import and export of clipboard history
+//!
+//! JSON exports can be imported again. CSV and Markdown are export-only, for
+//! use in spreadsheets and documentation. Entries can also be salvaged from a
+//! truncated data file.
+use crate::ClipboardEntry;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Timestamp format used in CSV and Markdown exports
+const EXPORT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A file format entries can be exported to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Every export format, in menu order
+    pub const ALL: [ExportFormat; 3] = [
+        ExportFormat::Json,
+        ExportFormat::Csv,
+        ExportFormat::Markdown,
+    ];
+
+    /// Name shown in menus and file dialogs
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Markdown => "Markdown",
+        }
+    }
+
+    /// File extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+/// A full Klippy data file, of which only the entries are imported
+#[derive(Deserialize)]
+struct DataFile {
+    entries: Vec<ClipboardEntry>,
+}
+
+/// Write entries to a file in the given format
+pub fn export(entries: &[ClipboardEntry], path: &Path, format: ExportFormat) -> Result<(), String> {
+    let data = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("Failed to serialize entries: {}", e))?,
+        ExportFormat::Csv => to_csv(entries),
+        ExportFormat::Markdown => to_markdown(entries),
+    };
+    fs::write(path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Read entries from a JSON export or a Klippy data file
+pub fn import_json(path: &Path) -> Result<Vec<ClipboardEntry>, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    serde_json::from_str::<Vec<ClipboardEntry>>(&data)
+        .or_else(|_| serde_json::from_str::<DataFile>(&data).map(|file| file.entries))
+        .map_err(|e| format!("{} is not a Klippy export: {}", path.display(), e))
+}
+
+/// Salvage the complete entries from a damaged JSON export or data file
+///
+/// Entries are read one at a time from the start of the entries array, up to
+/// the first one that can't be parsed, e.g. where the file was cut off.
+pub fn recover_entries(data: &str) -> Vec<ClipboardEntry> {
+    let start = if data.trim_start().starts_with('[') {
+        data.find('[')
+    } else {
+        data.find("\"entries\"")
+            .and_then(|key| data[key..].find('[').map(|offset| key + offset))
+    };
+    let Some(start) = start else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    let mut rest = &data[start + 1..];
+    loop {
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<ClipboardEntry>();
+        match stream.next() {
+            Some(Ok(entry)) => entries.push(entry),
+            _ => break,
+        }
+        rest = rest[stream.byte_offset()..].trim_start();
+        match rest.strip_prefix(',') {
+            Some(next) => rest = next,
+            None => break,
+        }
+    }
+    entries
+}
+
+/// Render entries as CSV with a header row
+fn to_csv(entries: &[ClipboardEntry]) -> String {
+    let mut csv = String::from("timestamp,pinned,content\r\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{}\r\n",
+            entry.timestamp.format(EXPORT_TIME_FORMAT),
+            entry.pinned,
+            csv_field(&entry.content)
+        ));
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Render entries as Markdown, one heading and fenced code block per entry
+fn to_markdown(entries: &[ClipboardEntry]) -> String {
+    let mut markdown = String::from("# Klippy export\n");
+    for entry in entries {
+        let pinned = if entry.pinned { " (pinned)" } else { "" };
+        // The fence must be longer than any backtick run inside the content
+        let fence = "`".repeat(longest_backtick_run(&entry.content).max(2) + 1);
+        markdown.push_str(&format!(
+            "\n## {}{}\n\n{}\n{}\n{}\n",
+            entry.timestamp.format(EXPORT_TIME_FORMAT),
+            pinned,
+            fence,
+            entry.content.trim_end_matches(['\n', '\r']),
+            fence
+        ));
+    }
+    markdown
+}
+
+/// Length of the longest run of consecutive backticks in the text
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
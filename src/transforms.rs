@@ -0,0 +1,237 @@
+//! User-defined text transforms
+//!
+//! Every `.rhai` script in the `transforms` folder next to the data file is
+//! offered in an entry's context menu, named after the file. The script sees
+//! the entry's text as `text`, and the value it ends with is added as a new
+//! entry.
+//!
+//! Scripts are sandboxed: the engine can't touch files, the network or other
+//! programs, and `import` is disabled. A script is stopped once it runs past
+//! `TIMEOUT` or builds a string larger than `MAX_STRING`. Transforms run on a
+//! worker thread, so the window keeps drawing while one is busy.
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name of the folder holding transforms, inside the data directory
+pub const FOLDER: &str = "transforms";
+/// File extension of transform scripts
+const EXTENSION: &str = "rhai";
+/// Longest a transform may run before it's stopped
+pub const TIMEOUT: Duration = Duration::from_secs(3);
+/// Largest string a transform may build, in bytes
+const MAX_STRING: usize = 32 * 1024 * 1024;
+/// Most items an array or map built by a transform may hold
+const MAX_ITEMS: usize = 1_000_000;
+
+/// A script offered as a transform
+#[derive(Clone)]
+pub struct Transform {
+    /// Shown in the menu: the file name without its extension
+    pub name: String,
+    /// Source of the script
+    script: String,
+}
+
+/// A transform running on a worker thread
+pub struct Running {
+    /// Name of the transform, for status messages
+    pub name: String,
+    result: Receiver<Result<String, String>>,
+}
+
+impl Running {
+    /// Start `transform` on `input`, repainting `ctx` once it's done
+    pub fn start(transform: &Transform, input: String, ctx: &egui::Context) -> Self {
+        let (sender, result) = mpsc::channel();
+        let ctx = ctx.clone();
+        let worker = transform.clone();
+        thread::spawn(move || {
+            let _ = sender.send(run(&worker, &input, TIMEOUT));
+            ctx.request_repaint();
+        });
+        Running {
+            name: transform.name.clone(),
+            result,
+        }
+    }
+
+    /// Returns the transform's result once it has finished
+    pub fn poll(&self) -> Option<Result<String, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(format!("{} stopped unexpectedly", self.name)))
+            }
+        }
+    }
+}
+
+/// Returns the transforms in `dir`, sorted by name
+///
+/// A missing folder just means there are none.
+pub fn load(dir: &Path) -> Vec<Transform> {
+    let Ok(files) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut transforms: Vec<Transform> = files
+        .flatten()
+        .map(|file| file.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                return None;
+            }
+            match fs::read_to_string(&path) {
+                Ok(script) => Some(Transform { name, script }),
+                Err(e) => {
+                    log::warn!("Skipping transform {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+    transforms.sort_by(|a, b| a.name.cmp(&b.name));
+    transforms
+}
+
+/// Run a transform on `input`, returning the text it produced
+///
+/// A script ending with a number or other value produces that value as
+/// text; one ending with nothing produces an empty string.
+fn run(transform: &Transform, input: &str, timeout: Duration) -> Result<String, String> {
+    let engine = sandbox(Instant::now() + timeout);
+    let mut scope = Scope::new();
+    scope.push_constant("text", input.to_owned());
+
+    let value = engine
+        .eval_with_scope::<Dynamic>(&mut scope, &transform.script)
+        .map_err(|e| match *e {
+            EvalAltResult::ErrorTerminated(..) => format!(
+                "{} took longer than {}s and was stopped",
+                transform.name,
+                timeout.as_secs_f32()
+            ),
+            EvalAltResult::ErrorDataTooLarge(..) => {
+                format!("{} produced too much data and was stopped", transform.name)
+            }
+            e => format!("{} failed: {}", transform.name, e),
+        })?;
+
+    if value.is_unit() {
+        Ok(String::new())
+    } else if value.is_string() {
+        Ok(value.into_string().unwrap_or_default())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Returns an engine with no outside access that gives up at `deadline`
+fn sandbox(deadline: Instant) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_string_size(MAX_STRING)
+        .set_max_array_size(MAX_ITEMS)
+        .set_max_map_size(MAX_ITEMS)
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {})
+        .on_progress(move |_| (Instant::now() >= deadline).then_some(Dynamic::UNIT));
+    engine
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(script: &str) -> Transform {
+        Transform {
+            name: "test".into(),
+            script: script.into(),
+        }
+    }
+
+    #[test]
+    fn script_result_becomes_the_output() {
+        let slugify = transform(r#"let slug = text.to_lower(); slug.replace(" ", "-"); slug"#);
+        assert_eq!(
+            run(&slugify, "Hello Big World", TIMEOUT),
+            Ok("hello-big-world".into())
+        );
+        assert_eq!(
+            run(&transform("text.len()"), "four", TIMEOUT),
+            Ok("4".into())
+        );
+        assert_eq!(
+            run(&transform("let x = 1;"), "text", TIMEOUT),
+            Ok(String::new())
+        );
+    }
+
+    #[test]
+    fn endless_script_is_stopped() {
+        let started = Instant::now();
+        let result = run(&transform("loop {}"), "", Duration::from_millis(100));
+        assert!(result.unwrap_err().contains("was stopped"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn runaway_string_is_stopped() {
+        let result = run(&transform("let s = text; loop { s += s; }"), "x", TIMEOUT);
+        assert!(result.unwrap_err().contains("too much data"));
+    }
+
+    #[test]
+    fn scripts_cannot_import_modules() {
+        let dir = std::env::temp_dir().join(format!("klippy-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let module = dir.join("module.rhai");
+        fs::write(&module, "export const SECRET = 42;").unwrap();
+
+        let script = format!("import {:?} as m; m::SECRET", module.with_extension(""));
+        let result = run(&transform(&script), "", TIMEOUT);
+        assert!(result.is_err(), "import succeeded: {:?}", result);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn running_transform_reports_back() {
+        let running = Running::start(
+            &transform("text + \"!\""),
+            "hi".into(),
+            &egui::Context::default(),
+        );
+        let started = Instant::now();
+        let result = loop {
+            if let Some(result) = running.poll() {
+                break result;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5), "no result");
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(result, Ok("hi!".into()));
+    }
+
+    #[test]
+    fn only_scripts_are_loaded() {
+        let dir = std::env::temp_dir().join(format!("klippy-transforms-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("upper.rhai"), "text.to_upper()").unwrap();
+        fs::write(dir.join("Reverse.rhai"), "text").unwrap();
+        fs::write(dir.join(".hidden.rhai"), "text").unwrap();
+        fs::write(dir.join("notes.txt"), "text").unwrap();
+
+        let names: Vec<_> = load(&dir).into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["Reverse", "upper"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
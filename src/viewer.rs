@@ -0,0 +1,313 @@
+//! Full-content viewer
+//!
+//! Shows the complete text of an entry in its own window. Large content is
+//! split into fixed-size chunks once, and only the chunks scrolled into view
+//! are laid out, so pathological clips don't stall the UI.
+//!
+//! Smaller content can also be rendered as Markdown, or as a table when it
+//! looks like delimited rows. JSON, even large, can be browsed as a tree of
+//! collapsible objects and arrays. That only changes how it's displayed; the
+//! entry itself is never modified.
+use crate::detect::{self, Table};
+use crate::ClipboardEntry;
+use egui::{Context, Id, OutputCommand, RichText, ScrollArea, TextStyle, Ui};
+use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
+use serde_json::Value;
+
+/// Content larger than this is displayed in chunks
+const LARGE_CONTENT_BYTES: usize = 64 * 1024;
+/// Maximum characters per displayed row in chunked mode
+const CHUNK_CHARS: usize = 200;
+/// Content larger than this isn't parsed for the tree view
+const MAX_TREE_BYTES: usize = 8 * 1024 * 1024;
+/// Most children listed under one tree node
+const MAX_TREE_CHILDREN: usize = 500;
+
+/// How the content is displayed
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ViewMode {
+    /// Plain text, as stored
+    Raw,
+    /// Rendered as formatted Markdown
+    Markdown,
+    /// Split into rows and columns
+    Table,
+    /// JSON as a tree of collapsible objects and arrays
+    Tree,
+}
+
+/// What the user asked for in the viewer this frame
+pub struct ViewerResponse {
+    /// Whether the window is still open
+    pub open: bool,
+    /// Whether the copy button was clicked
+    pub copy: bool,
+    /// Error from opening a link in rendered Markdown
+    pub error: Option<String>,
+}
+
+/// State of an open viewer window
+pub struct ContentViewer {
+    /// Id of the entry being viewed
+    pub id: u64,
+    /// Version of the entry's content being viewed
+    version: u64,
+    /// The full content being viewed
+    content: String,
+    /// Summary of the content's size, computed once when opened
+    summary: String,
+    /// Display rows for large content, split once when opened
+    chunks: Option<Vec<String>>,
+    /// Rows and columns, if the content looks tabular
+    table: Option<Table>,
+    /// Parsed JSON object or array, if the content is one
+    tree: Option<Value>,
+    /// How the content is displayed
+    mode: ViewMode,
+    /// Layout cache for rendered Markdown
+    markdown_cache: CommonMarkCache,
+}
+
+impl ContentViewer {
+    /// Open a viewer for the given entry's content
+    pub fn new(entry: &ClipboardEntry) -> Self {
+        let content = &entry.content;
+        let chunks = (content.len() > LARGE_CONTENT_BYTES).then(|| split_chunks(content));
+        let table = chunks
+            .is_none()
+            .then(|| detect::detect_table(content))
+            .flatten();
+        let tree = parse_tree(content);
+        let summary = format!(
+            "{} characters · {} lines",
+            content.chars().count(),
+            content.lines().count()
+        );
+        Self {
+            id: entry.id,
+            version: entry.version,
+            content: content.to_owned(),
+            summary,
+            chunks,
+            table,
+            tree,
+            mode: ViewMode::Raw,
+            markdown_cache: CommonMarkCache::default(),
+        }
+    }
+
+    /// Show the viewer window
+    pub fn show(&mut self, ctx: &Context) -> ViewerResponse {
+        let mut response = ViewerResponse {
+            open: true,
+            copy: false,
+            error: None,
+        };
+        let mut open = true;
+        egui::Window::new("🔍 Full Content")
+            .open(&mut open)
+            .default_size([560.0, 420.0])
+            .show(ctx, |ui| self.contents(ui, &mut response));
+        response.open = open;
+        response
+    }
+
+    /// Show the viewer inside a panel instead of its own window
+    pub fn show_inline(&mut self, ui: &mut Ui) -> ViewerResponse {
+        let mut response = ViewerResponse {
+            open: true,
+            copy: false,
+            error: None,
+        };
+        self.contents(ui, &mut response);
+        response
+    }
+
+    /// Returns true if the viewer still shows the entry's current content
+    pub fn is_for(&self, entry: &ClipboardEntry) -> bool {
+        self.id == entry.id && self.version == entry.version
+    }
+
+    /// Show the toolbar and the content in the current mode
+    fn contents(&mut self, ui: &mut Ui, response: &mut ViewerResponse) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&self.summary).weak());
+            if ui.button("📋 Copy").clicked() {
+                response.copy = true;
+            }
+            if self.chunks.is_none() || self.tree.is_some() {
+                ui.separator();
+                ui.selectable_value(&mut self.mode, ViewMode::Raw, "Raw");
+            }
+            if self.tree.is_some() {
+                ui.selectable_value(&mut self.mode, ViewMode::Tree, "🌲 Tree")
+                    .on_hover_text("Browse the JSON as collapsible objects and arrays");
+            }
+            if self.chunks.is_none() {
+                ui.selectable_value(&mut self.mode, ViewMode::Markdown, "Ⓜ Markdown")
+                    .on_hover_text("Render the content as formatted Markdown");
+                if let Some(table) = &self.table {
+                    ui.selectable_value(&mut self.mode, ViewMode::Table, "▦ Table")
+                        .on_hover_text(format!(
+                            "{} rows × {} columns, {} separated",
+                            table.rows.len(),
+                            table.rows[0].len(),
+                            table.delimiter
+                        ));
+                }
+            }
+        });
+        ui.separator();
+
+        match &self.chunks {
+            _ if self.mode == ViewMode::Tree => {
+                if let Some(tree) = &self.tree {
+                    ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
+                        show_tree(ui, "JSON", tree, Id::new(("json_tree", self.id)), true);
+                    });
+                }
+            }
+            Some(chunks) => {
+                ui.label(
+                    RichText::new("Large entry: shown in chunks, long lines are split")
+                        .small()
+                        .weak(),
+                );
+                let row_height = ui.text_style_height(&TextStyle::Monospace);
+                ScrollArea::both().auto_shrink([false; 2]).show_rows(
+                    ui,
+                    row_height,
+                    chunks.len(),
+                    |ui, range| {
+                        for chunk in &chunks[range] {
+                            ui.label(RichText::new(chunk).monospace());
+                        }
+                    },
+                );
+            }
+            None if self.mode == ViewMode::Table => {
+                if let Some(table) = &self.table {
+                    ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
+                        egui::Grid::new("content_table")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for row in &table.rows {
+                                    for cell in row {
+                                        ui.label(cell);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+            }
+            None if self.mode == ViewMode::Markdown => {
+                ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        CommonMarkViewer::new().show(ui, &mut self.markdown_cache, &self.content);
+                    });
+            }
+            None => {
+                ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.content.as_str())
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            }
+        }
+
+        // Open clicked links ourselves rather than through the integration
+        if self.mode == ViewMode::Markdown {
+            let mut urls = Vec::new();
+            ui.ctx().output_mut(|output| {
+                output.commands.retain(|command| match command {
+                    OutputCommand::OpenUrl(open) => {
+                        urls.push(open.url.clone());
+                        false
+                    }
+                    _ => true,
+                });
+            });
+            for url in urls {
+                if let Err(e) = open::that(&url) {
+                    response.error = Some(format!("Failed to open {}: {}", url, e));
+                }
+            }
+        }
+    }
+}
+
+/// Parse content that is a JSON object or array, for the tree view
+fn parse_tree(content: &str) -> Option<Value> {
+    let trimmed = content.trim_start();
+    if content.len() > MAX_TREE_BYTES || !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+        return None;
+    }
+    serde_json::from_str(content).ok()
+}
+
+/// Show a JSON value as a tree node labelled `key`
+///
+/// Objects and arrays are collapsed until opened, so only the parts being
+/// looked at are laid out.
+fn show_tree(ui: &mut Ui, key: &str, value: &Value, id: Id, open: bool) {
+    let (summary, len) = match value {
+        Value::Object(map) => (format!("{} {{{}}}", key, map.len()), map.len()),
+        Value::Array(items) => (format!("{} [{}]", key, items.len()), items.len()),
+        _ => {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("{}:", key)).strong());
+                ui.label(RichText::new(value.to_string()).monospace());
+            });
+            return;
+        }
+    };
+
+    egui::CollapsingHeader::new(summary)
+        .id_salt(id)
+        .default_open(open)
+        .show(ui, |ui| {
+            match value {
+                Value::Object(map) => {
+                    for (child_key, child) in map.iter().take(MAX_TREE_CHILDREN) {
+                        show_tree(ui, child_key, child, id.with(child_key), false);
+                    }
+                }
+                Value::Array(items) => {
+                    for (index, child) in items.iter().enumerate().take(MAX_TREE_CHILDREN) {
+                        show_tree(ui, &index.to_string(), child, id.with(index), false);
+                    }
+                }
+                _ => {}
+            }
+            if len > MAX_TREE_CHILDREN {
+                ui.label(
+                    RichText::new(format!("{} more…", len - MAX_TREE_CHILDREN))
+                        .small()
+                        .weak(),
+                );
+            }
+        });
+}
+
+/// Split content into display rows of at most `CHUNK_CHARS` characters
+fn split_chunks(content: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    for line in content.lines() {
+        if line.is_empty() {
+            chunks.push(String::new());
+            continue;
+        }
+
+        let mut chars = line.chars().peekable();
+        while chars.peek().is_some() {
+            chunks.push(chars.by_ref().take(CHUNK_CHARS).collect());
+        }
+    }
+    chunks
+}
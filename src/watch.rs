@@ -0,0 +1,64 @@
+//! Watching the data file for external changes
+//!
+//! Lets `data.json` live in a synced folder or be edited by hand while Klippy
+//! is running. The folder is watched rather than the files themselves, since
+//! many editors and sync tools replace a file instead of writing it in place.
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+
+/// Watches the data files, waking the UI when one changes
+pub struct DataWatcher {
+    /// Kept alive for as long as changes should be reported
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+}
+
+impl DataWatcher {
+    /// Start watching the data files at `paths`, which share a folder
+    pub fn new(paths: &[PathBuf], ctx: &egui::Context) -> Result<Self, String> {
+        let path = paths.first().ok_or("No data file to watch")?;
+        let dir = path
+            .parent()
+            .ok_or_else(|| format!("{} has no parent folder", path.display()))?;
+        let file_names: Vec<_> = paths
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_owned())
+            .collect();
+
+        let (sender, changes) = mpsc::channel();
+        let ctx = ctx.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .filter_map(|changed| changed.file_name())
+                        .any(|changed| file_names.iter().any(|name| name == changed));
+                if relevant {
+                    let _ = sender.send(());
+                    ctx.request_repaint();
+                }
+            })
+            .map_err(|e| format!("Failed to watch data file: {}", e))?;
+
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Returns true if a watched file changed since the last poll
+    pub fn poll(&self) -> bool {
+        self.changes.try_iter().count() > 0
+    }
+}